@@ -0,0 +1,7 @@
+//! The solver as a C library, see `include/countdown_numbers.h`. A crate of
+//! its own so only this build is a `cdylib`, and the solver itself still
+//! builds without std.
+
+extern crate countdown_numbers as solver;
+
+pub use solver::ffi::*;
@@ -0,0 +1,65 @@
+#ifndef COUNTDOWN_NUMBERS_H
+#define COUNTDOWN_NUMBERS_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/*
+ Returned by [`countdown_solve`](fn.countdown_solve.html) when a pointer
+ argument is null, fewer than two numbers are provided or a value is zero.
+ */
+#define COUNTDOWN_ERROR_INVALID_ARGUMENT -1
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Finds all solutions and writes them, rendered as strings, into a
+ caller-provided buffer.
+
+ At most `solutions_len` strings are written to `solutions`, the remaining
+ slots are set to null, even when an error is returned. Returns the total
+ number of solutions found, which may be larger than `solutions_len`, or
+ a negative error code.
+
+ # Safety
+ `numbers` must point to `numbers_len` readable values and `solutions`
+ must point to `solutions_len` writable pointers. `solutions` may be null
+ if `solutions_len` is zero. Every string written must be released with
+ `countdown_string_free` or `countdown_solutions_free`.
+ */
+int64_t countdown_solve(const uint64_t *numbers,
+                        size_t numbers_len,
+                        uint64_t target,
+                        char **solutions,
+                        size_t solutions_len);
+
+/*
+ Releases a single string returned by this library. Null is ignored.
+
+ # Safety
+ `s` must be null or a string returned by `countdown_solve` that has not
+ already been freed.
+ */
+void countdown_string_free(char *s);
+
+/*
+ Releases the first `len` strings in a buffer filled by `countdown_solve`
+ and sets each released slot to null. Null slots are skipped, and
+ `countdown_solve` nulls the slots it doesn't fill, so passing the full
+ buffer capacity it was given is fine.
+
+ # Safety
+ `solutions` must be null or point to `len` pointers, each of them null or
+ a string returned by `countdown_solve` that has not already been freed.
+ */
+void countdown_solutions_free(char **solutions, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* COUNTDOWN_NUMBERS_H */
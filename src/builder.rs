@@ -0,0 +1,155 @@
+//! Fluent configuration of a [`Solver`](struct.Solver.html), so options can
+//! be added without changing the signature of `Solver::new`.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+use clock::Clock;
+use error::Error;
+use rules::Rules;
+use solver::{Dedup, Engine, Node, Observer, SearchOrder, Solver};
+use term::{Operation, Operator};
+use value::Value;
+
+/// A setter of the solver, called once it's built.
+type Setter<V> = Box<dyn FnOnce(&mut Solver<V>)>;
+
+/// Builds a [`Solver`](struct.Solver.html), see
+/// [`Solver::builder`](struct.Solver.html#method.builder).
+pub struct SolverBuilder<V = u64> {
+    numbers: Vec<V>,
+    target: Option<V>,
+    /// Setters to call on the solver, in the order they were given
+    options: Vec<Setter<V>>,
+}
+
+impl<V: fmt::Debug> fmt::Debug for SolverBuilder<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SolverBuilder")
+            .field("numbers", &self.numbers)
+            .field("target", &self.target)
+            .field("options", &self.options.len())
+            .finish()
+    }
+}
+
+impl<V: Value + 'static> Default for SolverBuilder<V> {
+    fn default() -> SolverBuilder<V> {
+        SolverBuilder::new()
+    }
+}
+
+impl<V: Value + 'static> SolverBuilder<V> {
+    /// Builder without numbers, target or options.
+    pub fn new() -> SolverBuilder<V> {
+        SolverBuilder { numbers: Vec::new(), target: None, options: Vec::new() }
+    }
+
+    /// Starting numbers, replacing any given before.
+    pub fn numbers(mut self, numbers: &[V]) -> SolverBuilder<V> {
+        self.numbers = numbers.to_vec();
+        self
+    }
+
+    /// Target number.
+    pub fn target(mut self, target: V) -> SolverBuilder<V> {
+        self.target = Some(target);
+        self
+    }
+
+    /// See [`Solver::set_engine`](struct.Solver.html#method.set_engine).
+    pub fn engine(self, engine: Engine) -> SolverBuilder<V> {
+        self.option(move |s| s.set_engine(engine))
+    }
+
+    /// See [`Solver::set_dedup`](struct.Solver.html#method.set_dedup).
+    /// Semantic dedup is [`Dedup::Value`](enum.Dedup.html#variant.Value),
+    /// the default.
+    pub fn dedup(self, dedup: Dedup) -> SolverBuilder<V> {
+        self.option(move |s| s.set_dedup(dedup))
+    }
+
+    /// See [`Solver::set_order`](struct.Solver.html#method.set_order).
+    pub fn order(self, order: SearchOrder) -> SolverBuilder<V> {
+        self.option(move |s| s.set_order(order))
+    }
+
+    /// See [`Solver::set_max_solutions`](struct.Solver.html#method.set_max_solutions).
+    pub fn limit(self, limit: usize) -> SolverBuilder<V> {
+        self.option(move |s| s.set_max_solutions(limit))
+    }
+
+    /// See [`Solver::set_sampling`](struct.Solver.html#method.set_sampling).
+    pub fn sampling(self, seed: u64) -> SolverBuilder<V> {
+        self.option(move |s| s.set_sampling(seed))
+    }
+
+    /// See [`Solver::set_max_intermediate`](struct.Solver.html#method.set_max_intermediate).
+    pub fn max_intermediate(self, max: V) -> SolverBuilder<V> {
+        self.option(move |s| s.set_max_intermediate(max))
+    }
+
+    /// See [`Solver::set_must_use`](struct.Solver.html#method.set_must_use).
+    pub fn must_use(self, op: Operator) -> SolverBuilder<V> {
+        self.option(move |s| s.set_must_use(op))
+    }
+
+    /// See [`Solver::set_forbidden`](struct.Solver.html#method.set_forbidden).
+    pub fn forbidden(self, ops: &[Operator]) -> SolverBuilder<V> {
+        let ops = ops.to_vec();
+        self.option(move |s| s.set_forbidden(&ops[..]))
+    }
+
+    /// See [`Solver::set_rules`](struct.Solver.html#method.set_rules).
+    pub fn rules<R: Rules<V> + 'static>(self, rules: R) -> SolverBuilder<V> {
+        self.option(move |s| s.set_rules(rules))
+    }
+
+    /// See [`Solver::add_operator`](struct.Solver.html#method.add_operator),
+    /// may be given more than once.
+    pub fn operator<O: Operation<V> + 'static>(self, op: O) -> SolverBuilder<V> {
+        self.option(move |s| s.add_operator(op))
+    }
+
+    /// See [`Solver::set_clock`](struct.Solver.html#method.set_clock).
+    pub fn clock<C: Clock + 'static>(self, clock: C) -> SolverBuilder<V> {
+        self.option(move |s| s.set_clock(clock))
+    }
+
+    /// See [`Solver::set_progress`](struct.Solver.html#method.set_progress).
+    pub fn progress<F: FnMut(usize, usize) + 'static>(self, f: F) -> SolverBuilder<V> {
+        self.option(move |s| s.set_progress(f))
+    }
+
+    /// See [`Solver::set_trace`](struct.Solver.html#method.set_trace).
+    pub fn trace<F: FnMut(&Node<V>) + 'static>(self, f: F) -> SolverBuilder<V> {
+        self.option(move |s| s.set_trace(f))
+    }
+
+    /// See [`Solver::set_cancel`](struct.Solver.html#method.set_cancel).
+    pub fn cancel<F: Fn() -> bool + 'static>(self, f: F) -> SolverBuilder<V> {
+        self.option(move |s| s.set_cancel(f))
+    }
+
+    /// See [`Solver::set_observer`](struct.Solver.html#method.set_observer).
+    pub fn observer<O: Observer<V> + 'static>(self, observer: O) -> SolverBuilder<V> {
+        self.option(move |s| s.set_observer(observer))
+    }
+
+    /// The solver, failing like [`Solver::new`](struct.Solver.html#method.new)
+    /// or if no target was given.
+    pub fn build(self) -> Result<Solver<V>, Error> {
+        let target = self.target.ok_or(Error::NoTarget)?;
+        let mut solver = Solver::new(&self.numbers[..], target)?;
+        for option in self.options {
+            option(&mut solver);
+        }
+        Ok(solver)
+    }
+
+    fn option<F: FnOnce(&mut Solver<V>) + 'static>(mut self, f: F) -> SolverBuilder<V> {
+        self.options.push(Box::new(f));
+        self
+    }
+}
@@ -0,0 +1,74 @@
+//! The `advise` subcommand, recommending how many large numbers to ask
+//! for.
+
+use std::error::Error;
+
+use countdown_numbers::rules::{LARGE_NUMBERS, MAX_TARGET, MIN_TARGET};
+use countdown_numbers::Reachable;
+use rand;
+
+use cli::plain;
+use cli::random;
+
+/// What a contestant is playing for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// Reaching the target exactly, for 10 points
+    Exact,
+    /// Getting within 10, for any points at all
+    Within10,
+}
+
+/// Simulates `samples` random selections for each number of large numbers,
+/// each against every target, then prints how often each reaches the
+/// target exactly and within 10 and recommends the best for `goal`, the
+/// fewest large numbers if tied.
+pub fn advise(goal: Goal, samples: usize) -> Result<(), Box<dyn Error>> {
+    if samples == 0 {
+        return Err("at least one selection must be simulated".into());
+    }
+    let options = LARGE_NUMBERS.len() + 1;
+    let bar = plain::progress((options * samples) as u64, plain::COUNT);
+
+    let mut rng = rand::thread_rng();
+    let mut rates = Vec::new();
+    for large in 0..options {
+        let (mut exact, mut within) = (0, 0);
+        for _ in 0..samples {
+            let (numbers, _) = random::round(&mut rng, large);
+            let reachable = Reachable::new(&numbers[..]);
+            for t in MIN_TARGET..=MAX_TARGET {
+                match reachable.closest(&t).map(|v| v.abs_diff(t)) {
+                    Some(0) => { exact += 1; within += 1; },
+                    Some(1..=10) => within += 1,
+                    _ => (),
+                }
+            }
+            bar.inc(1);
+        }
+        let rounds = (samples as u64 * (MAX_TARGET - MIN_TARGET + 1)) as f64;
+        rates.push((exact as f64 / rounds, within as f64 / rounds));
+    }
+    bar.finish_and_clear();
+
+    let rate = |&(exact, within): &(f64, f64)| match goal {
+        Goal::Exact => exact,
+        Goal::Within10 => within,
+    };
+    let best = (0..options).rev()
+        .max_by(|&a, &b| rate(&rates[a]).total_cmp(&rate(&rates[b])))
+        .expect("there is always an option");
+
+    println!("large  exact  within 10");
+    for (large, &(exact, within)) in rates.iter().enumerate() {
+        println!("{:>5} {:>5.1}% {:>9.1}%{}", large, exact * 100.0, within * 100.0,
+            if large == best { "  <" } else { "" });
+    }
+    let aim = match goal {
+        Goal::Exact => "reach the target exactly",
+        Goal::Within10 => "get within 10",
+    };
+    println!("Ask for {} large to {} most often, from {} selections each.",
+        best, aim, samples);
+    Ok(())
+}
@@ -0,0 +1,284 @@
+//! The `analyze` subcommand, statistics over every selection which could
+//! occur on the show.
+
+use std::error::Error;
+
+use countdown_numbers::rules::{self, LARGE_NUMBERS, MAX_TARGET, MIN_TARGET};
+use countdown_numbers::{Reachable, Solver};
+
+use cli::export::{self, Export};
+use cli::plain;
+
+/// Furthest a declaration may be from the target and still score.
+const MAX_DISTANCE: usize = 10;
+
+/// Prints CSV with a row per number of large numbers and a column per
+/// `bin` targets, holding the chance of getting within `within` of a
+/// target in the bin, 0 for exactly. Selections are weighted by how often
+/// they're drawn, so the rates are those of rounds on the show. Every
+/// selection is also written to `export`, if given.
+pub fn heatmap(bin: u64, within: u64, export: Option<&Export>) -> Result<(), Box<dyn Error>> {
+    if bin == 0 {
+        return Err("bins must hold at least one target".into());
+    }
+    let bins = ((MAX_TARGET - MIN_TARGET) / bin + 1) as usize;
+    // Weighted exact targets and weighted targets, by large count and bin
+    let mut exact = vec![vec![0u64; bins]; LARGE_NUMBERS.len() + 1];
+    let mut total = exact.clone();
+
+    for_each_selection(export, None, |numbers, reachable| {
+        let large = numbers.iter().filter(|n| LARGE_NUMBERS.contains(n)).count();
+        let weight = rules::draws(numbers);
+        for t in MIN_TARGET..=MAX_TARGET {
+            let b = ((t - MIN_TARGET) / bin) as usize;
+            total[large][b] += weight;
+            if reachable.closest(&t).is_some_and(|v| v.abs_diff(t) <= within) {
+                exact[large][b] += weight;
+            }
+        }
+        true
+    })?;
+
+    let header = (0..bins as u64).map(|b| {
+        let low = MIN_TARGET + b * bin;
+        format!("{}-{}", low, (low + bin - 1).min(MAX_TARGET))
+    }).collect::<Vec<_>>();
+    println!("large,{}", header.join(","));
+    for (large, (exact, total)) in exact.iter().zip(&total).enumerate() {
+        let rates = exact.iter().zip(total)
+            .map(|(&e, &t)| format!("{:.3}", e as f64 / t as f64))
+            .collect::<Vec<_>>();
+        println!("{},{}", large, rates.join(","));
+    }
+    Ok(())
+}
+
+/// Number of targets `reachable` gets within each distance of, from 0 to
+/// `MAX_DISTANCE`.
+fn within(reachable: &Reachable) -> [u64; MAX_DISTANCE + 1] {
+    let mut counts = [0; MAX_DISTANCE + 1];
+    for t in MIN_TARGET..=MAX_TARGET {
+        if let Some(d) = reachable.closest(&t).map(|v| v.abs_diff(t) as usize) {
+            for count in counts.iter_mut().skip(d) {
+                *count += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Prints the share of targets reached within each distance from 0 to 10,
+/// for `numbers` or else for every selection by number of large numbers.
+/// The selections are also written to `export`, if given.
+pub fn distances(numbers: Option<&[u64]>, export: Option<&Export>)
+    -> Result<(), Box<dyn Error>>
+{
+    let targets = MAX_TARGET - MIN_TARGET + 1;
+    let (columns, counts) = match numbers {
+        Some(numbers) => {
+            let reachable = Reachable::new(numbers);
+            write(export, numbers, &reachable)?;
+            let counts = within(&reachable);
+            (vec!["share".to_string()], vec![(counts, targets)])
+        },
+        None => {
+            let mut counts = vec![([0; MAX_DISTANCE + 1], 0); LARGE_NUMBERS.len() + 2];
+            for_each_selection(export, None, |numbers, reachable| {
+                let large = numbers.iter().filter(|n| LARGE_NUMBERS.contains(n)).count();
+                let within = within(reachable);
+                for &column in [large, LARGE_NUMBERS.len() + 1].iter() {
+                    let c = &mut counts[column];
+                    for (total, n) in c.0.iter_mut().zip(&within) {
+                        *total += n;
+                    }
+                    c.1 += targets;
+                }
+                true
+            })?;
+            let mut columns = (0..=LARGE_NUMBERS.len())
+                .map(|l| format!("{} large", l)).collect::<Vec<_>>();
+            columns.push("all".to_string());
+            (columns, counts)
+        },
+    };
+
+    print!("{:<6}", "within");
+    for c in &columns {
+        print!(" {:>8}", c);
+    }
+    println!();
+    for d in 0..=MAX_DISTANCE {
+        print!("{:<6}", d);
+        for &(ref within, total) in &counts {
+            print!(" {:>7.2}%", within[d] as f64 * 100.0 / total as f64);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// Prints the figures usually published on the game, computed from
+/// scratch: how many selection and target pairs can be solved exactly, by
+/// number of large numbers and overall. Each distinct selection counts
+/// once in the pairs, the share of rounds weighs selections by how often
+/// they're drawn.
+pub fn official() -> Result<(), Box<dyn Error>> {
+    let rows = LARGE_NUMBERS.len() + 2;
+    // Selections, solvable pairs, weighted solvable and weighted pairs
+    let mut counts = vec![(0u64, 0u64, 0u64, 0u64); rows];
+    let targets = MAX_TARGET - MIN_TARGET + 1;
+
+    for_each_selection(None, None, |numbers, reachable| {
+        let large = numbers.iter().filter(|n| LARGE_NUMBERS.contains(n)).count();
+        let weight = rules::draws(numbers);
+        let solvable = (MIN_TARGET..=MAX_TARGET).filter(|t| reachable.contains(t)).count() as u64;
+        for &row in [large, rows - 1].iter() {
+            let c = &mut counts[row];
+            c.0 += 1;
+            c.1 += solvable;
+            c.2 += solvable * weight;
+            c.3 += targets * weight;
+        }
+        true
+    })?;
+
+    println!("{:<6} {:>10} {:>10} {:>10} {:>8} {:>8}",
+        "large", "selections", "pairs", "solvable", "pairs", "rounds");
+    for (row, &(selections, solvable, weighted, all)) in counts.iter().enumerate() {
+        let label = if row == rows - 1 { "all".to_string() } else { row.to_string() };
+        let pairs = selections * targets;
+        println!("{:<6} {:>10} {:>10} {:>10} {:>7.2}% {:>7.2}%", label, selections, pairs,
+            solvable, solvable as f64 * 100.0 / pairs as f64,
+            weighted as f64 * 100.0 / all as f64);
+    }
+    Ok(())
+}
+
+/// Prints how many distinct values `numbers` reach, of the targets and in
+/// all, a measure of how rich the puzzles they make are. The selection is
+/// also written to `export`, if given.
+pub fn richness(numbers: &[u64], export: Option<&Export>) -> Result<(), Box<dyn Error>> {
+    let reachable = Reachable::new(numbers);
+    write(export, numbers, &reachable)?;
+    let targets = reachable.values().filter(|v| (MIN_TARGET..=MAX_TARGET).contains(v)).count();
+    println!("{} of the {} targets from {} to {}, {} values in all", targets,
+        MAX_TARGET - MIN_TARGET + 1, MIN_TARGET, MAX_TARGET, reachable.values().len());
+    Ok(())
+}
+
+/// Upper bounds of the histogram buckets of solution counts.
+const BUCKETS: [usize; 7] = [0, 1, 5, 20, 100, 500, usize::MAX];
+
+/// Prints how the number of solutions of `numbers` is distributed across
+/// the targets: the fewest, the most, the median and a histogram. The
+/// selection and its number of solutions for each target are also written
+/// to `export`, if given.
+pub fn distribution(numbers: &[u64], export: Option<&Export>) -> Result<(), Box<dyn Error>> {
+    // A single search finds the solutions of every target
+    let targets = (MIN_TARGET..=MAX_TARGET).collect::<Vec<_>>();
+    let mut solver = Solver::new(numbers, MIN_TARGET)?;
+    let counts = solver.solve_each(&targets[..]).iter().zip(targets.iter())
+        .map(|(solutions, &t)| (solutions.len(), t))
+        .collect::<Vec<_>>();
+    if let Some(mut db) = export::open(export)? {
+        let id = db.selection(numbers, &Reachable::new(numbers))?;
+        for &(count, t) in counts.iter() {
+            db.solutions(id, t, count)?;
+        }
+        db.close()?;
+    }
+
+    let mut sorted = counts.clone();
+    sorted.sort();
+    let (fewest, fewest_target) = sorted[0];
+    let (most, most_target) = sorted[sorted.len() - 1];
+    let total: usize = counts.iter().map(|c| c.0).sum();
+    println!("fewest {} ({}), most {} ({}), median {}, mean {:.1}",
+        fewest, fewest_target, most, most_target, sorted[sorted.len() / 2].0,
+        total as f64 / counts.len() as f64);
+
+    let mut low = 0;
+    for &high in BUCKETS.iter() {
+        let n = counts.iter().filter(|&&(c, _)| c >= low && c <= high).count();
+        let label = match high {
+            h if h == low => h.to_string(),
+            usize::MAX => format!("{}+", low),
+            h => format!("{}-{}", low, h),
+        };
+        // A # per 10 targets, rounded up so no bucket looks empty by mistake
+        println!("{:>8} {:>4} {}", label, n, "#".repeat(n.div_ceil(10)));
+        low = high.saturating_add(1);
+    }
+    Ok(())
+}
+
+/// Prints the selections which can't reach `target`, stopping after
+/// `limit` if given, then how many there are. The selections seen are also
+/// written to `export`, if given.
+pub fn unsolvable(target: u64, limit: Option<usize>, export: Option<&Export>)
+    -> Result<(), Box<dyn Error>>
+{
+    let mut found = 0;
+    let mut draws = 0;
+    let complete = for_each_selection(export, Some(&[target]), |numbers, reachable| {
+        if !reachable.contains(&target) {
+            println!("{}", numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" "));
+            found += 1;
+            draws += rules::draws(numbers);
+        }
+        limit.is_none_or(|limit| found < limit)
+    })?;
+
+    if complete {
+        let all = rules::selections().iter().map(|s| rules::draws(s)).sum::<u64>();
+        println!("{} selections can't make {}, {:.2}% of rounds", found, target,
+            draws as f64 * 100.0 / all as f64);
+    }
+    Ok(())
+}
+
+/// Writes `numbers` and the values they reach to `export`, if given.
+fn write(export: Option<&Export>, numbers: &[u64], reachable: &Reachable)
+    -> Result<(), Box<dyn Error>>
+{
+    if let Some(mut db) = export::open(export)? {
+        db.selection(numbers, reachable)?;
+        db.close()?;
+    }
+    Ok(())
+}
+
+/// Calls `f` with every selection and the values it reaches, showing
+/// progress, until `f` returns false, writing each selection seen to
+/// `export` if given. Returns whether every selection was seen. Given
+/// `targets`, values are only built until they're all reached, see
+/// [`Reachable::covering`], unless every value is exported.
+fn for_each_selection<F>(export: Option<&Export>, targets: Option<&[u64]>, mut f: F)
+    -> Result<bool, Box<dyn Error>>
+    where F: FnMut(&[u64], &Reachable) -> bool
+{
+    let mut db = export::open(export)?;
+    let selections = rules::selections();
+    let bar = plain::progress(selections.len() as u64, plain::COUNT);
+    let mut complete = true;
+    for numbers in &selections {
+        let reachable = match targets {
+            Some(targets) if export.is_none() => Reachable::covering(&numbers[..], targets),
+            _ => Reachable::new(&numbers[..]),
+        };
+        if let Some(ref mut db) = db {
+            db.selection(numbers, &reachable)?;
+        }
+        let more = f(numbers, &reachable);
+        bar.inc(1);
+        if !more {
+            complete = false;
+            break;
+        }
+    }
+    bar.finish_and_clear();
+    if let Some(db) = db {
+        db.close()?;
+    }
+    Ok(complete)
+}
@@ -0,0 +1,265 @@
+//! The `bench` subcommand, timing a fixed corpus of rounds so runs of
+//! different versions or engines can be compared.
+
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use countdown_numbers::rules::{Countdown, Rules};
+use countdown_numbers::strategy::{Human, Strategy};
+use countdown_numbers::{self, Operator, Prune, Reachable, Solver};
+#[cfg(feature = "profile")]
+use countdown_numbers::profile::OrderProfile;
+#[cfg(feature = "profile")]
+use countdown_numbers::Dedup;
+#[cfg(feature = "profile")]
+use rand::{self, Rng};
+
+#[cfg(feature = "profile")]
+use cli::random;
+
+/// Representative rounds, named by what they exercise.
+const CORPUS: &[(&str, &[u64], u64)] = &[
+    ("easy", &[50, 5, 4, 3, 2, 1], 250),
+    ("hard", &[100, 75, 50, 25, 6, 3], 952),
+    ("unsolvable", &[3, 3, 2, 2, 1, 1], 999),
+    ("all-large", &[100, 75, 50, 25, 9, 8], 952),
+    ("no-large", &[9, 8, 7, 4, 3, 2], 987),
+    ("mixed", &[75, 25, 10, 9, 4, 1], 614),
+];
+
+/// Search strategies which can be benchmarked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// `Solver::solve`, enumerating every expression
+    Exhaustive,
+    /// `Solver::solve` asking the rules for every expression, rather than
+    /// checking subtraction and division for every pair of terms at once
+    Unbatched,
+    /// `Solver::solve_best`, deepening until a solution is found
+    Deepening,
+    /// `Engine::SubsetDp`, the values of every subset of the numbers
+    SubsetDp,
+    /// `Engine::MeetInTheMiddle`, the values of every subset but the whole
+    /// set
+    MeetInTheMiddle,
+    /// The `human` strategy, a bounded search which may miss solutions
+    Human,
+}
+
+/// What an engine found for a round.
+#[derive(Debug, Clone, Copy)]
+struct Outcome {
+    /// Distance of the closest value found from the target
+    distance: Option<u64>,
+    /// Solutions found, for engines which enumerate them
+    solutions: Option<usize>,
+    /// Nodes expanded and expressions evaluated, for `Solver` engines
+    work: Option<(u64, usize)>,
+}
+
+impl Engine {
+    /// Names accepted by `--engine`.
+    pub const NAMES: &'static [&'static str] =
+        &["exhaustive", "unbatched", "deepening", "subset-dp", "meet", "human"];
+
+    /// Every engine, in the order `--compare` lists them.
+    const ALL: [Engine; 6] = [Engine::Exhaustive, Engine::Unbatched, Engine::Deepening,
+        Engine::SubsetDp, Engine::MeetInTheMiddle, Engine::Human];
+
+    /// Looks up an engine by its `--engine` name.
+    pub fn from_name(name: &str) -> Option<Engine> {
+        Engine::NAMES.iter().position(|&n| n == name).map(|i| Engine::ALL[i])
+    }
+
+    fn name(self) -> &'static str {
+        Engine::NAMES[Engine::ALL.iter().position(|&e| e == self).expect("every engine is listed")]
+    }
+
+    fn run(self, numbers: &[u64], target: u64) -> Result<Outcome, Box<dyn Error>> {
+        let solve = |solver: &mut Solver| Outcome {
+            distance: solver.closest().map(|t| t.value.abs_diff(target)),
+            solutions: Some(solver.stats().solutions),
+            work: Some((solver.nodes(), solver.stats().expressions)),
+        };
+        let distance = |value: Option<u64>| Outcome {
+            distance: value.map(|v| v.abs_diff(target)),
+            solutions: None,
+            work: None,
+        };
+        Ok(match self {
+            Engine::Exhaustive => {
+                let mut solver = Solver::new(numbers, target)?;
+                solver.solve();
+                solve(&mut solver)
+            },
+            Engine::Unbatched => {
+                let mut solver = Solver::new(numbers, target)?;
+                solver.set_rules(Unbatched);
+                solver.solve();
+                solve(&mut solver)
+            },
+            Engine::Deepening => {
+                let mut solver = Solver::new(numbers, target)?;
+                solver.solve_best(1);
+                solve(&mut solver)
+            },
+            Engine::SubsetDp => {
+                let mut solver = Solver::new(numbers, target)?;
+                solver.set_engine(countdown_numbers::Engine::SubsetDp);
+                solver.solve();
+                distance(solver.closest().map(|t| t.value))
+            },
+            // Only finds exact solutions, a miss is reported as no value
+            Engine::MeetInTheMiddle => {
+                let mut solver = Solver::new(numbers, target)?;
+                solver.set_engine(countdown_numbers::Engine::MeetInTheMiddle);
+                solver.solve();
+                distance(solver.solutions().first().map(|s| s.term.value))
+            },
+            Engine::Human => distance(Human::default().play(numbers, target).map(|t| t.value)),
+        })
+    }
+
+    /// Runs the engine on a round `runs` times, returning the outcome with
+    /// the fastest and mean times.
+    fn time(self, numbers: &[u64], target: u64, runs: u32)
+        -> Result<(Outcome, Duration, Duration), Box<dyn Error>>
+    {
+        let mut best = None;
+        let mut sum = Duration::new(0, 0);
+        let mut outcome = None;
+        for _ in 0..runs {
+            let start = Instant::now();
+            outcome = Some(self.run(numbers, target)?);
+            let time = start.elapsed();
+            sum += time;
+            best = Some(best.map_or(time, |b: Duration| b.min(time)));
+        }
+        Ok((outcome.expect("at least one run"), best.expect("at least one run"), sum / runs))
+    }
+}
+
+/// The rules of the show, without telling the solver they're its
+/// arithmetic.
+struct Unbatched;
+
+impl Rules for Unbatched {
+    fn apply(&self, op: Operator, a: &u64, b: &u64) -> Result<u64, Prune> {
+        Countdown.apply(op, a, b)
+    }
+}
+
+/// Solves every round in the corpus `runs` times with `engine`, printing
+/// a table of the fastest and mean times, and the work done, per round.
+pub fn run(engine: Engine, runs: u32) -> Result<(), Box<dyn Error>> {
+    println!("{:<12} {:>6} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "round", "target", "solutions", "nodes", "exprs", "best ms", "mean ms");
+
+    let mut total_nodes = 0;
+    let mut total_time = Duration::new(0, 0);
+    for &(name, numbers, target) in CORPUS.iter() {
+        let (outcome, best, mean) = engine.time(numbers, target, runs)?;
+        let (nodes, exprs) = outcome.work.unwrap_or((0, 0));
+        total_nodes += nodes;
+        total_time += best;
+        println!("{:<12} {:>6} {:>10} {:>10} {:>10} {:>10.3} {:>10.3}",
+            name, target, or_dash(outcome.solutions), or_dash(outcome.work.map(|_| nodes)),
+            or_dash(outcome.work.map(|_| exprs)), millis(best), millis(mean));
+    }
+
+    println!();
+    if total_nodes > 0 {
+        println!("{} nodes in {:.3} ms, {:.1} million nodes per second",
+            total_nodes, millis(total_time),
+            total_nodes as f64 / total_time.as_secs_f64() / 1e6);
+    } else {
+        println!("{:.3} ms", millis(total_time));
+    }
+    Ok(())
+}
+
+/// Runs every engine on the corpus and prints a row per engine: the total
+/// of the fastest times, the nodes expanded, how many rounds it reached
+/// exactly of those which can be, how much further from the targets its
+/// answers were than the closest values, and how many rounds it gave no
+/// answer for.
+pub fn compare(runs: u32) -> Result<(), Box<dyn Error>> {
+    println!("{:<12} {:>10} {:>12} {:>8} {:>8} {:>10}",
+        "engine", "best ms", "nodes", "exact", "off by", "no answer");
+    let closest = CORPUS.iter().map(|&(_, numbers, target)| {
+        Reachable::new(numbers).closest(&target).map(|v| v.abs_diff(target))
+    }).collect::<Vec<_>>();
+    let solvable = closest.iter().filter(|&&d| d == Some(0)).count();
+
+    for &engine in Engine::ALL.iter() {
+        let mut time = Duration::new(0, 0);
+        let mut nodes = None;
+        let (mut exact, mut off, mut none) = (0, 0, 0);
+        for (&(_, numbers, target), &closest) in CORPUS.iter().zip(&closest) {
+            let (outcome, best, _) = engine.time(numbers, target, runs)?;
+            time += best;
+            if let Some((n, _)) = outcome.work {
+                nodes = Some(nodes.unwrap_or(0) + n);
+            }
+            match outcome.distance {
+                Some(0) => exact += 1,
+                Some(d) => off += d - closest.unwrap_or(d),
+                None => none += 1,
+            }
+        }
+        println!("{:<12} {:>10.3} {:>12} {:>8} {:>8} {:>10}", engine.name(), millis(time),
+            or_dash(nodes), format!("{}/{}", exact, solvable), off, none);
+    }
+    Ok(())
+}
+
+/// Solves `rounds` random rounds, counting the choices on the paths to
+/// their solutions, and prints the operators and, for each number of terms
+/// left, the pairs of them, those most often on the paths first.
+#[cfg(feature = "profile")]
+pub fn profile_ordering(rounds: usize) -> Result<(), Box<dyn Error>> {
+    let mut rng = rand::thread_rng();
+    let mut total = OrderProfile::default();
+    for _ in 0..rounds {
+        let num_big = rng.gen_range(0, 5);
+        let (numbers, target) = random::round(&mut rng, num_big);
+        let mut solver = Solver::new(&numbers[..], target)?;
+        // Every path to a solution is counted, not only the first found,
+        // so the counts don't depend on the order they were found in
+        solver.set_dedup(Dedup::None);
+        solver.set_profiling(true);
+        solver.solve();
+        total.merge(solver.profile().expect("profiling is on"));
+    }
+
+    println!("{} solutions of {} rounds", total.solutions(), rounds);
+    let ops = total.operators();
+    println!("operators: {}", ops.iter()
+        .map(|&op| format!("{} {}", op.symbol(), total.operator(op)))
+        .collect::<Vec<_>>().join(", "));
+    for terms in (2..7).rev() {
+        let mut pairs = (0..terms)
+            .flat_map(|i| (i + 1..terms).map(move |j| (i, j)))
+            .collect::<Vec<_>>();
+        pairs.sort_by_key(|&(i, j)| std::cmp::Reverse(total.pair(terms, i, j)));
+        println!("{} terms: {}", terms, pairs.iter()
+            .map(|&(i, j)| format!("({}, {}) {}", i, j, total.pair(terms, i, j)))
+            .collect::<Vec<_>>().join(", "));
+    }
+    Ok(())
+}
+
+/// Without the `profile` feature the solver doesn't count choices.
+#[cfg(not(feature = "profile"))]
+pub fn profile_ordering(_: usize) -> Result<(), Box<dyn Error>> {
+    Err("built without the profile feature, rebuild with `--features profile`".into())
+}
+
+/// `value`, or `-` for a figure an engine doesn't report.
+fn or_dash<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| v.to_string())
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1e3
+}
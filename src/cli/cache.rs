@@ -0,0 +1,78 @@
+//! The on-disk cache of search reports, so repeated queries return without
+//! searching again.
+//!
+//! Reports are stored as JSON files named by a hash of the numbers,
+//! target, options affecting the result and the versions of the crate and
+//! of `FORMAT` which produced them, in `$COUNTDOWN_CACHE_DIR`, or else `countdown-numbers` in
+//! `$XDG_CACHE_HOME` or `~/.cache`.
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use countdown_numbers::Report;
+use serde_json;
+
+use cli::fingerprint::fnv1a64;
+
+/// Version of the cached reports, part of every key. Bump it whenever the
+/// search or `Report` changes, so reports of older builds aren't served.
+const FORMAT: u32 = 2;
+
+/// Directory the cache is kept in, `None` if no home directory is known.
+pub fn dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("COUNTDOWN_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    env::var_os("XDG_CACHE_HOME").map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+        .map(|d| d.join("countdown-numbers"))
+}
+
+/// Key of a search. `options` must describe every option which changes the
+/// report. The order of `numbers` matters, since the report lists them and
+/// which of them each solution uses in the order given.
+pub fn key(numbers: &[u64], target: u64, options: &str) -> String {
+    let text = format!("{}|{}|{:?}|{}|{}",
+        env!("CARGO_PKG_VERSION"), FORMAT, numbers, target, options);
+    format!("{:016x}", fnv1a64(text.as_bytes()))
+}
+
+/// The cached report for `key`, if any. Unreadable entries are ignored.
+pub fn load(key: &str) -> Option<Report> {
+    let path = dir()?.join(format!("{}.json", key));
+    let text = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Stores a report under `key`. Failing to is only worth a note, the
+/// cache is an optimization.
+pub fn store(key: &str, report: &Report) {
+    let result = dir().ok_or_else(|| "no cache directory".into())
+        .and_then(|dir| -> Result<(), Box<dyn Error>> {
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join(format!("{}.json", key)), serde_json::to_string(report)?)?;
+            Ok(())
+        });
+    if let Err(e) = result {
+        eprintln!("note: couldn't cache the result: {}", e);
+    }
+}
+
+/// Removes every cached report, returns how many there were.
+pub fn clear() -> Result<usize, Box<dyn Error>> {
+    let dir = match dir() {
+        Some(dir) if dir.is_dir() => dir,
+        _ => return Ok(0),
+    };
+    let mut removed = 0;
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "json") {
+            fs::remove_file(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
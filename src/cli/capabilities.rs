@@ -0,0 +1,92 @@
+//! `--capabilities`, a JSON description of what this build supports, so
+//! editors, bots and web UIs can feature-detect instead of parsing
+//! `--help`.
+
+use countdown_numbers::presets::PRESETS;
+use countdown_numbers::Operator;
+
+use cli::bench::Engine;
+use cli::formats;
+use cli::lang;
+use cli::multiplayer;
+use cli::plain;
+use cli::theme;
+
+/// Version of the description, raised when a field changes meaning or is
+/// removed. New fields may be added without raising it.
+pub const VERSION: u32 = 1;
+
+/// Scoring schemes accepted by `--scoring`, `file` being a JSON or TOML
+/// file of bands.
+pub const SCORING: [&str; 4] = ["official", "linear", "linear:N", "file"];
+
+/// What this build supports.
+#[derive(Debug, Serialize)]
+pub struct Capabilities {
+    /// [`VERSION`](constant.VERSION.html)
+    pub capabilities: u32,
+    /// Version of the solver
+    pub version: &'static str,
+    /// Engines accepted by `bench --engine`
+    pub engines: &'static [&'static str],
+    /// Formats accepted by `--format`
+    pub formats: Vec<String>,
+    pub operators: Vec<OperatorInfo>,
+    /// Bundled rounds accepted by `--preset`
+    pub presets: Vec<PresetInfo>,
+    pub scoring: &'static [&'static str],
+    pub themes: &'static [&'static str],
+    pub languages: &'static [&'static str],
+    pub protocols: Protocols,
+}
+
+/// An operator, by the names it goes by.
+#[derive(Debug, Serialize)]
+pub struct OperatorInfo {
+    /// Name accepted by `--with`, `--without` and `drill --op`
+    pub name: &'static str,
+    pub symbol: &'static str,
+    /// Word `--plain` writes it as
+    pub word: &'static str,
+}
+
+/// A bundled round.
+#[derive(Debug, Serialize)]
+pub struct PresetInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub numbers: &'static [u64],
+    pub target: u64,
+}
+
+/// Versions of the protocols spoken with other programs.
+#[derive(Debug, Serialize)]
+pub struct Protocols {
+    /// JSON lines between `host` and `join`
+    pub multiplayer: u32,
+}
+
+/// Describes this build.
+pub fn describe() -> Capabilities {
+    Capabilities {
+        capabilities: VERSION,
+        version: env!("CARGO_PKG_VERSION"),
+        engines: Engine::NAMES,
+        formats: formats::names(),
+        operators: Operator::ALL.iter().map(|&op| OperatorInfo {
+            name: op.name(),
+            symbol: op.symbol(),
+            word: plain::word(op),
+        }).collect(),
+        presets: PRESETS.iter().map(|p| PresetInfo {
+            name: p.name,
+            description: p.description,
+            numbers: p.numbers,
+            target: p.target,
+        }).collect(),
+        scoring: &SCORING,
+        themes: &theme::NAMES,
+        languages: &lang::CODES,
+        protocols: Protocols { multiplayer: multiplayer::PROTOCOL },
+    }
+}
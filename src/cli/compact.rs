@@ -0,0 +1,54 @@
+//! `--format compact`, a single deterministic line per puzzle for chat
+//! bots relaying the solver's answer, such as
+//!
+//! ```text
+//! 812 = 75*(50+2)/... | exact | 14 solutions
+//! ```
+
+use std::fmt;
+
+use countdown_numbers::format::SolutionFormatter;
+use countdown_numbers::Report;
+
+/// Longest line printed, longer expressions are cut short with `...`.
+pub const MAX_LEN: usize = 160;
+
+/// The line for `report`: the target, the recommended solution or else the
+/// closest expression, how far off it is and the number of `solutions`.
+/// Plain ASCII, so it passes through any chat unchanged.
+pub fn line(report: &Report, target: u64, solutions: usize) -> String {
+    let solutions = match solutions {
+        0 => "no solutions".to_string(),
+        1 => "1 solution".to_string(),
+        n => format!("{} solutions", n),
+    };
+    let (head, distance) = match (report.recommended(), &report.closest) {
+        (Some(s), _) => (format!("{} = {}", target, s.compact()), "exact".to_string()),
+        (None, Some(c)) => (format!("{} ~ {} = {}", target, c.compact(), c.value),
+            format!("{} away", c.value.abs_diff(target))),
+        (None, None) => (target.to_string(), "unreachable".to_string()),
+    };
+
+    let tail = format!(" | {} | {}", distance, solutions);
+    let room = MAX_LEN.saturating_sub(tail.len());
+    let mut head = head;
+    if head.len() > room {
+        head.truncate(room.saturating_sub(3));
+        head.push_str("...");
+    }
+    head + &tail
+}
+
+/// `--format compact`, the [`line`](fn.line.html) for a report.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Compact;
+
+impl SolutionFormatter for Compact {
+    fn name(&self) -> &str {
+        "compact"
+    }
+
+    fn write(&self, report: &Report, out: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(out, "{}", line(report, report.target, report.stats.solutions))
+    }
+}
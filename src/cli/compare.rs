@@ -0,0 +1,65 @@
+//! The `compare` subcommand, two selections head to head across every
+//! target.
+
+use countdown_numbers::rules::{MAX_TARGET, MIN_TARGET};
+use countdown_numbers::Reachable;
+
+/// Parses a selection given as a comma separated list such as
+/// `100,75,6,3,2,1`.
+pub fn parse_selection(s: &str) -> Result<Vec<u64>, String> {
+    let numbers = s.split(',')
+        .map(|n| n.trim().parse::<u64>().ok().filter(|&n| n > 0))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| format!("`{}` is not a list of positive numbers like 100,75,6,3,2,1", s))?;
+    if numbers.len() < 2 {
+        return Err(format!("`{}` has fewer than two numbers", s));
+    }
+    Ok(numbers)
+}
+
+/// Prints how often each of `a` and `b` reaches a target exactly, their
+/// average distance from the targets, and the targets only one of them
+/// reaches.
+pub fn compare(a: &[u64], b: &[u64]) {
+    let (ra, rb) = (Reachable::new(a), Reachable::new(b));
+    let targets = (MIN_TARGET..=MAX_TARGET).collect::<Vec<_>>();
+    let name = |numbers: &[u64]| {
+        numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" ")
+    };
+
+    let mut only = [Vec::new(), Vec::new()];
+    println!("{:<20} {:>7} {:>9}", "selection", "reached", "distance");
+    for (i, (numbers, reachable)) in [(a, &ra), (b, &rb)].iter().enumerate() {
+        let other = if i == 0 { &rb } else { &ra };
+        let mut reached = 0;
+        let mut distance = 0;
+        for &t in &targets {
+            distance += reachable.closest(&t).map_or(t, |v| v.abs_diff(t));
+            if reachable.contains(&t) {
+                reached += 1;
+                if !other.contains(&t) {
+                    only[i].push(t);
+                }
+            }
+        }
+        println!("{:<20} {:>7} {:>9.2}", name(numbers), reached,
+            distance as f64 / targets.len() as f64);
+    }
+
+    for (numbers, only) in [a, b].iter().zip(&only) {
+        println!();
+        println!("Targets only {} reaches: {}", name(numbers), only.len());
+        for row in only.chunks(15) {
+            println!("  {}", row.iter().map(|t| format!("{:>3}", t)).collect::<Vec<_>>().join(" "));
+        }
+    }
+
+    let (a_wins, b_wins) = (only[0].len(), only[1].len());
+    println!();
+    if a_wins == b_wins {
+        println!("Neither selection reaches more targets.");
+    } else {
+        let winner = if a_wins > b_wins { a } else { b };
+        println!("{} reaches {} more targets.", name(winner), a_wins.abs_diff(b_wins));
+    }
+}
@@ -0,0 +1,156 @@
+//! The `curriculum` subcommand, a worksheet of puzzles growing harder for
+//! a class to work through, with an answer key.
+
+use std::error::Error;
+use std::fmt::Write;
+use std::fs;
+
+use countdown_numbers::{human, Solver};
+use rand::{self, Rng};
+
+use cli::random;
+
+/// How the difficulty climbs over the worksheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Curve {
+    /// Evenly from the easiest to the hardest
+    Linear,
+    /// Slowly at first, most of the climb near the end
+    Gentle,
+    /// Quickly at first, levelling off near the end
+    Steep,
+}
+
+impl Curve {
+    /// Names accepted by [`from_name`](#method.from_name).
+    pub const NAMES: [&'static str; 3] = ["linear", "gentle", "steep"];
+
+    /// Looks up a curve by name.
+    pub fn from_name(name: &str) -> Option<Curve> {
+        match name {
+            "linear" => Some(Curve::Linear),
+            "gentle" => Some(Curve::Gentle),
+            "steep" => Some(Curve::Steep),
+            _ => None,
+        }
+    }
+
+    /// Share of the climb made `x` of the way through, both from 0 to 1.
+    fn at(self, x: f64) -> f64 {
+        match self {
+            Curve::Linear => x,
+            Curve::Gentle => x * x,
+            Curve::Steep => x.sqrt(),
+        }
+    }
+}
+
+/// A puzzle on a worksheet with its answer.
+#[derive(Debug, Clone)]
+pub struct Exercise {
+    pub numbers: Vec<u64>,
+    pub target: u64,
+    /// Rated difficulty from 1 to 10
+    pub difficulty: u32,
+    /// The recommended solution, written out
+    pub answer: String,
+}
+
+/// Draws random rounds until it has `length` rated from `min` to `max`
+/// along `curve`. Ratings which are rarely drawn are replaced by the
+/// closest rating drawn after a while, and the puzzles are put in order of
+/// difficulty, so the worksheet never gets easier.
+pub fn generate(length: usize, min: u32, max: u32, curve: Curve)
+    -> Result<Vec<Exercise>, Box<dyn Error>>
+{
+    let wanted = (0..length).map(|i| {
+        let x = if length > 1 { i as f64 / (length - 1) as f64 } else { 0.0 };
+        min + (curve.at(x) * (max - min) as f64).round() as u32
+    }).collect::<Vec<_>>();
+    let mut slots: Vec<Option<Exercise>> = (0..length).map(|_| None).collect();
+    let mut rng = rand::thread_rng();
+
+    let mut draws = 0;
+    while slots.iter().any(Option::is_none) {
+        draws += 1;
+        if draws > length * 2000 {
+            return Err(format!("only found {} of {} puzzles rated {} to {}",
+                slots.iter().filter(|s| s.is_some()).count(), length, min, max).into());
+        }
+
+        let num_big = rng.gen_range(0, 5);
+        let (numbers, target) = random::round(&mut rng, num_big);
+        let mut solver = Solver::new(&numbers[..], target)?;
+        solver.solve();
+        let rating = match human::rate(solver.solutions().iter().map(|s| &*s.term)) {
+            Some(r) if r >= min && r <= max => r,
+            _ => continue,
+        };
+
+        let mut open = (0..length).filter(|&i| slots[i].is_none());
+        let slot = if draws < length * 50 {
+            open.find(|&i| wanted[i] == rating)
+        } else {
+            open.min_by_key(|&i| wanted[i].abs_diff(rating))
+        };
+        if let Some(i) = slot {
+            let s = solver.recommended().expect("rated rounds have solutions");
+            slots[i] = Some(Exercise {
+                answer: s.to_string(),
+                numbers,
+                target,
+                difficulty: rating,
+            });
+        }
+    }
+    let mut exercises = slots.into_iter().map(|s| s.expect("every slot is filled"))
+        .collect::<Vec<_>>();
+    exercises.sort_by_key(|e| e.difficulty);
+    Ok(exercises)
+}
+
+/// A printable worksheet, the puzzles with space for working and the
+/// answer key after them.
+pub fn text(exercises: &[Exercise]) -> String {
+    let mut s = String::new();
+    writeln!(s, "Countdown numbers worksheet").unwrap();
+    writeln!(s, "Make each target from its numbers using + - * /, each number at most once.")
+        .unwrap();
+    writeln!(s).unwrap();
+    for (i, e) in exercises.iter().enumerate() {
+        writeln!(s, "{:>3}. Make {} from {}", i + 1, e.target, join(&e.numbers, " ")).unwrap();
+        writeln!(s, "     ____________________________________________").unwrap();
+        writeln!(s).unwrap();
+    }
+    writeln!(s, "Answer key").unwrap();
+    for (i, e) in exercises.iter().enumerate() {
+        writeln!(s, "{:>3}. {}  (difficulty {})", i + 1, e.answer, e.difficulty).unwrap();
+    }
+    s
+}
+
+/// The worksheet as CSV, a row per puzzle with its answer.
+pub fn csv(exercises: &[Exercise]) -> String {
+    let mut s = String::from("puzzle,numbers,target,difficulty,answer\n");
+    for (i, e) in exercises.iter().enumerate() {
+        writeln!(s, "{},{},{},{},\"{}\"", i + 1, join(&e.numbers, " "), e.target,
+            e.difficulty, e.answer).unwrap();
+    }
+    s
+}
+
+/// Writes `worksheet` to `path`, or prints it without one.
+pub fn save(path: Option<&str>, worksheet: &str) -> Result<(), Box<dyn Error>> {
+    match path {
+        Some(path) => fs::write(path, worksheet)
+            .map_err(|e| format!("can't write worksheet {}: {}", path, e).into()),
+        None => {
+            print!("{}", worksheet);
+            Ok(())
+        },
+    }
+}
+
+fn join(numbers: &[u64], sep: &str) -> String {
+    numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(sep)
+}
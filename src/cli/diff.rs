@@ -0,0 +1,65 @@
+//! The `diff-solutions` subcommand, describing how two answers to the same
+//! puzzle differ.
+
+use std::error::Error;
+
+use countdown_numbers::cluster;
+use countdown_numbers::human;
+use countdown_numbers::{parse, Operator, Term};
+
+/// Parses `a` and `b` and prints the calculations they share, those only
+/// one of them makes, and how each adjusts its goal to the result.
+pub fn print(a: &str, b: &str) -> Result<(), Box<dyn Error>> {
+    let a: Term = parse(a).map_err(|e| format!("first expression: {}", e))?;
+    let b: Term = parse(b).map_err(|e| format!("second expression: {}", e))?;
+
+    if a.value == b.value {
+        println!("Both reach {}", a.value);
+    } else {
+        println!("The first reaches {}, the second {}", a.value, b.value);
+    }
+
+    let d = cluster::diff(&a, &b);
+    if d.only_first.is_empty() && d.only_second.is_empty() {
+        println!("They make the same calculations, only the order of operands differs");
+        return Ok(());
+    }
+    list("Both calculate", &d.shared);
+    list("Only the first calculates", &d.only_first);
+    list("Only the second calculates", &d.only_second);
+
+    let (pa, pb) = (human::plan(&a), human::plan(&b));
+    if pa.goal.value == pb.goal.value {
+        println!("Both aim for {}", pa.goal.value);
+    } else {
+        println!("The first aims for {}, the second for {}", pa.goal.value, pb.goal.value);
+    }
+    adjustments("The first", &pa);
+    adjustments("The second", &pb);
+    Ok(())
+}
+
+fn list(heading: &str, calculations: &[Term]) {
+    if calculations.is_empty() {
+        return;
+    }
+    println!("{}:", heading);
+    // Innermost first, the order they're worked out in
+    for t in calculations.iter().rev() {
+        if let Some((op, ref x, ref y)) = t.expression {
+            println!("  {} {} {} = {}", x.value, op.symbol(), y.value, t.value);
+        }
+    }
+}
+
+fn adjustments(name: &str, plan: &human::Plan) {
+    if plan.adjustments.is_empty() {
+        println!("{} makes it directly", name);
+        return;
+    }
+    let steps = plan.adjustments.iter().map(|step| {
+        let verb = if step.op == Operator::Subtraction { "subtracts" } else { "adds" };
+        format!("{} {}", verb, step.operand.value)
+    }).collect::<Vec<_>>();
+    println!("{} then {}", name, steps.join(", then "));
+}
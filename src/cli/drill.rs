@@ -0,0 +1,243 @@
+//! The `drill` subcommand, a series of timed rounds played back to back,
+//! optionally of puzzles which practise an operation.
+
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use countdown_numbers::scoring::ScoringScheme;
+use countdown_numbers::{human, parse, Operator, Solver};
+use rand::{self, Rng};
+
+use cli::leaderboard::{self, Leaderboard};
+use cli::pack::{Pack, Puzzle};
+use cli::play;
+use cli::random;
+use cli::stats::{self, Round};
+use cli::theme::{self, Role};
+
+/// The puzzles of a drill: `count` which can't be solved without `op`, or
+/// random solvable rounds without one.
+pub fn puzzles(op: Option<Operator>, count: usize) -> Result<Pack, Box<dyn Error>> {
+    match op {
+        Some(op) => generate(op, count),
+        None => Ok(Pack {
+            name: "Drill".to_string(),
+            author: None,
+            description: None,
+            puzzles: (0..count).map(|_| play::draw(None)).collect::<Result<_, _>>()?,
+        }),
+    }
+}
+
+/// Draws random rounds until it has `count` which can be solved, but not
+/// without `op`, so every solution uses it.
+pub fn generate(op: Operator, count: usize) -> Result<Pack, Box<dyn Error>> {
+    let mut rng = rand::thread_rng();
+    let mut puzzles = Vec::new();
+
+    let mut draws = 0;
+    while puzzles.len() < count {
+        draws += 1;
+        if draws > count * 2000 {
+            return Err(format!("only found {} of {} puzzles needing {}",
+                puzzles.len(), count, op.symbol()).into());
+        }
+
+        let num_big = rng.gen_range(0, 5);
+        let (numbers, target) = random::round(&mut rng, num_big);
+
+        // Most rounds can be solved without the operator, so rule those
+        // out first with the smaller search
+        let mut without = Solver::new(&numbers[..], target)?;
+        without.set_forbidden(&[op]);
+        without.solve();
+        if !without.solutions().is_empty() || !without.exhaustive() {
+            continue;
+        }
+
+        let mut solver = Solver::new(&numbers[..], target)?;
+        solver.solve();
+        let rating = match human::rate(solver.solutions().iter().map(|s| &*s.term)) {
+            Some(r) => r,
+            None => continue,
+        };
+        puzzles.push(Puzzle {
+            numbers,
+            target,
+            difficulty: Some(rating),
+            tags: vec![op.name().to_string()],
+        });
+    }
+
+    puzzles.sort_by_key(|p| p.difficulty);
+    Ok(Pack {
+        name: format!("Drill, every solution uses {}", op.symbol()),
+        author: None,
+        description: None,
+        puzzles,
+    })
+}
+
+/// Prints the puzzles of a drill, one per line.
+pub fn print(pack: &Pack) {
+    println!("{}", pack.name);
+    for (i, puzzle) in pack.puzzles.iter().enumerate() {
+        let numbers = puzzle.numbers.iter().map(|n| n.to_string())
+            .collect::<Vec<_>>().join(" ");
+        match puzzle.difficulty {
+            Some(d) => println!("{:>3}. {} from {}, difficulty {}", i + 1, puzzle.target, numbers, d),
+            None => println!("{:>3}. {} from {}", i + 1, puzzle.target, numbers),
+        }
+    }
+}
+
+/// How a timed round went.
+struct Timed {
+    /// Distance of the answer from the target, `None` without one
+    distance: Option<u64>,
+    /// Time taken to answer, the whole limit without an answer
+    taken: Duration,
+    /// Share code of the puzzle
+    code: String,
+}
+
+/// Plays the puzzles of `pack` back to back on stdin and stdout, each
+/// within `seconds`, then prints a report of the session. Answers given
+/// too late don't count, an empty line gives up on a round and end of
+/// input ends the session early. Each round is recorded in the stats file,
+/// and the session and exact solves are entered on the leaderboard for
+/// `player`.
+pub fn session(pack: &Pack, seconds: u64, scoring: &dyn ScoringScheme, player: &str)
+    -> Result<(), Box<dyn Error>>
+{
+    let lines = input();
+    let limit = Duration::from_secs(seconds);
+    let mut rounds = Vec::new();
+
+    println!("{}, {} rounds of {} seconds. Type an expression using the numbers given, \
+              or an empty line to give up.", pack.name, pack.puzzles.len(), seconds);
+
+    for (i, puzzle) in pack.puzzles.iter().enumerate() {
+        let numbers = puzzle.numbers.iter().map(|n| n.to_string())
+            .collect::<Vec<_>>().join(" ");
+        println!();
+        println!("{}", theme::paint(Role::Heading, format!("Round {} of {}: make {} from {}",
+            i + 1, pack.puzzles.len(), puzzle.target, numbers)));
+
+        let start = Instant::now();
+        let answer = loop {
+            print!("> ");
+            io::stdout().flush()?;
+            let left = limit.checked_sub(start.elapsed()).unwrap_or_default();
+            let line = match lines.recv_timeout(left) {
+                Ok(line) => line?,
+                Err(RecvTimeoutError::Timeout) => {
+                    println!();
+                    println!("  {}", theme::paint(Role::Miss, "Time's up"));
+                    break None;
+                },
+                Err(RecvTimeoutError::Disconnected) => return report(&rounds, scoring, player),
+            };
+            if line.trim().is_empty() {
+                break None;
+            }
+
+            match parse::<u64>(&line) {
+                Ok(ref term) if !term.uses_only(&puzzle.numbers[..]) => {
+                    println!("  only the numbers given may be used, each once");
+                },
+                Ok(term) => break Some(term),
+                Err(e) => println!("  {}", e),
+            }
+        };
+        let taken = start.elapsed().min(limit);
+
+        let distance = answer.as_ref().map(|a| a.value.abs_diff(puzzle.target));
+        let score = distance.map_or(0, |d| scoring.score(d));
+        let verdict = match distance {
+            Some(0) => format!("Spot on in {:.1}s, {} points", taken.as_secs_f64(), score),
+            Some(d) => format!("{} away in {:.1}s, {} points", d, taken.as_secs_f64(), score),
+            None => String::new(),
+        };
+        if let Some(d) = distance {
+            println!("  {}", theme::paint(Role::for_distance(d), verdict));
+        }
+
+        let mut round = Round::new(&puzzle.numbers, puzzle.target, puzzle.difficulty, score,
+            scoring.max());
+        round.seconds = Some(taken.as_secs_f64());
+        stats::announce(&stats::record(round, answer.as_ref()));
+        rounds.push(Timed { code: puzzle.code(), distance, taken });
+    }
+
+    report(&rounds, scoring, player)
+}
+
+/// Prints the total score, the average distance of the answers given and
+/// the slowest round, then enters the session on the leaderboard.
+fn report(rounds: &[Timed], scoring: &dyn ScoringScheme, player: &str)
+    -> Result<(), Box<dyn Error>>
+{
+    let total = rounds.iter()
+        .map(|r| r.distance.map_or(0, |d| scoring.score(d)))
+        .sum::<u32>();
+    println!();
+    println!("Scored {} of {} points in {} rounds", total, scoring.max() * rounds.len() as u32,
+        rounds.len());
+
+    let distances = rounds.iter().filter_map(|r| r.distance).collect::<Vec<_>>();
+    match distances.len() {
+        0 => println!("No answers given"),
+        n => println!("Average distance {:.1} over {} answers",
+            distances.iter().sum::<u64>() as f64 / n as f64, n),
+    }
+    if let Some((i, r)) = rounds.iter().enumerate().max_by_key(|&(_, r)| r.taken) {
+        println!("Slowest round {}, {:.1}s", i + 1, r.taken.as_secs_f64());
+    }
+
+    if rounds.is_empty() {
+        return Ok(());
+    }
+    let mut board = leaderboard::load()?;
+    enter(&mut board, rounds, scoring, player);
+    // Failing to is only worth a note, like recording the rounds
+    if let Err(e) = leaderboard::save(&board) {
+        eprintln!("note: couldn't update the leaderboard: {}", e);
+    }
+    Ok(())
+}
+
+/// Enters the session and its exact solves on the leaderboard, printing
+/// the places they make.
+fn enter(board: &mut Leaderboard, rounds: &[Timed], scoring: &dyn ScoringScheme, player: &str) {
+    let total = rounds.iter()
+        .map(|r| r.distance.map_or(0, |d| scoring.score(d)))
+        .sum::<u32>();
+    if let Some(place) = board.session(player, total, scoring.max() * rounds.len() as u32,
+        rounds.len())
+    {
+        println!("Session entered the leaderboard in place {}", place);
+    }
+    for (i, r) in rounds.iter().enumerate().filter(|(_, r)| r.distance == Some(0)) {
+        if let Some(place) = board.solve(player, r.code.clone(), r.taken.as_secs_f64()) {
+            println!("Round {} entered the fastest solves in place {}", i + 1, place);
+        }
+    }
+}
+
+/// Lines of stdin read on another thread, so waiting for one can time out.
+pub fn input() -> Receiver<io::Result<String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
@@ -0,0 +1,429 @@
+//! Exporting analysis results to a database or a file, given as
+//! `KIND:PATH` with `--export`.
+//!
+//! SQLite databases are written through `rusqlite`, with the SQLite it
+//! bundles, so the binary needs the `sqlite` feature to export. Selections
+//! are keyed by their numbers, largest first, and other rows by selection
+//! and target, writing one twice replaces it, so a resumed sweep or several
+//! commands can export to the same database.
+//!
+//! Parquet files, for loading straight into pandas or Polars, are written
+//! through the `parquet` crate with the `parquet` feature. A file is a
+//! single flat table written from scratch by each command, and is only
+//! complete once closed.
+
+use std::error::Error;
+#[cfg(feature = "parquet")]
+use std::fs::File;
+#[cfg(feature = "parquet")]
+use std::sync::Arc;
+
+#[cfg(any(feature = "sqlite", feature = "parquet"))]
+use countdown_numbers::rules::{LARGE_NUMBERS, MAX_TARGET, MIN_TARGET};
+use countdown_numbers::Reachable;
+#[cfg(feature = "parquet")]
+use parquet::basic::Compression;
+#[cfg(feature = "parquet")]
+use parquet::column::writer::ColumnWriterImpl;
+#[cfg(feature = "parquet")]
+use parquet::data_type::{ByteArray, ByteArrayType, Int32Type, Int64Type};
+#[cfg(feature = "parquet")]
+use parquet::errors::ParquetError;
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+#[cfg(feature = "parquet")]
+use parquet::file::writer::SerializedFileWriter;
+#[cfg(feature = "parquet")]
+use parquet::schema::parser::parse_message_type;
+#[cfg(feature = "sqlite")]
+use rusqlite::{params, Connection};
+
+/// Tables shared by every export. `solvability` holds the value closest to
+/// each target for each selection, `solution_counts` the number of
+/// solutions, and `summaries` counts the targets each selection reaches.
+#[cfg(feature = "sqlite")]
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS selections (
+    id INTEGER PRIMARY KEY,
+    numbers TEXT NOT NULL UNIQUE,
+    large INTEGER NOT NULL,
+    reachable INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS targets (
+    value INTEGER PRIMARY KEY
+);
+CREATE TABLE IF NOT EXISTS solvability (
+    selection INTEGER NOT NULL REFERENCES selections (id),
+    target INTEGER NOT NULL REFERENCES targets (value),
+    closest INTEGER,
+    distance INTEGER,
+    PRIMARY KEY (selection, target)
+);
+CREATE TABLE IF NOT EXISTS solution_counts (
+    selection INTEGER NOT NULL REFERENCES selections (id),
+    target INTEGER NOT NULL REFERENCES targets (value),
+    solutions INTEGER NOT NULL,
+    PRIMARY KEY (selection, target)
+);
+CREATE VIEW IF NOT EXISTS summaries AS
+    SELECT selection,
+        SUM(distance = 0) AS exact,
+        SUM(distance BETWEEN 1 AND 5) AS within_5,
+        SUM(distance BETWEEN 6 AND 10) AS within_10
+    FROM solvability GROUP BY selection;
+";
+
+/// Columns of a Parquet export, a row for each selection and target. The
+/// number of solutions is only known to `analyze distribution`.
+#[cfg(feature = "parquet")]
+const PARQUET_SCHEMA: &str = "
+message results {
+    REQUIRED BYTE_ARRAY numbers (UTF8);
+    REQUIRED INT32 large;
+    REQUIRED INT64 reachable;
+    REQUIRED INT32 target;
+    OPTIONAL INT64 closest;
+    OPTIONAL INT64 distance;
+    OPTIONAL INT64 solutions;
+}
+";
+
+/// Rows of a Parquet export held before they're written as a row group.
+#[cfg(feature = "parquet")]
+const ROW_GROUP_ROWS: usize = 1 << 20;
+
+/// Where to export results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Export {
+    /// A SQLite database at the path
+    Sqlite(String),
+    /// A Parquet file at the path
+    Parquet(String),
+}
+
+/// Parses an `--export` value, such as `sqlite:results.db` or
+/// `parquet:results.parquet`.
+pub fn parse(s: &str) -> Result<Export, String> {
+    match s.find(':').map(|i| (&s[..i], &s[i + 1..])) {
+        Some(("sqlite", path)) if !path.is_empty() => Ok(Export::Sqlite(path.to_string())),
+        Some(("parquet", path)) if !path.is_empty() => Ok(Export::Parquet(path.to_string())),
+        _ => Err(format!("invalid export '{}', expected sqlite:PATH or parquet:PATH", s)),
+    }
+}
+
+/// Opens the database or file of `export`, if given.
+pub fn open(export: Option<&Export>) -> Result<Option<Writer>, Box<dyn Error>> {
+    match export {
+        Some(Export::Sqlite(path)) => Ok(Some(Writer::Sqlite(Sqlite::open(path)?))),
+        Some(Export::Parquet(path)) => Ok(Some(Writer::Parquet(Box::new(Parquet::open(path)?)))),
+        None => Ok(None),
+    }
+}
+
+/// An open export of either kind.
+pub enum Writer {
+    Sqlite(Sqlite),
+    Parquet(Box<Parquet>),
+}
+
+impl Writer {
+    /// Records a selection and the value it gets closest to each target
+    /// with, returning its id.
+    pub fn selection(&mut self, numbers: &[u64], reachable: &Reachable)
+        -> Result<i64, Box<dyn Error>>
+    {
+        match *self {
+            Writer::Sqlite(ref mut db) => db.selection(numbers, reachable),
+            Writer::Parquet(ref mut file) => file.selection(numbers, reachable),
+        }
+    }
+
+    /// Records that selection `id` has `count` solutions for `target`.
+    pub fn solutions(&mut self, id: i64, target: u64, count: usize)
+        -> Result<(), Box<dyn Error>>
+    {
+        match *self {
+            Writer::Sqlite(ref mut db) => db.solutions(id, target, count),
+            Writer::Parquet(ref mut file) => file.solutions(id, target, count),
+        }
+    }
+
+    /// Commits everything written so far, where the kind allows it.
+    pub fn commit(&mut self) -> Result<(), Box<dyn Error>> {
+        match *self {
+            Writer::Sqlite(ref mut db) => db.commit(),
+            // A Parquet file is only complete once closed
+            Writer::Parquet(_) => Ok(()),
+        }
+    }
+
+    /// Writes out everything and closes the export.
+    pub fn close(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            Writer::Sqlite(db) => db.close(),
+            Writer::Parquet(file) => file.close(),
+        }
+    }
+}
+
+/// An open SQLite database. Writes are batched in a transaction until
+/// [`commit`](#method.commit).
+#[cfg(feature = "sqlite")]
+pub struct Sqlite {
+    conn: Connection,
+}
+
+#[cfg(feature = "sqlite")]
+impl Sqlite {
+    /// Opens or creates the database at `path` and the tables, filling in
+    /// the targets.
+    pub fn open(path: &str) -> Result<Sqlite, Box<dyn Error>> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        conn.execute_batch("BEGIN")?;
+        {
+            let mut insert = conn.prepare("INSERT OR IGNORE INTO targets VALUES (?1)")?;
+            for t in MIN_TARGET..=MAX_TARGET {
+                insert.execute(params![t as i64])?;
+            }
+        }
+        let mut db = Sqlite { conn };
+        db.commit()?;
+        Ok(db)
+    }
+
+    /// Records a selection and the value it gets closest to each target
+    /// with, returning its id.
+    pub fn selection(&mut self, numbers: &[u64], reachable: &Reachable)
+        -> Result<i64, Box<dyn Error>>
+    {
+        let mut sorted = numbers.to_vec();
+        sorted.sort_by(|a, b| b.cmp(a));
+        let text = sorted.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" ");
+        let large = numbers.iter().filter(|n| LARGE_NUMBERS.contains(n)).count();
+
+        let id = self.conn.prepare_cached(
+            "INSERT INTO selections (numbers, large, reachable) VALUES (?1, ?2, ?3)
+             ON CONFLICT (numbers) DO UPDATE SET reachable = excluded.reachable
+             RETURNING id")?
+            .query_row(params![text, large as i64, reachable.values().len() as i64],
+                |row| row.get(0))?;
+
+        let mut insert = self.conn.prepare_cached(
+            "INSERT OR REPLACE INTO solvability VALUES (?1, ?2, ?3, ?4)")?;
+        for t in MIN_TARGET..=MAX_TARGET {
+            let closest = reachable.closest(&t).cloned();
+            insert.execute(params![id, t as i64, closest.map(|c| c as i64),
+                closest.map(|c| c.abs_diff(t) as i64)])?;
+        }
+        Ok(id)
+    }
+
+    /// Records that selection `id` has `count` solutions for `target`.
+    pub fn solutions(&mut self, id: i64, target: u64, count: usize)
+        -> Result<(), Box<dyn Error>>
+    {
+        self.conn.prepare_cached("INSERT OR REPLACE INTO solution_counts VALUES (?1, ?2, ?3)")?
+            .execute(params![id, target as i64, count as i64])?;
+        Ok(())
+    }
+
+    /// Commits everything written so far.
+    pub fn commit(&mut self) -> Result<(), Box<dyn Error>> {
+        self.conn.execute_batch("COMMIT; BEGIN")?;
+        Ok(())
+    }
+
+    /// Commits and closes the database.
+    pub fn close(self) -> Result<(), Box<dyn Error>> {
+        self.conn.execute_batch("COMMIT")?;
+        self.conn.close().map_err(|(_, e)| e)?;
+        Ok(())
+    }
+}
+
+/// Without the `sqlite` feature no database can be opened, so there are
+/// none to write to.
+#[cfg(not(feature = "sqlite"))]
+pub enum Sqlite {}
+
+#[cfg(not(feature = "sqlite"))]
+impl Sqlite {
+    pub fn open(_: &str) -> Result<Sqlite, Box<dyn Error>> {
+        Err("built without the sqlite feature, rebuild with `--features sqlite`".into())
+    }
+
+    pub fn selection(&mut self, _: &[u64], _: &Reachable) -> Result<i64, Box<dyn Error>> {
+        match *self {}
+    }
+
+    pub fn solutions(&mut self, _: i64, _: u64, _: usize) -> Result<(), Box<dyn Error>> {
+        match *self {}
+    }
+
+    pub fn commit(&mut self) -> Result<(), Box<dyn Error>> {
+        match *self {}
+    }
+
+    pub fn close(self) -> Result<(), Box<dyn Error>> {
+        match self {}
+    }
+}
+
+/// An open Parquet file. The rows of recent selections are held until
+/// there are enough for a row group.
+#[cfg(feature = "parquet")]
+pub struct Parquet {
+    writer: SerializedFileWriter<File>,
+    /// Id of the first selection held
+    first: i64,
+    /// Numbers, large numbers and values reached of each selection held
+    selections: Vec<(String, i32, i64)>,
+    /// Closest value and number of solutions for each target of each
+    /// selection held, in order
+    targets: Vec<(Option<u64>, Option<usize>)>,
+}
+
+#[cfg(feature = "parquet")]
+impl Parquet {
+    /// Creates the file at `path`, replacing any there.
+    pub fn open(path: &str) -> Result<Parquet, Box<dyn Error>> {
+        let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = SerializedFileWriter::new(File::create(path)?, schema, Arc::new(properties))?;
+        Ok(Parquet { writer, first: 0, selections: Vec::new(), targets: Vec::new() })
+    }
+
+    /// Holds the rows of a selection, returning its id, its place in the
+    /// file.
+    pub fn selection(&mut self, numbers: &[u64], reachable: &Reachable)
+        -> Result<i64, Box<dyn Error>>
+    {
+        if self.targets.len() >= ROW_GROUP_ROWS {
+            self.flush()?;
+        }
+        let mut sorted = numbers.to_vec();
+        sorted.sort_by(|a, b| b.cmp(a));
+        let text = sorted.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" ");
+        let large = numbers.iter().filter(|n| LARGE_NUMBERS.contains(n)).count();
+        self.selections.push((text, large as i32, reachable.values().len() as i64));
+        for t in MIN_TARGET..=MAX_TARGET {
+            self.targets.push((reachable.closest(&t).cloned(), None));
+        }
+        Ok(self.first + self.selections.len() as i64 - 1)
+    }
+
+    /// Fills in the number of solutions of selection `id` for `target`,
+    /// which must be one of those held.
+    pub fn solutions(&mut self, id: i64, target: u64, count: usize)
+        -> Result<(), Box<dyn Error>>
+    {
+        let per_selection = (MAX_TARGET - MIN_TARGET + 1) as usize;
+        let row = (id - self.first) as usize * per_selection + (target - MIN_TARGET) as usize;
+        match self.targets.get_mut(row) {
+            Some(row) if id >= self.first => row.1 = Some(count),
+            _ => return Err(format!("selection {} has already been written", id).into()),
+        }
+        Ok(())
+    }
+
+    /// Writes the rows held as a row group.
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.selections.is_empty() {
+            return Ok(());
+        }
+        let rows = self.targets.len();
+        let mut numbers = Vec::with_capacity(rows);
+        let (mut large, mut reachable, mut targets) =
+            (Vec::with_capacity(rows), Vec::with_capacity(rows), Vec::with_capacity(rows));
+        let (mut closest, mut distance, mut solutions) =
+            (Nullable::default(), Nullable::default(), Nullable::default());
+        let per_selection = self.targets.chunks((MAX_TARGET - MIN_TARGET + 1) as usize);
+        for (s, row) in self.selections.iter().zip(per_selection) {
+            for (t, &(c, n)) in (MIN_TARGET..=MAX_TARGET).zip(row) {
+                numbers.push(ByteArray::from(s.0.as_str()));
+                large.push(s.1);
+                reachable.push(s.2);
+                targets.push(t as i32);
+                closest.push(c.map(|c| c as i64));
+                distance.push(c.map(|c| c.abs_diff(t) as i64));
+                solutions.push(n.map(|n| n as i64));
+            }
+        }
+
+        let mut group = self.writer.next_row_group()?;
+        let mut column = 0;
+        while let Some(mut writer) = group.next_column()? {
+            match column {
+                0 => writer.typed::<ByteArrayType>().write_batch(&numbers, None, None)?,
+                1 => writer.typed::<Int32Type>().write_batch(&large, None, None)?,
+                2 => writer.typed::<Int64Type>().write_batch(&reachable, None, None)?,
+                3 => writer.typed::<Int32Type>().write_batch(&targets, None, None)?,
+                4 => closest.write(writer.typed())?,
+                5 => distance.write(writer.typed())?,
+                _ => solutions.write(writer.typed())?,
+            };
+            writer.close()?;
+            column += 1;
+        }
+        group.close()?;
+
+        self.first += self.selections.len() as i64;
+        self.selections.clear();
+        self.targets.clear();
+        Ok(())
+    }
+
+    /// Writes the rows held and the footer, completing the file.
+    pub fn close(mut self) -> Result<(), Box<dyn Error>> {
+        self.flush()?;
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+/// Values of an optional column, and whether each row has one.
+#[cfg(feature = "parquet")]
+#[derive(Default)]
+struct Nullable {
+    values: Vec<i64>,
+    levels: Vec<i16>,
+}
+
+#[cfg(feature = "parquet")]
+impl Nullable {
+    fn push(&mut self, value: Option<i64>) {
+        self.levels.push(i16::from(value.is_some()));
+        self.values.extend(value);
+    }
+
+    fn write(&self, writer: &mut ColumnWriterImpl<Int64Type>) -> Result<usize, ParquetError> {
+        writer.write_batch(&self.values, Some(&self.levels), None)
+    }
+}
+
+/// Without the `parquet` feature no file can be written, so there are
+/// none to write to.
+#[cfg(not(feature = "parquet"))]
+pub enum Parquet {}
+
+#[cfg(not(feature = "parquet"))]
+impl Parquet {
+    pub fn open(_: &str) -> Result<Parquet, Box<dyn Error>> {
+        Err("built without the parquet feature, rebuild with `--features parquet`".into())
+    }
+
+    pub fn selection(&mut self, _: &[u64], _: &Reachable) -> Result<i64, Box<dyn Error>> {
+        match *self {}
+    }
+
+    pub fn solutions(&mut self, _: i64, _: u64, _: usize) -> Result<(), Box<dyn Error>> {
+        match *self {}
+    }
+
+    pub fn close(self) -> Result<(), Box<dyn Error>> {
+        match self {}
+    }
+}
@@ -0,0 +1,32 @@
+//! `--fingerprint`, a short hash identifying a set of solutions.
+
+use countdown_numbers::Term;
+
+/// Hash of the set of solutions, independent of the order they were found
+/// in and of swapped operands. Each solution is put in its canonical form,
+/// the forms are sorted and deduplicated, then their text is hashed with
+/// 64-bit FNV-1a, which is the same on every platform and version.
+pub fn fingerprint<'a, I>(solutions: I) -> String
+    where I: IntoIterator<Item = &'a Term>
+{
+    let mut set = solutions.into_iter()
+        .map(|s| s.clone().canonical())
+        .collect::<Vec<_>>();
+    set.sort();
+    set.dedup();
+
+    let text = set.iter()
+        .map(|s| format!("{} = {}\n", s, s.value))
+        .collect::<String>();
+    format!("fnv1a64:{:016x}", fnv1a64(text.as_bytes()))
+}
+
+/// 64-bit FNV-1a hash of `bytes`.
+pub fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,41 @@
+//! The formats of `--format` besides text, which prints a report of the
+//! search rather than only the solutions.
+
+use std::fmt;
+
+use countdown_numbers::format::{Registry, SolutionFormatter};
+use countdown_numbers::Report;
+use serde_json;
+
+use cli::compact::Compact;
+use cli::tsv::Tsv;
+
+/// `--format json`, the report of the search.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json;
+
+impl SolutionFormatter for Json {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn write(&self, report: &Report, out: &mut dyn fmt::Write) -> fmt::Result {
+        let json = serde_json::to_string_pretty(report).map_err(|_| fmt::Error)?;
+        writeln!(out, "{}", json)
+    }
+}
+
+/// The built-in formats of the library and those of the command line,
+/// `tsv` set up as given.
+pub fn registry(tsv: Tsv) -> Registry {
+    let mut registry = Registry::default();
+    registry.register(Json);
+    registry.register(Compact);
+    registry.register(tsv);
+    registry
+}
+
+/// Names of the formats, for `--format`.
+pub fn names() -> Vec<String> {
+    registry(Tsv::default()).names().map(str::to_string).collect()
+}
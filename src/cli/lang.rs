@@ -0,0 +1,191 @@
+//! The message catalog, the rules and the text of play mode and `--teach`
+//! in each language.
+//!
+//! Messages are templates whose `{}` are filled in order by
+//! [`fill`](fn.fill.html), or `{0}`, `{1}`... by position where a language
+//! orders them differently. The language is set with `--lang`, or else
+//! read from `$COUNTDOWN_LANG` or `$LANG`, English by default.
+
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use countdown_numbers::RULES;
+
+/// Every message in one language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Catalog {
+    /// Code selecting the language, as in `$LANG`
+    pub code: &'static str,
+    pub rules: &'static str,
+
+    // Play mode
+    pub intro_pack: &'static str,
+    pub intro_rounds: &'static str,
+    pub puzzle_heading: &'static str,
+    pub round_heading: &'static str,
+    pub round_heading_rated: &'static str,
+    pub make: &'static str,
+    pub only_numbers: &'static str,
+    pub spot_on: &'static str,
+    pub away: &'static str,
+    pub opponent: &'static str,
+    pub opponent_nothing: &'static str,
+    pub best: &'static str,
+    pub nothing_to_make: &'static str,
+    pub scored: &'static str,
+    pub opponent_scored: &'static str,
+
+    // --teach
+    pub how_to: &'static str,
+    pub aim_short: &'static str,
+    pub aim_past: &'static str,
+    pub reach_directly: &'static str,
+    pub starting_number: &'static str,
+    pub make_as: &'static str,
+    pub add: &'static str,
+    pub subtract: &'static str,
+    pub add_made: &'static str,
+    pub subtract_made: &'static str,
+    pub no_solution: &'static str,
+}
+
+/// Every language, English first.
+pub const CATALOGS: [Catalog; 2] = [
+    Catalog {
+        code: "en",
+        rules: RULES,
+
+        intro_pack: "{}, {} puzzles. Type an expression using the numbers given, \
+                     or an empty line to give up.",
+        intro_rounds: "{} rounds. Type an expression using the numbers given, \
+                       or an empty line to give up.",
+        puzzle_heading: "Puzzle {} of {}: ",
+        round_heading: "Round {} of {}: ",
+        round_heading_rated: "Round {} of {}, difficulty {}: ",
+        make: "make {} from {}",
+        only_numbers: "only the numbers given may be used, each once",
+        spot_on: "{} is spot on, {} points",
+        away: "{} is {} away, {} points",
+        opponent: "Opponent: {}, {} points",
+        opponent_nothing: "Opponent: nothing",
+        best: "Best: {}",
+        nothing_to_make: "There's nothing to make",
+        scored: "Scored {} of {} points",
+        opponent_scored: "Opponent scored {} of {} points",
+
+        how_to: "How to find {} = {}:",
+        aim_short: "Aim for {} = {}, which is {} short of the target {}",
+        aim_past: "Aim for {} = {}, which is {} past the target {}",
+        reach_directly: "Reach {} directly as {}",
+        starting_number: "{} is one of the starting numbers",
+        make_as: "Make {} as {}",
+        add: "Add {} to reach {}",
+        subtract: "Subtract {} to reach {}",
+        add_made: "Add {}, made as {}, to reach {}",
+        subtract_made: "Subtract {}, made as {}, to reach {}",
+        no_solution: "No solution to explain",
+    },
+    Catalog {
+        code: "fr",
+        rules: "\
+Les règles du jeu des chiffres sont les suivantes :
+
+Le candidat choisit six nombres parmi deux groupes, 20 petits
+nombres et 4 grands nombres. Les petits nombres sont deux
+exemplaires de chaque nombre de 1 à 10. Les 4 grands nombres
+sont 25, 50, 75 et 100. Le candidat décide combien de grands
+nombres prendre, d'aucun aux quatre, les autres étant des
+petits nombres.
+
+Un compte de trois chiffres est tiré au hasard. Les candidats
+ont 30 secondes pour trouver une suite de calculs avec les
+nombres dont le résultat final est aussi proche du compte que
+possible. Seules les quatre opérations de base sont permises,
+l'addition, la soustraction, la multiplication et la division,
+et il n'est pas obligatoire d'utiliser les six nombres. Les
+fractions sont interdites, et chaque résultat intermédiaire
+doit être un entier positif.
+",
+
+        intro_pack: "{}, {} problèmes. Tapez un calcul avec les nombres donnés, \
+                     ou une ligne vide pour abandonner.",
+        intro_rounds: "{} manches. Tapez un calcul avec les nombres donnés, \
+                       ou une ligne vide pour abandonner.",
+        puzzle_heading: "Problème {} sur {} : ",
+        round_heading: "Manche {} sur {} : ",
+        round_heading_rated: "Manche {} sur {}, difficulté {} : ",
+        make: "trouvez {} avec {}",
+        only_numbers: "seuls les nombres donnés sont permis, chacun une fois",
+        spot_on: "{} : le compte est bon, {} points",
+        away: "{} : à {} du compte, {} points",
+        opponent: "Adversaire : {}, {} points",
+        opponent_nothing: "Adversaire : rien",
+        best: "Meilleur : {}",
+        nothing_to_make: "Il n'y a rien à trouver",
+        scored: "{} points marqués sur {}",
+        opponent_scored: "L'adversaire a marqué {} points sur {}",
+
+        how_to: "Comment trouver {} = {} :",
+        aim_short: "Visez {} = {}, soit {} de moins que le compte {}",
+        aim_past: "Visez {} = {}, soit {} de plus que le compte {}",
+        reach_directly: "Obtenez {} directement avec {}",
+        starting_number: "{} est l'un des nombres de départ",
+        make_as: "Faites {} avec {}",
+        add: "Ajoutez {} pour obtenir {}",
+        subtract: "Retirez {} pour obtenir {}",
+        add_made: "Ajoutez {}, fait avec {}, pour obtenir {}",
+        subtract_made: "Retirez {}, fait avec {}, pour obtenir {}",
+        no_solution: "Aucune solution à expliquer",
+    },
+];
+
+/// Language codes, for `--lang`.
+pub const CODES: [&str; 2] = [CATALOGS[0].code, CATALOGS[1].code];
+
+/// Looks up a language by code, or by a locale such as `fr_FR.UTF-8`.
+pub fn find(code: &str) -> Option<&'static Catalog> {
+    CATALOGS.iter().find(|c| code == c.code
+        || (code.starts_with(c.code) && code[c.code.len()..].starts_with(['_', '-', '.'])))
+}
+
+/// The language asked for by `$COUNTDOWN_LANG`, or else by `$LANG` if it's
+/// one there's a catalog for.
+pub fn from_env() -> Option<String> {
+    env::var("COUNTDOWN_LANG").ok()
+        .or_else(|| env::var("LANG").ok().filter(|l| find(l).is_some()))
+}
+
+/// The language in use.
+static CURRENT: OnceLock<&'static Catalog> = OnceLock::new();
+
+/// Uses `catalog` from now on. Only the first call has an effect.
+pub fn set(catalog: &'static Catalog) {
+    let _ = CURRENT.set(catalog);
+}
+
+/// The messages of the language in use, English if none was set.
+pub fn text() -> &'static Catalog {
+    CURRENT.get().cloned().unwrap_or(&CATALOGS[0])
+}
+
+/// `template` with each `{}` replaced by the next of `args`, and each
+/// `{N}` by the `N`th from 0.
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        filled.push_str(&rest[..open]);
+        let close = open + rest[open..].find('}').expect("placeholders are closed");
+        let i = match &rest[open + 1..close] {
+            "" => next,
+            n => n.parse().expect("placeholders are numbered"),
+        };
+        filled.push_str(&args[i].to_string());
+        next = i + 1;
+        rest = &rest[close + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
@@ -0,0 +1,148 @@
+//! The local leaderboard, the best drill sessions and fastest exact solves
+//! of everyone playing on the machine.
+//!
+//! Stored as JSON in `$COUNTDOWN_LEADERBOARD_FILE`, or else
+//! `leaderboard.json` next to the [stats file](../stats/index.html).
+
+use std::cmp::Ordering;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json;
+
+use cli::stats;
+
+/// Places kept in each table.
+pub const PLACES: usize = 10;
+
+/// The best sessions and fastest solves, best first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+    #[serde(default)]
+    pub solves: Vec<Solve>,
+}
+
+/// A drill session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub player: String,
+    /// Points scored
+    pub score: u32,
+    /// Points which could have been scored
+    pub max: u32,
+    /// Number of rounds played
+    pub rounds: usize,
+    /// When the session ended, in seconds since the Unix epoch
+    pub played: u64,
+}
+
+/// An exact solve in a timed round.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Solve {
+    pub player: String,
+    /// [Share code](../pack/struct.Puzzle.html#method.code) of the puzzle
+    pub code: String,
+    /// Seconds taken
+    pub seconds: f64,
+    /// When the puzzle was solved, in seconds since the Unix epoch
+    pub played: u64,
+}
+
+impl Session {
+    /// Higher share of the points first, then more points.
+    fn rank(&self, other: &Session) -> Ordering {
+        (other.score as u64 * self.max as u64).cmp(&(self.score as u64 * other.max as u64))
+            .then(other.score.cmp(&self.score))
+    }
+}
+
+impl Leaderboard {
+    /// Enters a session, returns its place from 1 if it made the table.
+    pub fn session(&mut self, player: &str, score: u32, max: u32, rounds: usize)
+        -> Option<usize>
+    {
+        let session = Session { player: player.to_string(), score, max, rounds, played: now() };
+        let place = self.sessions.iter().position(|s| session.rank(s) == Ordering::Less)
+            .unwrap_or(self.sessions.len());
+        self.sessions.insert(place, session);
+        self.sessions.truncate(PLACES);
+        if place < PLACES { Some(place + 1) } else { None }
+    }
+
+    /// Enters an exact solve, returns its place from 1 if it made the
+    /// table.
+    pub fn solve(&mut self, player: &str, code: String, seconds: f64) -> Option<usize> {
+        let place = self.solves.iter().position(|s| seconds < s.seconds)
+            .unwrap_or(self.solves.len());
+        let solve = Solve { player: player.to_string(), code, seconds, played: now() };
+        self.solves.insert(place, solve);
+        self.solves.truncate(PLACES);
+        if place < PLACES { Some(place + 1) } else { None }
+    }
+
+    /// Prints both tables.
+    pub fn print(&self) {
+        println!("Best sessions");
+        if self.sessions.is_empty() {
+            println!("  none yet, play a drill");
+        }
+        for (i, s) in self.sessions.iter().enumerate() {
+            println!("{:>3}. {:<16} {:>4} of {:<4} {:>3} rounds", i + 1, s.player, s.score, s.max,
+                s.rounds);
+        }
+        println!();
+        println!("Fastest exact solves");
+        if self.solves.is_empty() {
+            println!("  none yet");
+        }
+        for (i, s) in self.solves.iter().enumerate() {
+            println!("{:>3}. {:<16} {:>6.1}s  {}", i + 1, s.player, s.seconds, s.code);
+        }
+    }
+}
+
+/// Name to enter on the leaderboard unless one is given, the user's login
+/// name.
+pub fn player() -> String {
+    env::var("USER").or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "anonymous".to_string())
+}
+
+/// Path of the leaderboard file, `None` if no home directory is known.
+pub fn path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("COUNTDOWN_LEADERBOARD_FILE") {
+        return Some(PathBuf::from(path));
+    }
+    stats::path().map(|p| p.with_file_name("leaderboard.json"))
+}
+
+/// The leaderboard, empty if there is none yet.
+pub fn load() -> Result<Leaderboard, Box<dyn Error>> {
+    let path = match path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok(Leaderboard::default()),
+    };
+    let text = fs::read_to_string(&path)
+        .map_err(|e| format!("can't read leaderboard {}: {}", path.display(), e))?;
+    serde_json::from_str(&text)
+        .map_err(|e| format!("can't read leaderboard {}: {}", path.display(), e).into())
+}
+
+/// Writes the leaderboard back.
+pub fn save(leaderboard: &Leaderboard) -> Result<(), Box<dyn Error>> {
+    let path = path().ok_or("no home directory for the leaderboard file")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(leaderboard)? + "\n")
+        .map_err(|e| format!("can't write leaderboard {}: {}", path.display(), e).into())
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,93 @@
+//! The man page printed by the hidden `mangen` command, assembled from
+//! the help of each command so it can't drift from the interface.
+
+use clap::App;
+
+/// Renders a roff man page for the interface built by `app`, with a
+/// section for each subcommand and the rules of the game.
+pub fn render(app: fn() -> App<'static, 'static>, rules: &str) -> String {
+    let name = app().get_name().to_string();
+    let mut page = String::new();
+
+    page.push_str(&format!(".TH {} 1 \"\" \"{} {}\"\n",
+        name.to_uppercase(), name, env!("CARGO_PKG_VERSION")));
+    page.push_str(".SH NAME\n");
+    page.push_str(&format!("{} \\- Countdown Numbers Game solver\n", escape(&name)));
+
+    page.push_str(".SH DESCRIPTION\n");
+    preformatted(&mut page, &help(app, &[]));
+
+    let mut commands = subcommands(&help(app, &[]))
+        .into_iter().map(|c| vec![c]).collect::<Vec<_>>();
+    if !commands.is_empty() {
+        page.push_str(".SH COMMANDS\n");
+    }
+    while !commands.is_empty() {
+        let path = commands.remove(0);
+        let text = help(app, &path);
+        page.push_str(&format!(".SS {}\n", escape(&path.join(" "))));
+        preformatted(&mut page, &text);
+        for (i, sub) in subcommands(&text).into_iter().enumerate() {
+            let mut child = path.clone();
+            child.push(sub);
+            commands.insert(i, child);
+        }
+    }
+
+    page.push_str(".SH RULES\n");
+    for paragraph in rules.split("\n\n") {
+        page.push_str(".PP\n");
+        page.push_str(&escape(paragraph.trim()));
+        page.push('\n');
+    }
+    page
+}
+
+/// Help text of the command at `path`, as printed by `--help`.
+fn help(app: fn() -> App<'static, 'static>, path: &[String]) -> String {
+    let mut args = vec![app().get_name().to_string()];
+    args.extend(path.iter().cloned());
+    args.push("--help".to_string());
+    match app().get_matches_from_safe(args) {
+        Err(e) => e.message,
+        Ok(_) => String::new(),
+    }
+}
+
+/// Names listed under `SUBCOMMANDS:` in a help text, except `help`.
+fn subcommands(help: &str) -> Vec<String> {
+    help.lines()
+        .skip_while(|l| !l.starts_with("SUBCOMMANDS:"))
+        .skip(1)
+        .take_while(|l| l.starts_with(' '))
+        .filter_map(|l| l.split_whitespace().next())
+        .filter(|&name| name != "help")
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Appends `text` as a block which keeps its line breaks.
+fn preformatted(page: &mut String, text: &str) {
+    page.push_str(".nf\n");
+    for line in text.lines() {
+        page.push_str(&escape(line));
+        page.push('\n');
+    }
+    page.push_str(".fi\n");
+}
+
+/// Escapes text for roff, so backslashes, hyphens and lines starting with
+/// a control character are printed as is.
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|l| {
+            let l = l.replace('\\', "\\e").replace('-', "\\-");
+            if l.starts_with('.') || l.starts_with('\'') {
+                format!("\\&{}", l)
+            } else {
+                l
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,59 @@
+//! Machine-readable metrics written by `--metrics`.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+
+use countdown_numbers::Stats;
+use serde_json;
+
+/// Metrics of a single run, serialized as JSON.
+#[derive(Debug, Serialize)]
+pub struct Metrics<'a> {
+    /// Version of the solver which produced the metrics
+    pub version: &'static str,
+    /// Starting numbers
+    pub numbers: &'a [u64],
+    /// Target number
+    pub target: u64,
+    /// Number of solutions found
+    pub solutions: usize,
+    /// Search statistics
+    pub stats: &'a Stats,
+    /// Time from start to exit, in seconds
+    pub wall_time: f64,
+    /// Time spent in each phase, in seconds
+    pub phases: Phases,
+    /// Peak resident memory in kB, if the platform reports it
+    pub memory_high_water_kb: Option<u64>,
+}
+
+/// Time spent in each phase of a run, in seconds.
+#[derive(Debug, Default, Serialize)]
+pub struct Phases {
+    /// Argument parsing, validation and solver setup
+    pub setup: f64,
+    /// The search itself
+    pub solve: f64,
+    /// Printing results
+    pub output: f64,
+}
+
+/// Peak resident memory of this process in kB, read from `/proc` on Linux.
+pub fn memory_high_water_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status.lines()
+        .find(|l| l.starts_with("VmHWM:"))
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse().ok())
+}
+
+/// Write metrics as JSON to a file, or to stderr if the path is `-`.
+pub fn write(path: &str, metrics: &Metrics) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(metrics)?;
+    if path == "-" {
+        writeln!(io::stderr(), "{}", json)
+    } else {
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", json)
+    }
+}
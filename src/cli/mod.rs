@@ -0,0 +1,39 @@
+//! Parts of the command line interface which aren't part of the library.
+
+pub mod advise;
+pub mod analyze;
+pub mod bench;
+pub mod cache;
+pub mod capabilities;
+pub mod compact;
+pub mod compare;
+pub mod curriculum;
+pub mod diff;
+pub mod drill;
+pub mod lang;
+pub mod leaderboard;
+pub mod export;
+pub mod fingerprint;
+pub mod formats;
+pub mod man;
+pub mod metrics;
+pub mod multiplayer;
+pub mod one_away;
+pub mod pack;
+pub mod plain;
+pub mod play;
+pub mod preset;
+pub mod progress;
+pub mod random;
+pub mod scan;
+pub mod scoring;
+pub mod simulate;
+pub mod stats;
+pub mod sweep;
+pub mod table;
+pub mod teach;
+pub mod theme;
+pub mod trace;
+pub mod tsv;
+pub mod verify;
+pub mod watch;
@@ -0,0 +1,320 @@
+//! The `host` and `join` subcommands, playing rounds together over the
+//! network.
+//!
+//! The protocol is a JSON [`Message`](enum.Message.html) per line over
+//! TCP. A player joins with `hello`, giving the version of the protocol it
+//! speaks, and is sent `rejected` if the host speaks another or it takes
+//! too long to say hello. Then for each round the host sends
+//! the same `round` to every player, takes one `answer` from each before
+//! the clock runs out, verifies and scores them all and sends everyone
+//! the `results`. A `done` with the totals ends the game.
+
+use std::cmp::Reverse;
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use countdown_numbers::scoring::ScoringScheme;
+use countdown_numbers::{parse, Solver};
+use serde_json;
+
+use cli::drill;
+use cli::play;
+use cli::plain;
+use cli::theme::{self, Role};
+
+/// Version of the protocol, raised with each incompatible change.
+pub const PROTOCOL: u32 = 2;
+
+/// Longest the host waits for a new connection to say hello, so one which
+/// never does can't keep the others from joining.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Extra time the host allows for answers to arrive over the network.
+const GRACE: Duration = Duration::from_secs(1);
+
+/// A line of the protocol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// A player joining, sent once. Players from before versions were sent
+    /// speak version 1.
+    Hello {
+        name: String,
+        #[serde(default = "first_protocol")]
+        protocol: u32,
+    },
+    /// A player the host turned away, and why
+    Rejected { reason: String },
+    /// The next round, sent to every player at once
+    Round { round: usize, rounds: usize, numbers: Vec<u64>, target: u64, seconds: u64 },
+    /// A player's answer to the current round
+    Answer { expression: String },
+    /// Everyone's verified answers to the round just played
+    Results { scores: Vec<Score>, best: Option<String> },
+    /// The end of the game, with everyone's total
+    Done { totals: Vec<Total> },
+}
+
+fn first_protocol() -> u32 {
+    1
+}
+
+/// A player's answer to a round, as the host verified and scored it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub name: String,
+    /// The answer sent, `None` if none arrived in time
+    pub answer: Option<String>,
+    /// Value of the answer, `None` if there's none or it breaks the rules
+    pub value: Option<u64>,
+    pub points: u32,
+}
+
+/// A player's score over the game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Total {
+    pub name: String,
+    pub points: u32,
+}
+
+/// A connected player.
+struct Player {
+    name: String,
+    stream: TcpStream,
+    total: u32,
+}
+
+fn send(stream: &mut TcpStream, message: &Message) -> io::Result<()> {
+    let line = serde_json::to_string(message).map_err(io::Error::other)?;
+    writeln!(stream, "{}", line)
+}
+
+/// Hosts a game on `port`: waits for `players` to join, then plays
+/// `rounds` random rounds of `seconds` each with them.
+pub fn host(port: u16, players: usize, rounds: usize, seconds: u64,
+    scoring: &dyn ScoringScheme) -> Result<(), Box<dyn Error>>
+{
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    println!("Hosting on port {}, waiting for {} players", port, players);
+
+    // Every answer arrives on one channel, tagged with the player's index
+    let (tx, rx) = mpsc::channel();
+    let mut joined = Vec::new();
+    while joined.len() < players {
+        let (mut stream, addr) = listener.accept()?;
+        let (name, reader) = match hello(&mut stream) {
+            Ok(hello) => hello,
+            Err(e) => {
+                eprintln!("note: turned {} away: {}", addr, e);
+                let _ = send(&mut stream, &Message::Rejected { reason: e.to_string() });
+                continue;
+            },
+        };
+        println!("{} joined from {}", name, addr);
+
+        let (i, tx) = (joined.len(), tx.clone());
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let message = match line.map(|l| serde_json::from_str::<Message>(&l)) {
+                    Ok(Ok(message)) => message,
+                    Ok(Err(_)) => continue,
+                    Err(_) => break,
+                };
+                if tx.send((i, message)).is_err() {
+                    break;
+                }
+            }
+        });
+        joined.push(Player { name, stream, total: 0 });
+    }
+
+    let limit = Duration::from_secs(seconds) + GRACE;
+    for round in 1..=rounds {
+        let puzzle = play::draw(None)?;
+        println!();
+        println!("{}", theme::paint(Role::Heading, format!("Round {} of {}: make {} from {:?}",
+            round, rounds, puzzle.target, puzzle.numbers)));
+        let message = Message::Round {
+            round,
+            rounds,
+            numbers: puzzle.numbers.clone(),
+            target: puzzle.target,
+            seconds,
+        };
+        // Answers left over from the last round are too late
+        while rx.try_recv().is_ok() {}
+        broadcast(&mut joined, &message);
+
+        let start = Instant::now();
+        let mut answers: Vec<Option<String>> = vec![None; joined.len()];
+        while answers.iter().any(Option::is_none) {
+            let left = limit.checked_sub(start.elapsed()).unwrap_or_default();
+            match rx.recv_timeout(left) {
+                Ok((i, Message::Answer { expression })) if answers[i].is_none() => {
+                    answers[i] = Some(expression);
+                },
+                Ok(_) => (),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let mut scores = Vec::new();
+        for (player, answer) in joined.iter_mut().zip(answers) {
+            let value = answer.as_ref()
+                .and_then(|a| parse::<u64>(a).ok())
+                .filter(|t| t.uses_only(&puzzle.numbers[..]))
+                .map(|t| t.value);
+            let points = value.map_or(0, |v| scoring.score(v.abs_diff(puzzle.target)));
+            player.total += points;
+            scores.push(Score { name: player.name.clone(), answer, value, points });
+        }
+
+        let mut solver = Solver::new(&puzzle.numbers[..], puzzle.target)?;
+        solver.solve();
+        let best = solver.recommended().map(|s| &*s.term).or(solver.closest())
+            .map(|t| format!("{} = {}", plain::expression(t), t.value));
+        print_scores(&scores, puzzle.target, best.as_deref());
+        broadcast(&mut joined, &Message::Results { scores, best });
+    }
+
+    let totals = joined.iter()
+        .map(|p| Total { name: p.name.clone(), points: p.total })
+        .collect::<Vec<_>>();
+    print_totals(&totals);
+    broadcast(&mut joined, &Message::Done { totals });
+    Ok(())
+}
+
+/// Reads the `hello` of a new connection, waiting at most `HELLO_TIMEOUT`.
+/// Returns the player's name and a reader for the rest of the connection.
+fn hello(stream: &mut TcpStream) -> Result<(String, BufReader<TcpStream>), Box<dyn Error>> {
+    stream.set_read_timeout(Some(HELLO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| match e.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+            format!("didn't say hello within {} seconds", HELLO_TIMEOUT.as_secs())
+        },
+        _ => format!("no hello: {}", e),
+    })?;
+    let name = match serde_json::from_str(&line) {
+        Ok(Message::Hello { name, protocol }) if protocol == PROTOCOL => name,
+        Ok(Message::Hello { protocol, .. }) => {
+            return Err(format!("speaks version {} of the protocol, the host {}",
+                protocol, PROTOCOL).into());
+        },
+        _ => return Err("didn't say hello".into()),
+    };
+    stream.set_read_timeout(None)?;
+    Ok((name, reader))
+}
+
+/// Sends `message` to every player. Players who left are only noted.
+fn broadcast(players: &mut [Player], message: &Message) {
+    for p in players.iter_mut() {
+        if let Err(e) = send(&mut p.stream, message) {
+            eprintln!("note: couldn't reach {}: {}", p.name, e);
+        }
+    }
+}
+
+/// Joins the game hosted at `address` as `name`, answering each round from
+/// stdin. An empty line gives up on a round.
+pub fn join(address: &str, name: &str) -> Result<(), Box<dyn Error>> {
+    let mut stream = TcpStream::connect(address)
+        .map_err(|e| format!("can't connect to {}: {}", address, e))?;
+    send(&mut stream, &Message::Hello { name: name.to_string(), protocol: PROTOCOL })?;
+    println!("Joined {} as {}, waiting for the game to start", address, name);
+
+    let lines = drill::input();
+    // Target of the round being played, to show how close answers are
+    let mut current = 0;
+    let reader = BufReader::new(stream.try_clone()?);
+    for line in reader.lines() {
+        let message: Message = serde_json::from_str(&line?)?;
+        match message {
+            Message::Round { round, rounds, numbers, target, seconds } => {
+                current = target;
+                let numbers_str = numbers.iter().map(|n| n.to_string())
+                    .collect::<Vec<_>>().join(" ");
+                println!();
+                println!("{}", theme::paint(Role::Heading,
+                    format!("Round {} of {}, {} seconds: make {} from {}",
+                        round, rounds, seconds, target, numbers_str)));
+
+                // Lines typed between rounds aren't answers to this one
+                while lines.try_recv().is_ok() {}
+                let limit = Duration::from_secs(seconds);
+                let start = Instant::now();
+                let answer = loop {
+                    print!("> ");
+                    io::stdout().flush()?;
+                    let left = limit.checked_sub(start.elapsed()).unwrap_or_default();
+                    let line = match lines.recv_timeout(left) {
+                        Ok(line) => line?,
+                        Err(_) => {
+                            println!();
+                            println!("  {}", theme::paint(Role::Miss, "Time's up"));
+                            break None;
+                        },
+                    };
+                    if line.trim().is_empty() {
+                        break None;
+                    }
+                    match parse::<u64>(&line) {
+                        Ok(ref term) if !term.uses_only(&numbers[..]) => {
+                            println!("  only the numbers given may be used, each once");
+                        },
+                        Ok(_) => break Some(line),
+                        Err(e) => println!("  {}", e),
+                    }
+                };
+                if let Some(expression) = answer {
+                    send(&mut stream, &Message::Answer { expression })?;
+                    println!("  Sent, waiting for the others");
+                }
+            },
+            Message::Results { scores, best } => print_scores(&scores, current, best.as_deref()),
+            Message::Done { totals } => {
+                print_totals(&totals);
+                return Ok(());
+            },
+            Message::Rejected { reason } => {
+                return Err(format!("the host turned us away: {}", reason).into());
+            },
+            Message::Hello { .. } | Message::Answer { .. } => (),
+        }
+    }
+    Err("the host left before the game ended".into())
+}
+
+/// Prints everyone's answer to the round for `target` and the best.
+fn print_scores(scores: &[Score], target: u64, best: Option<&str>) {
+    for s in scores {
+        match (&s.answer, s.value) {
+            (Some(a), Some(v)) => println!("  {:<16} {}", s.name,
+                theme::paint(Role::for_distance(v.abs_diff(target)),
+                    format!("{} = {}, {} points", a, v, s.points))),
+            (Some(a), None) => println!("  {:<16} {}", s.name,
+                theme::paint(Role::Miss, format!("{}, not allowed", a))),
+            (None, _) => println!("  {:<16} {}", s.name, theme::paint(Role::Miss, "no answer")),
+        }
+    }
+    if let Some(best) = best {
+        println!("  Best: {}", theme::paint(Role::Solution, best));
+    }
+}
+
+fn print_totals(totals: &[Total]) {
+    let mut totals = totals.to_vec();
+    totals.sort_by_key(|t| Reverse(t.points));
+    println!();
+    println!("Final scores");
+    for (i, t) in totals.iter().enumerate() {
+        println!("{:>3}. {:<16} {} points", i + 1, t.name, t.points);
+    }
+}
@@ -0,0 +1,44 @@
+//! `--one-away`, whether the values next to the target could be made.
+
+use std::error::Error;
+
+use countdown_numbers::Solver;
+
+use cli::plain;
+
+/// Prints a shortest expression reaching each positive value one away
+/// from `target`, or that there is none, then whether getting stuck one
+/// away was bad luck: only if the target itself can't be reached, which
+/// is `solved` when it can. The neighbours are solved by solvers set up
+/// by `configure`, under the same limits and rules as the target.
+pub fn print<F>(numbers: &[u64], target: u64, solved: bool, mut configure: F)
+    -> Result<(), Box<dyn Error>>
+    where F: FnMut(&mut Solver) -> Result<(), Box<dyn Error>>
+{
+    let near = target.checked_sub(1).filter(|&v| v > 0).into_iter()
+        .chain(target.checked_add(1));
+
+    println!("One away:");
+    let mut reached = 0;
+    for value in near {
+        let mut solver = Solver::new(numbers, value)?;
+        configure(&mut solver)?;
+        solver.solve_best(1);
+        match solver.solutions().first() {
+            Some(s) => {
+                println!("  {} = {}", plain::expression(&s.term), value);
+                reached += 1;
+            },
+            None => println!("  {} can't be reached", value),
+        }
+    }
+
+    if solved {
+        println!("  {} itself can be reached, so the gap could have been closed", target);
+    } else if reached > 0 {
+        println!("  {} can't be reached, so one away was the best anyone could do", target);
+    } else {
+        println!("  Neither {} nor its neighbours can be reached", target);
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+//! Puzzle packs, curated sets of rounds stored as JSON or TOML for
+//! `pack solve` and `pack play`.
+//!
+//! ```toml
+//! name = "Warm-up"
+//! author = "A. Trainer"
+//!
+//! [[puzzles]]
+//! numbers = [100, 75, 50, 25, 6, 3]
+//! target = 952
+//! difficulty = 9
+//! tags = ["classic"]
+//! ```
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use countdown_numbers::strategy::{Human, Strategy};
+use countdown_numbers::{human, Solver};
+use rand::{self, Rng};
+use serde_json;
+use toml;
+
+use cli::random;
+
+/// A named set of puzzles.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Pack {
+    /// Title of the pack
+    pub name: String,
+    /// Who put the pack together
+    pub author: Option<String>,
+    /// What the pack is for
+    pub description: Option<String>,
+    /// The puzzles, in the order they are played
+    pub puzzles: Vec<Puzzle>,
+}
+
+/// A single round in a pack.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Puzzle {
+    /// Starting numbers
+    pub numbers: Vec<u64>,
+    /// Target number
+    pub target: u64,
+    /// Rated difficulty, higher is harder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<u32>,
+    /// Free-form labels
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl Puzzle {
+    /// Short code to share the puzzle by, the target then the numbers
+    /// largest first, such as `952-100-75-50-25-6-3`. Passing the parts as
+    /// arguments solves it.
+    pub fn code(&self) -> String {
+        code(&self.numbers, self.target)
+    }
+}
+
+/// Share code of the puzzle making `target` from `numbers`, see
+/// [`Puzzle::code`](struct.Puzzle.html#method.code).
+pub fn code(numbers: &[u64], target: u64) -> String {
+    let mut numbers = numbers.to_vec();
+    numbers.sort_by(|a, b| b.cmp(a));
+    let mut code = target.to_string();
+    for n in numbers {
+        code += &format!("-{}", n);
+    }
+    code
+}
+
+/// Whether `path` names a TOML file, otherwise packs are JSON.
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == "toml")
+}
+
+/// Reads a pack from a `.toml` or JSON file.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Pack, Box<dyn Error>> {
+    let path = path.as_ref();
+    let s = fs::read_to_string(path)
+        .map_err(|e| format!("can't read pack {}: {}", path.display(), e))?;
+    let pack = if is_toml(path) {
+        toml::from_str(&s)?
+    } else {
+        serde_json::from_str(&s)?
+    };
+    Ok(pack)
+}
+
+/// Writes a pack to a `.toml` or JSON file.
+pub fn save<P: AsRef<Path>>(path: P, pack: &Pack) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let s = if is_toml(path) {
+        toml::to_string_pretty(pack)?
+    } else {
+        serde_json::to_string_pretty(pack)? + "\n"
+    };
+    fs::write(path, s)
+        .map_err(|e| format!("can't write pack {}: {}", path.display(), e).into())
+}
+
+/// Draws random rounds until it has `count` solvable ones rated from `min`
+/// to `max` by [`human::rate`], spread evenly over the ratings. Ratings
+/// which are rarely drawn stop holding the pack up after a while, and the
+/// pack is filled with whatever else is in range.
+pub fn generate(count: usize, min: u32, max: u32) -> Result<Pack, Box<dyn Error>> {
+    let mut rng = rand::thread_rng();
+    let levels = (max - min + 1) as usize;
+    let mut quota = (0..levels)
+        .map(|i| count / levels + (i < count % levels) as usize)
+        .collect::<Vec<_>>();
+    let mut puzzles = Vec::new();
+
+    let mut draws = 0;
+    while puzzles.len() < count {
+        draws += 1;
+        if draws > count * 2000 {
+            return Err(format!("only found {} of {} puzzles rated {} to {}",
+                puzzles.len(), count, min, max).into());
+        }
+
+        let num_big = rng.gen_range(0, 5);
+        let (numbers, target) = random::round(&mut rng, num_big);
+        let mut solver = Solver::new(&numbers[..], target)?;
+        solver.solve();
+
+        let rating = match human::rate(solver.solutions().iter().map(|s| &*s.term)) {
+            Some(r) if r >= min && r <= max => r,
+            _ => continue,
+        };
+        let left = &mut quota[(rating - min) as usize];
+        if *left == 0 && draws < count * 200 {
+            continue;
+        }
+        *left = left.saturating_sub(1);
+
+        puzzles.push(Puzzle { numbers, target, difficulty: Some(rating), tags: Vec::new() });
+    }
+
+    puzzles.sort_by_key(|p| p.difficulty);
+    Ok(Pack {
+        name: format!("Generated, difficulty {} to {}", min, max),
+        author: None,
+        description: None,
+        puzzles,
+    })
+}
+
+/// Solves every puzzle in a pack, printing a line for each with the
+/// number of solutions and the recommended one, or the closest term.
+pub fn solve(pack: &Pack) -> Result<(), Box<dyn Error>> {
+    println!("{}, {} puzzles", pack.name, pack.puzzles.len());
+    for (i, puzzle) in pack.puzzles.iter().enumerate() {
+        let mut solver = Solver::new(&puzzle.numbers[..], puzzle.target)?;
+        solver.solve();
+
+        let numbers = puzzle.numbers.iter().map(|n| n.to_string())
+            .collect::<Vec<_>>().join(" ");
+        print!("{:>3}. {} from {}: ", i + 1, puzzle.target, numbers);
+        match (solver.recommended(), solver.closest()) {
+            (Some(s), _) => {
+                let findable = Human::default().play(&puzzle.numbers[..], puzzle.target)
+                    .is_some_and(|t| t.value == puzzle.target);
+                println!("{} solutions, {}{}", solver.stats().solutions, s,
+                    if findable { ", human-findable" } else { "" });
+            },
+            (None, Some(c)) => {
+                println!("no solution, closest {} = {}, {} away",
+                    c, c.value, c.value.abs_diff(puzzle.target));
+            },
+            (None, None) => println!("no solution"),
+        }
+    }
+    Ok(())
+}
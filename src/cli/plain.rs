@@ -0,0 +1,83 @@
+//! `--plain`, output for screen readers: no colour or other ANSI
+//! sequences, no progress bars redrawn in place, only ASCII, and
+//! expressions read out with operators as words, `(75 plus 25) times 3`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use countdown_numbers::{Operator, Term, TermVisitor};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// Template of a progress bar counting steps.
+pub const COUNT: &str = "{bar:40} {pos}/{len} {elapsed} eta {eta}";
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Makes output plain from now on.
+pub fn set() {
+    PLAIN.store(true, Ordering::Relaxed);
+}
+
+/// Whether output is plain.
+pub fn on() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// The operator as a word, such as `divided by`.
+pub fn word(op: Operator) -> &'static str {
+    match op {
+        Operator::Addition => "plus",
+        Operator::Subtraction => "minus",
+        Operator::Multiplication => "times",
+        Operator::Division => "divided by",
+        Operator::Custom(c) => c.name,
+    }
+}
+
+/// The operator as output shows it, a word if output is plain.
+pub fn operator(op: Operator) -> &'static str {
+    if on() { word(op) } else { op.symbol() }
+}
+
+/// Writes a term with operators as words.
+struct Words(String);
+
+impl TermVisitor for Words {
+    fn number(&mut self, value: &u64) {
+        self.0.push_str(&value.to_string());
+    }
+
+    fn enter(&mut self, _op: Operator, _term: &Term) {
+        self.0.push('(');
+    }
+
+    fn between(&mut self, op: Operator, _term: &Term) {
+        self.0.push(' ');
+        self.0.push_str(word(op));
+        self.0.push(' ');
+    }
+
+    fn leave(&mut self, _op: Operator, _term: &Term) {
+        self.0.push(')');
+    }
+}
+
+/// `term` as output shows it, with operators as words if output is plain.
+pub fn expression(term: &Term) -> String {
+    if !on() {
+        return term.to_string();
+    }
+    let mut words = Words(String::new());
+    term.walk(&mut words);
+    words.0
+}
+
+/// A progress bar of `len` steps drawn on stderr with `template`, or
+/// drawn nowhere if output is plain.
+pub fn progress(len: u64, template: &str) -> ProgressBar {
+    let bar = ProgressBar::new(len);
+    bar.set_style(ProgressStyle::with_template(template).expect("progress template is valid"));
+    if on() {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    bar
+}
@@ -0,0 +1,247 @@
+//! Play mode, the player types an expression for each puzzle and is
+//! scored, as on the show by default. Every round played is recorded in
+//! the [stats file](../stats/index.html).
+
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+
+use countdown_numbers::scoring::ScoringScheme;
+use countdown_numbers::strategy::{Human, Strategy};
+use countdown_numbers::{human, parse, Solver, Term};
+use rand::{self, Rng};
+
+use cli::lang::{self, fill};
+use cli::pack::{Pack, Puzzle};
+use cli::plain;
+use cli::random;
+use cli::stats::{self, Round, Stats};
+use cli::theme::{self, Role};
+
+/// Recent rounds the difficulty of an adaptive game follows.
+const RECENT: usize = 5;
+
+/// Level an adaptive game starts at without rated rounds to go by.
+const START_LEVEL: u32 = 3;
+
+/// Points scored in a puzzle, and by the opponent if there is one.
+struct Outcome {
+    score: u32,
+    opponent: u32,
+}
+
+/// Plays through a pack on stdin and stdout, scoring answers with
+/// `scoring`. An empty line gives up on a puzzle, end of input stops early.
+/// With an `opponent`, it plays each puzzle too and is scored alongside.
+pub fn play(pack: &Pack, scoring: &dyn ScoringScheme, opponent: Option<&Human>)
+    -> Result<(), Box<dyn Error>>
+{
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut total = 0;
+    let mut opponent_total = 0;
+
+    let text = lang::text();
+    println!("{}", fill(text.intro_pack, &[&pack.name, &pack.puzzles.len()]));
+
+    for (i, puzzle) in pack.puzzles.iter().enumerate() {
+        println!();
+        print!("{}", fill(text.puzzle_heading, &[&(i + 1), &pack.puzzles.len()]));
+        match round(&mut lines, puzzle, scoring, opponent)? {
+            Some(outcome) => {
+                total += outcome.score;
+                opponent_total += outcome.opponent;
+            },
+            None => {
+                return finish(total, opponent.map(|_| opponent_total),
+                    scoring.max() * i as u32);
+            },
+        }
+    }
+
+    finish(total, opponent.map(|_| opponent_total), scoring.max() * pack.puzzles.len() as u32)
+}
+
+/// Plays `rounds` random rounds like [`play`](fn.play.html). When
+/// `adaptive`, each round is drawn at the level the player's recent
+/// rounds suggest, see [`level`](fn.level.html).
+pub fn random(rounds: usize, adaptive: bool, scoring: &dyn ScoringScheme,
+    opponent: Option<&Human>) -> Result<(), Box<dyn Error>>
+{
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut total = 0;
+    let mut opponent_total = 0;
+
+    let text = lang::text();
+    println!("{}", fill(text.intro_rounds, &[&rounds]));
+
+    for i in 0..rounds {
+        let puzzle = if adaptive {
+            let level = level(&stats::load()?);
+            draw(Some(level))?
+        } else {
+            draw(None)?
+        };
+
+        println!();
+        match puzzle.difficulty {
+            Some(d) if adaptive => {
+                print!("{}", fill(text.round_heading_rated, &[&(i + 1), &rounds, &d]));
+            },
+            _ => print!("{}", fill(text.round_heading, &[&(i + 1), &rounds])),
+        }
+        match round(&mut lines, &puzzle, scoring, opponent)? {
+            Some(outcome) => {
+                total += outcome.score;
+                opponent_total += outcome.opponent;
+            },
+            None => {
+                return finish(total, opponent.map(|_| opponent_total), scoring.max() * i as u32);
+            },
+        }
+    }
+
+    finish(total, opponent.map(|_| opponent_total), scoring.max() * rounds as u32)
+}
+
+/// The difficulty to practise at next, from the player's last rated
+/// rounds: a level up after scoring at least 80% of the points, a level
+/// down after scoring under half, otherwise the same level, so the player
+/// is stretched without being lost.
+pub fn level(stats: &Stats) -> u32 {
+    let recent = stats.rounds.iter().rev()
+        .filter(|r| r.difficulty.is_some())
+        .take(RECENT)
+        .collect::<Vec<_>>();
+    let last = match recent.first() {
+        Some(r) => r.difficulty.expect("rounds were filtered by difficulty"),
+        None => return START_LEVEL,
+    };
+
+    let score = recent.iter().map(|r| r.score).sum::<u32>();
+    let max = recent.iter().map(|r| r.max).sum::<u32>();
+    let level = if score * 5 >= max * 4 {
+        last + 1
+    } else if score * 2 < max {
+        last - 1
+    } else {
+        last
+    };
+    level.clamp(1, 10)
+}
+
+/// Draws a random solvable round, rated `level` if given. Levels rarely
+/// drawn are given up on after a while for the closest level drawn.
+pub fn draw(level: Option<u32>) -> Result<Puzzle, Box<dyn Error>> {
+    let mut rng = rand::thread_rng();
+    let mut best: Option<Puzzle> = None;
+
+    for draws in 0..1000 {
+        let num_big = rng.gen_range(0, 5);
+        let (numbers, target) = random::round(&mut rng, num_big);
+        let mut solver = Solver::new(&numbers[..], target)?;
+        solver.solve();
+        let rating = match human::rate(solver.solutions().iter().map(|s| &*s.term)) {
+            Some(r) => r,
+            None => continue,
+        };
+
+        let puzzle = Puzzle { numbers, target, difficulty: Some(rating), tags: Vec::new() };
+        let wanted = match level {
+            Some(level) => level,
+            None => return Ok(puzzle),
+        };
+        let off = |p: &Puzzle| p.difficulty.map_or(u32::MAX, |d| d.abs_diff(wanted));
+        if best.as_ref().is_none_or(|b| off(&puzzle) < off(b)) {
+            best = Some(puzzle);
+        }
+        if best.as_ref().is_some_and(|b| off(b) == 0 || (draws >= 100 && off(b) <= 1)) {
+            break;
+        }
+    }
+    best.ok_or_else(|| "couldn't draw a solvable round".into())
+}
+
+/// Plays a single puzzle, the heading having been started, and records it
+/// in the stats file. `None` if input ended first.
+fn round<B: BufRead>(lines: &mut io::Lines<B>, puzzle: &Puzzle, scoring: &dyn ScoringScheme,
+    opponent: Option<&Human>) -> Result<Option<Outcome>, Box<dyn Error>>
+{
+    let text = lang::text();
+    let mut solver = Solver::new(&puzzle.numbers[..], puzzle.target)?;
+    let numbers = puzzle.numbers.iter().map(|n| n.to_string())
+        .collect::<Vec<_>>().join(" ");
+    println!("{}", theme::paint(Role::Heading,
+        fill(text.make, &[&puzzle.target, &numbers])));
+
+    let answer = loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(None),
+        };
+        if line.trim().is_empty() {
+            break None;
+        }
+
+        match parse::<u64>(&line) {
+            Ok(ref term) if !term.uses_only(&puzzle.numbers[..]) => {
+                println!("  {}", text.only_numbers);
+            },
+            Ok(term) => break Some(term),
+            Err(e) => println!("  {}", e),
+        }
+    };
+
+    let mut outcome = Outcome { score: 0, opponent: 0 };
+    if let Some(ref answer) = answer {
+        let distance = answer.value.abs_diff(puzzle.target);
+        outcome.score = scoring.score(distance);
+        let verdict = match distance {
+            0 => fill(text.spot_on, &[&answer.value, &outcome.score]),
+            d => fill(text.away, &[&answer.value, &d, &outcome.score]),
+        };
+        println!("  {}", theme::paint(Role::for_distance(distance), verdict));
+    }
+
+    if let Some(opponent) = opponent {
+        match opponent.play(&puzzle.numbers[..], puzzle.target) {
+            Some(answer) => {
+                outcome.opponent = scoring.score(answer.value.abs_diff(puzzle.target));
+                println!("  {}", fill(text.opponent, &[&show(&answer), &outcome.opponent]));
+            },
+            None => println!("  {}", text.opponent_nothing),
+        }
+    }
+
+    solver.solve();
+    match solver.recommended().map(|s| &*s.term).or(solver.closest()) {
+        Some(best) => {
+            println!("  {}", fill(text.best, &[&theme::paint(Role::Solution, show(best))]));
+        },
+        None => println!("  {}", text.nothing_to_make),
+    }
+
+    let difficulty = human::rate(solver.solutions().iter().map(|s| &*s.term));
+    let round = Round::new(&puzzle.numbers, puzzle.target, difficulty, outcome.score,
+        scoring.max());
+    stats::announce(&stats::record(round, answer.as_ref()));
+    Ok(Some(outcome))
+}
+
+/// Prints the final score, and the opponent's if there was one, out of the
+/// `max` possible.
+fn finish(total: u32, opponent: Option<u32>, max: u32) -> Result<(), Box<dyn Error>> {
+    println!();
+    let text = lang::text();
+    println!("{}", fill(text.scored, &[&total, &max]));
+    if let Some(opponent) = opponent {
+        println!("{}", fill(text.opponent_scored, &[&opponent, &max]));
+    }
+    Ok(())
+}
+
+fn show(term: &Term) -> String {
+    format!("{} = {}", plain::expression(term), term.value)
+}
@@ -0,0 +1,39 @@
+//! Listing and checking the bundled rounds for the `preset` subcommand.
+
+use std::error::Error;
+
+use countdown_numbers::presets::PRESETS;
+use countdown_numbers::Solver;
+
+/// Prints the name, round and description of every bundled round.
+pub fn list() {
+    for p in PRESETS.iter() {
+        let numbers = p.numbers.iter().map(|n| n.to_string())
+            .collect::<Vec<_>>().join(" ");
+        println!("{:<16} {} from {}", p.name, p.target, numbers);
+        println!("{:<16} {}", "", p.description);
+    }
+}
+
+/// Solves every bundled round, failing if any finds a different number
+/// of solutions than recorded.
+pub fn check() -> Result<(), Box<dyn Error>> {
+    let mut failed = 0;
+    for p in PRESETS.iter() {
+        let mut solver = Solver::new(p.numbers, p.target)?;
+        solver.solve();
+        let found = solver.stats().solutions;
+        if found == p.solutions {
+            println!("ok     {}", p.name);
+        } else {
+            println!("FAILED {}, found {} solutions, expected {}",
+                p.name, found, p.solutions);
+            failed += 1;
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} of {} presets failed", n, PRESETS.len()).into()),
+    }
+}
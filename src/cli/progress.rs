@@ -0,0 +1,45 @@
+//! The progress bar drawn on stderr while solving, an observer of the
+//! search.
+
+use countdown_numbers::{Observer, Operator, Stats};
+use indicatif::ProgressBar;
+
+use cli::plain;
+
+/// Counts top-level branches as the search completes them.
+pub struct Progress {
+    bar: ProgressBar,
+    /// Completed at the current depth
+    done: u64,
+    depth: usize,
+}
+
+impl Progress {
+    /// Progress of a search of `tiles` starting numbers, drawn only when
+    /// stderr is a terminal and output isn't plain.
+    pub fn new(tiles: usize) -> Progress {
+        // A top-level branch is a pair of starting numbers and an operator
+        let branches = tiles * (tiles - 1) / 2 * Operator::ALL.len();
+        Progress {
+            bar: plain::progress(branches as u64, "{bar:40} {percent:>3}% {elapsed}"),
+            done: 0,
+            depth: 0,
+        }
+    }
+}
+
+impl Observer for Progress {
+    fn on_progress(&mut self, _nodes: u64, depth: usize) {
+        // Deepening searches start over at each depth
+        if depth != self.depth {
+            self.depth = depth;
+            self.done = 0;
+        }
+        self.done += 1;
+        self.bar.set_position(self.done);
+    }
+
+    fn on_finish(&mut self, _stats: &Stats) {
+        self.bar.finish_and_clear();
+    }
+}
@@ -0,0 +1,17 @@
+//! Random rounds drawn the way the show draws them.
+
+use rand::Rng;
+
+/// Draws `num_big` large numbers and fills the rest of the six tiles with
+/// small numbers, then picks a target from 101 to 999.
+pub fn round<R: Rng>(rng: &mut R, num_big: usize) -> (Vec<u64>, u64) {
+    let mut small = (1u64..11).flat_map(|i| vec![i, i]).collect::<Vec<_>>();
+    let mut big = vec![100, 75, 50, 25];
+
+    rng.shuffle(&mut small[..]);
+    rng.shuffle(&mut big[..]);
+
+    let target = rng.gen_range(101, 1000);
+    (big.into_iter().take(num_big)
+        .chain(small.into_iter().take(6 - num_big)).collect(), target)
+}
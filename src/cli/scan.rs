@@ -0,0 +1,28 @@
+//! `--scan`, a map of which targets a selection reaches.
+
+use countdown_numbers::Reachable;
+
+/// Prints a row of 50 targets per line, each marked by how close the
+/// selection gets: `#` exactly, `+` within 5, `.` within 10, `-` further.
+/// Then the number of targets in each class.
+pub fn print(reachable: &Reachable, targets: &[u64]) {
+    let mut counts = [0; 4];
+    let marks = targets.iter().map(|&t| {
+        let distance = reachable.closest(&t).map_or(u64::MAX, |v| v.abs_diff(t));
+        let class = match distance {
+            0 => 0,
+            1..=5 => 1,
+            6..=10 => 2,
+            _ => 3,
+        };
+        counts[class] += 1;
+        ['#', '+', '.', '-'][class]
+    }).collect::<Vec<_>>();
+
+    println!("# exact, + within 5, . within 10, - further");
+    for (row, chunk) in targets.chunks(50).zip(marks.chunks(50)) {
+        println!("{:>6} {}", row[0], chunk.iter().collect::<String>());
+    }
+    println!("{} exact, {} within 5, {} within 10, {} further, of {} targets",
+        counts[0], counts[1], counts[2], counts[3], targets.len());
+}
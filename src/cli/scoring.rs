@@ -0,0 +1,44 @@
+//! The `--scoring` option, choosing how declarations are scored.
+
+use std::error::Error;
+use std::fs;
+
+use countdown_numbers::scoring::{Bands, Linear, Official, ScoringScheme};
+use serde_json;
+use toml;
+
+/// Parses `--scoring`: `official`, `linear` losing a point per step from
+/// 10, `linear:N` from N, or a JSON or TOML file of bands such as
+///
+/// ```toml
+/// [[bands]]
+/// within = 0
+/// points = 10
+///
+/// [[bands]]
+/// within = 20
+/// points = 3
+/// ```
+pub fn parse(s: &str) -> Result<Box<dyn ScoringScheme>, Box<dyn Error>> {
+    if s == "official" {
+        return Ok(Box::new(Official));
+    }
+    if s == "linear" {
+        return Ok(Box::new(Linear { points: 10 }));
+    }
+    if let Some(points) = s.strip_prefix("linear:") {
+        let points = points.parse()
+            .map_err(|_| format!("`{}` is not a number of points", points))?;
+        return Ok(Box::new(Linear { points }));
+    }
+
+    let text = fs::read_to_string(s).map_err(|e| {
+        format!("`{}` is not official, linear or a readable file of bands: {}", s, e)
+    })?;
+    let bands: Bands = if s.ends_with(".toml") {
+        toml::from_str(&text)?
+    } else {
+        serde_json::from_str(&text)?
+    };
+    Ok(Box::new(bands))
+}
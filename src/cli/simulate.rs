@@ -0,0 +1,107 @@
+//! The `simulate` and `simulate-strategy` subcommands, the expected score
+//! of a perfect solver for each number of large numbers, and how simple
+//! strategies compare with it.
+
+use std::error::Error;
+
+use countdown_numbers::rules::{LARGE_NUMBERS, MAX_TARGET, MIN_TARGET};
+use countdown_numbers::scoring::ScoringScheme;
+use countdown_numbers::strategy;
+use countdown_numbers::Reachable;
+use rand::{self, Rng};
+
+use cli::export::{self, Export};
+use cli::plain;
+use cli::random;
+
+/// Simulates `samples` random selections for each number of large numbers,
+/// each against every target, and prints the mean and variance of the
+/// points a solver which always finds the closest value scores under
+/// `scoring`. The selections simulated are also written to `export`, if
+/// given.
+pub fn simulate(scoring: &dyn ScoringScheme, samples: usize, export: Option<&Export>)
+    -> Result<(), Box<dyn Error>>
+{
+    if samples == 0 {
+        return Err("at least one selection must be simulated".into());
+    }
+    let options = LARGE_NUMBERS.len() + 1;
+    let mut db = export::open(export)?;
+    let bar = plain::progress((options * samples) as u64, plain::COUNT);
+
+    let mut rng = rand::thread_rng();
+    let rounds = (samples as u64 * (MAX_TARGET - MIN_TARGET + 1)) as f64;
+    println!("large   mean  variance  std dev");
+    for large in 0..options {
+        let (mut sum, mut squares) = (0.0, 0.0);
+        for _ in 0..samples {
+            let (numbers, _) = random::round(&mut rng, large);
+            let reachable = Reachable::new(&numbers[..]);
+            if let Some(ref mut db) = db {
+                db.selection(&numbers[..], &reachable)?;
+            }
+            for t in MIN_TARGET..=MAX_TARGET {
+                let points = reachable.closest(&t)
+                    .map_or(0, |v| scoring.score(v.abs_diff(t))) as f64;
+                sum += points;
+                squares += points * points;
+            }
+            bar.inc(1);
+        }
+        let mean = sum / rounds;
+        let variance = squares / rounds - mean * mean;
+        bar.suspend(|| {
+            println!("{:>5} {:>6.3} {:>9.3} {:>8.3}", large, mean, variance, variance.sqrt());
+        });
+    }
+    bar.finish_and_clear();
+    if let Some(db) = db {
+        db.close()?;
+    }
+    println!("Points out of {}, over {} rounds each.", scoring.max(), rounds);
+    Ok(())
+}
+
+/// Plays `games` random rounds with every strategy, and with a perfect
+/// solver for reference, and prints how often each reaches the target
+/// exactly, within 5 and within 10. Each round has `large` large numbers,
+/// or a random number of them.
+pub fn strategies(games: usize, large: Option<usize>) -> Result<(), Box<dyn Error>> {
+    if games == 0 {
+        return Err("at least one game must be simulated".into());
+    }
+    let strategies = strategy::all();
+    // Rounds within 0, 5 and 10, the perfect solver last
+    let mut counts = vec![[0usize; 3]; strategies.len() + 1];
+    let bar = plain::progress(games as u64, plain::COUNT);
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..games {
+        let large = large.unwrap_or_else(|| rng.gen_range(0, LARGE_NUMBERS.len() + 1));
+        let (numbers, target) = random::round(&mut rng, large);
+        let perfect = Reachable::new(&numbers[..]).closest(&target).cloned();
+        let values = strategies.iter()
+            .map(|s| s.play(&numbers, target).map(|t| t.value))
+            .chain(Some(perfect));
+        for (count, value) in counts.iter_mut().zip(values) {
+            let distance = value.map_or(u64::MAX, |v| v.abs_diff(target));
+            for (c, &within) in count.iter_mut().zip([0, 5, 10].iter()) {
+                if distance <= within {
+                    *c += 1;
+                }
+            }
+        }
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    let names = strategies.iter().map(|s| s.name()).chain(Some("perfect"));
+    println!("{:<18} {:>7} {:>9} {:>10}", "strategy", "exact", "within 5", "within 10");
+    for (name, count) in names.zip(&counts) {
+        let share = |n: usize| n as f64 * 100.0 / games as f64;
+        println!("{:<18} {:>6.1}% {:>8.1}% {:>9.1}%", name, share(count[0]), share(count[1]),
+            share(count[2]));
+    }
+    println!("Over {} games.", games);
+    Ok(())
+}
@@ -0,0 +1,178 @@
+//! The player's stats file, a record of every round played and the
+//! milestones reached, kept so play mode can follow how the player is
+//! doing across sessions.
+//!
+//! Stored as JSON in `$COUNTDOWN_STATS_FILE`, or else
+//! `countdown-numbers/stats.json` in `$XDG_DATA_HOME` or `~/.local/share`.
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use countdown_numbers::rules::LARGE_NUMBERS;
+use countdown_numbers::Term;
+use serde_json;
+
+use cli::pack;
+
+/// Every round the player has played and every milestone reached, oldest
+/// first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub rounds: Vec<Round>,
+    #[serde(default)]
+    pub achievements: Vec<Achievement>,
+}
+
+/// A notable first, reached once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Milestone {
+    /// An exact solve using all four large numbers
+    AllFourLarge,
+    /// An exact solve in under 10 seconds
+    QuickSolve,
+    /// An exact solve using all six numbers, like the famous 952
+    SixTiles,
+}
+
+impl Milestone {
+    pub const ALL: [Milestone; 3] =
+        [Milestone::AllFourLarge, Milestone::QuickSolve, Milestone::SixTiles];
+
+    /// What the milestone is, to announce it.
+    pub fn describe(&self) -> &'static str {
+        match *self {
+            Milestone::AllFourLarge => "first exact solve using all four large numbers",
+            Milestone::QuickSolve => "first exact solve in under 10 seconds",
+            Milestone::SixTiles => "first exact solve using all six numbers",
+        }
+    }
+
+    /// Whether `answer` to `round` reaches the milestone.
+    fn reached(&self, round: &Round, answer: &Term) -> bool {
+        if answer.value != round.target {
+            return false;
+        }
+        match *self {
+            Milestone::AllFourLarge => {
+                let used = answer.numbers();
+                LARGE_NUMBERS.iter().all(|n| used.contains(&n))
+            },
+            Milestone::QuickSolve => round.seconds.is_some_and(|s| s < 10.0),
+            Milestone::SixTiles => answer.tiles() == 6,
+        }
+    }
+}
+
+/// A milestone reached.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Achievement {
+    pub milestone: Milestone,
+    /// [Share code](../pack/struct.Puzzle.html#method.code) of the round
+    /// reaching it
+    pub code: String,
+    /// When it was reached, in seconds since the Unix epoch
+    pub played: u64,
+}
+
+/// A round played.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Round {
+    /// Starting numbers
+    pub numbers: Vec<u64>,
+    /// Target number
+    pub target: u64,
+    /// Rated difficulty from 1 to 10, `None` for rounds without solutions
+    pub difficulty: Option<u32>,
+    /// Points scored
+    pub score: u32,
+    /// Points which could have been scored
+    pub max: u32,
+    /// When the round was played, in seconds since the Unix epoch
+    pub played: u64,
+    /// Seconds taken to answer in a timed round
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seconds: Option<f64>,
+}
+
+impl Round {
+    /// A round played now.
+    pub fn new(numbers: &[u64], target: u64, difficulty: Option<u32>, score: u32, max: u32)
+        -> Round
+    {
+        let played = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs()).unwrap_or(0);
+        Round {
+            numbers: numbers.to_vec(),
+            target,
+            difficulty,
+            score,
+            max,
+            played,
+            seconds: None,
+        }
+    }
+}
+
+/// Path of the stats file, `None` if no home directory is known.
+pub fn path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("COUNTDOWN_STATS_FILE") {
+        return Some(PathBuf::from(path));
+    }
+    env::var_os("XDG_DATA_HOME").map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))
+        .map(|d| d.join("countdown-numbers").join("stats.json"))
+}
+
+/// The player's stats, empty if there are none yet.
+pub fn load() -> Result<Stats, Box<dyn Error>> {
+    let path = match path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok(Stats::default()),
+    };
+    let text = fs::read_to_string(&path)
+        .map_err(|e| format!("can't read stats {}: {}", path.display(), e))?;
+    serde_json::from_str(&text)
+        .map_err(|e| format!("can't read stats {}: {}", path.display(), e).into())
+}
+
+/// Appends `round`, answered with `answer` if one was given, to the stats
+/// file, returns the milestones it reaches for the first time. Failing to
+/// is only worth a note, the round has been played either way.
+pub fn record(round: Round, answer: Option<&Term>) -> Vec<Milestone> {
+    let result = load().and_then(|mut stats| -> Result<Vec<Milestone>, Box<dyn Error>> {
+        let path = path().ok_or("no home directory for the stats file")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let reached = Milestone::ALL.iter().cloned()
+            .filter(|m| !stats.achievements.iter().any(|a| a.milestone == *m))
+            .filter(|m| answer.is_some_and(|a| m.reached(&round, a)))
+            .collect::<Vec<_>>();
+        for &milestone in &reached {
+            stats.achievements.push(Achievement {
+                milestone,
+                code: pack::code(&round.numbers, round.target),
+                played: round.played,
+            });
+        }
+
+        stats.rounds.push(round);
+        fs::write(&path, serde_json::to_string_pretty(&stats)? + "\n")?;
+        Ok(reached)
+    });
+    result.unwrap_or_else(|e| {
+        eprintln!("note: couldn't record the round: {}", e);
+        Vec::new()
+    })
+}
+
+/// Announces newly reached milestones.
+pub fn announce(milestones: &[Milestone]) {
+    for m in milestones {
+        println!("  Achievement: {}", m.describe());
+    }
+}
@@ -0,0 +1,137 @@
+//! The `sweep` subcommand, rating every selection which could occur on the
+//! show against every target, with checkpoints so an interrupted sweep
+//! can resume.
+//!
+//! The output is CSV with a row per selection: its numbers, how many are
+//! large, how many of the targets 100 to 999 it reaches exactly, within 5
+//! and within 10, and how many distinct values it reaches in all.
+
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::time::{Duration, Instant};
+
+use countdown_numbers::rules::{self, LARGE_NUMBERS, MAX_TARGET, MIN_TARGET};
+use countdown_numbers::Reachable;
+use serde_json;
+
+use cli::export::{self, Export};
+use cli::plain;
+
+/// How often progress is saved.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Progress of a sweep, saved next to the output.
+#[derive(Debug, Serialize, Deserialize)]
+struct Checkpoint {
+    /// Number of selections written
+    completed: usize,
+    /// Length of the output once they were
+    offset: u64,
+}
+
+/// How close a selection gets to each target.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Summary {
+    /// Targets reached exactly
+    pub exact: usize,
+    /// Targets missed by 1 to 5
+    pub within_5: usize,
+    /// Targets missed by 6 to 10
+    pub within_10: usize,
+    /// Distinct values reached, targets or not
+    pub values: usize,
+}
+
+impl Summary {
+    /// Classifies each target in `targets` by how close `reachable` gets.
+    pub fn new<I: IntoIterator<Item = u64>>(reachable: &Reachable, targets: I) -> Summary {
+        let mut summary = Summary { values: reachable.values().len(), ..Summary::default() };
+        for t in targets {
+            match reachable.closest(&t).map(|v| v.abs_diff(t)) {
+                Some(0) => summary.exact += 1,
+                Some(1..=5) => summary.within_5 += 1,
+                Some(6..=10) => summary.within_10 += 1,
+                _ => (),
+            }
+        }
+        summary
+    }
+}
+
+/// Sweeps every selection, writing CSV to `path` and to `export` if given.
+/// With `resume`, carries on from the last checkpoint of an interrupted
+/// sweep to the same path.
+pub fn run(path: &str, resume: bool, export: Option<&Export>) -> Result<(), Box<dyn Error>> {
+    if let (true, Some(&Export::Parquet(_))) = (resume, export) {
+        return Err("a Parquet file is only complete once closed, so its sweep can't be \
+                    resumed, sweep again without --resume".into());
+    }
+    let checkpoint_path = format!("{}.checkpoint", path);
+    let selections = rules::selections();
+
+    let (file, start) = if resume {
+        let text = fs::read_to_string(&checkpoint_path)
+            .map_err(|e| format!("no sweep to resume at {}: {}", checkpoint_path, e))?;
+        let checkpoint: Checkpoint = serde_json::from_str(&text)?;
+        // Drop anything written after the checkpoint, it's redone
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(checkpoint.offset)?;
+        file.seek(SeekFrom::End(0))?;
+        (file, checkpoint.completed)
+    } else {
+        let mut file = File::create(path)?;
+        writeln!(file, "numbers,large,exact,within_5,within_10,values")?;
+        (file, 0)
+    };
+    let mut out = BufWriter::new(file);
+    let mut db = export::open(export)?;
+
+    let bar = plain::progress(selections.len() as u64, plain::COUNT);
+    bar.set_position(start as u64);
+
+    let mut saved = Instant::now();
+    for (i, numbers) in selections.iter().enumerate().skip(start) {
+        let reachable = Reachable::new(&numbers[..]);
+        let s = Summary::new(&reachable, MIN_TARGET..=MAX_TARGET);
+        let large = numbers.iter().filter(|n| LARGE_NUMBERS.contains(n)).count();
+        let text = numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(" ");
+        writeln!(out, "{},{},{},{},{},{}", text, large, s.exact, s.within_5, s.within_10,
+            s.values)?;
+        bar.inc(1);
+
+        if let Some(ref mut db) = db {
+            db.selection(&numbers[..], &reachable)?;
+        }
+
+        if saved.elapsed() >= CHECKPOINT_INTERVAL {
+            // The database first, the checkpoint mustn't get ahead of it
+            if let Some(ref mut db) = db {
+                db.commit()?;
+            }
+            save(&mut out, &checkpoint_path, i + 1)?;
+            saved = Instant::now();
+        }
+    }
+
+    out.flush()?;
+    if let Some(db) = db {
+        db.close()?;
+    }
+    bar.finish_and_clear();
+    if fs::metadata(&checkpoint_path).is_ok() {
+        fs::remove_file(&checkpoint_path)?;
+    }
+    Ok(())
+}
+
+/// Flushes the output and records that `completed` selections are in it.
+/// The checkpoint is replaced atomically, so it's never half written.
+fn save(out: &mut BufWriter<File>, path: &str, completed: usize) -> Result<(), Box<dyn Error>> {
+    out.flush()?;
+    let offset = out.get_mut().stream_position()?;
+    let tmp = format!("{}.tmp", path);
+    fs::write(&tmp, serde_json::to_string(&Checkpoint { completed, offset })?)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
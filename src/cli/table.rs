@@ -0,0 +1,16 @@
+//! `--table`, a cheat-sheet of the values a selection reaches.
+
+use countdown_numbers::Reachable;
+
+/// Prints each of `targets` the selection reaches with an expression
+/// reaching it, one per line, then how many were reached.
+pub fn print(reachable: &Reachable, targets: &[u64]) {
+    let mut reached = 0;
+    for &target in targets.iter() {
+        if let Some(t) = reachable.expression(&target) {
+            println!("{:>6} = {}", target, t);
+            reached += 1;
+        }
+    }
+    println!("{} of {} targets reachable", reached, targets.len());
+}
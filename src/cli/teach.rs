@@ -0,0 +1,66 @@
+//! The narrative printed by `--teach`, walking through a solution the way
+//! a contestant would find it.
+
+use countdown_numbers::human;
+use countdown_numbers::{Operator, Term};
+
+use cli::lang::{self, fill};
+use cli::plain;
+
+/// Prints how to find `solution`, first the goal near `target` and how to
+/// make it, then the steps adjusting it to the target.
+pub fn print(solution: &Term, target: u64) {
+    let plan = human::plan(solution);
+    let goal = plan.goal;
+    let text = lang::text();
+
+    println!("{}", fill(text.how_to, &[&plain::expression(solution), &solution.value]));
+
+    match goal.expression {
+        Some((_, ref a, ref b)) if !plan.adjustments.is_empty() => {
+            let (aim, offset) = if goal.value < target {
+                (text.aim_short, target - goal.value)
+            } else {
+                (text.aim_past, goal.value - target)
+            };
+            println!("  {}", fill(aim, &[&goal.value, &flat(goal), &offset, &target]));
+            make(a);
+            make(b);
+        },
+        Some((_, ref a, ref b)) => {
+            println!("  {}", fill(text.reach_directly, &[&goal.value, &flat(goal)]));
+            make(a);
+            make(b);
+        },
+        None => println!("  {}", fill(text.starting_number, &[&goal.value])),
+    }
+
+    for step in plan.adjustments.iter() {
+        let subtract = step.op == Operator::Subtraction;
+        if step.operand.expression.is_some() {
+            let made = if subtract { text.subtract_made } else { text.add_made };
+            println!("  {}", fill(made, &[&step.operand.value, &flat(step.operand),
+                &step.result.value]));
+        } else {
+            let step_text = if subtract { text.subtract } else { text.add };
+            println!("  {}", fill(step_text, &[&step.operand.value, &step.result.value]));
+        }
+    }
+}
+
+/// Prints how to make an operand of the goal, if it isn't a starting number.
+fn make(term: &Term) {
+    if term.expression.is_some() {
+        println!("  {}", fill(lang::text().make_as, &[&term.value, &flat(term)]));
+    }
+}
+
+/// The term without its outermost parentheses, as output shows it.
+fn flat(term: &Term) -> String {
+    match term.expression {
+        Some((op, ref a, ref b)) => {
+            format!("{} {} {}", plain::expression(a), plain::operator(op), plain::expression(b))
+        },
+        None => term.value.to_string(),
+    }
+}
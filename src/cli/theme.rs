@@ -0,0 +1,136 @@
+//! Colour themes, the one place deciding how output is styled.
+//!
+//! Output is styled by [`Role`](enum.Role.html), what the text means, and
+//! the theme set with `--theme` or `$COUNTDOWN_THEME` picks the style for
+//! each role. Nothing is styled unless stdout is a terminal and
+//! `$NO_COLOR` is unset.
+
+use std::env;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::sync::OnceLock;
+
+use cli::plain;
+
+/// What a piece of output means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Titles and the puzzle being solved
+    Heading,
+    /// An expression
+    Solution,
+    /// A declaration reaching the target
+    Exact,
+    /// A declaration which still scores, within 10
+    Near,
+    /// A declaration scoring nothing, or no declaration
+    Miss,
+}
+
+impl Role {
+    /// The role of a declaration `distance` away from the target.
+    pub fn for_distance(distance: u64) -> Role {
+        match distance {
+            0 => Role::Exact,
+            1..=10 => Role::Near,
+            _ => Role::Miss,
+        }
+    }
+}
+
+/// A style for each role, as ANSI SGR parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    heading: &'static str,
+    solution: &'static str,
+    exact: &'static str,
+    near: &'static str,
+    miss: &'static str,
+}
+
+/// Every theme, the default first.
+pub const THEMES: [Theme; 4] = [
+    Theme {
+        name: "default",
+        heading: "1",
+        solution: "36",
+        exact: "1;32",
+        near: "33",
+        miss: "31",
+    },
+    Theme {
+        name: "high-contrast",
+        heading: "1;4",
+        solution: "1;97",
+        exact: "1;30;102",
+        near: "1;30;103",
+        miss: "1;97;41",
+    },
+    // Blue and orange, told apart with any colour vision deficiency
+    Theme {
+        name: "colorblind-safe",
+        heading: "1",
+        solution: "38;5;39",
+        exact: "1;38;5;33",
+        near: "38;5;214",
+        miss: "38;5;202;4",
+    },
+    Theme {
+        name: "monochrome",
+        heading: "1",
+        solution: "",
+        exact: "1",
+        near: "4",
+        miss: "2",
+    },
+];
+
+/// Theme names, for `--theme`.
+pub const NAMES: [&str; 4] = [THEMES[0].name, THEMES[1].name, THEMES[2].name, THEMES[3].name];
+
+/// Looks up a theme by name.
+pub fn find(name: &str) -> Option<&'static Theme> {
+    THEMES.iter().find(|t| t.name == name)
+}
+
+impl Theme {
+    fn style(&self, role: Role) -> &'static str {
+        match role {
+            Role::Heading => self.heading,
+            Role::Solution => self.solution,
+            Role::Exact => self.exact,
+            Role::Near => self.near,
+            Role::Miss => self.miss,
+        }
+    }
+}
+
+/// The theme in use, `None` when output isn't styled.
+static CURRENT: OnceLock<Option<&'static Theme>> = OnceLock::new();
+
+/// Styles output with `theme` from now on, if stdout is a terminal,
+/// `$NO_COLOR` is unset and output isn't [plain](../plain/index.html). Only
+/// the first call has an effect.
+pub fn set(theme: &'static Theme) {
+    let styled = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+        && !plain::on();
+    let _ = CURRENT.set(if styled { Some(theme) } else { None });
+}
+
+/// `text` styled for `role`, shown with `{}`.
+pub fn paint<T: fmt::Display>(role: Role, text: T) -> Painted<T> {
+    Painted(role, text)
+}
+
+/// Text styled for a role, see [`paint`](fn.paint.html).
+pub struct Painted<T>(Role, T);
+
+impl<T: fmt::Display> fmt::Display for Painted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match CURRENT.get().cloned().flatten().map(|t| t.style(self.0)) {
+            Some(style) if !style.is_empty() => write!(f, "\x1b[{}m{}\x1b[0m", style, self.1),
+            _ => write!(f, "{}", self.1),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Search traces recorded by `--trace`, one JSON object per line.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::rc::Rc;
+
+use countdown_numbers::{Node, Prune};
+
+/// Write buffer size, large so tracing doesn't dominate the search.
+const BUFFER_SIZE: usize = 1 << 20;
+
+/// Records expanded nodes to a JSONL file.
+pub struct Recorder {
+    state: Rc<RefCell<State>>,
+}
+
+struct State {
+    out: BufWriter<File>,
+    /// First write error, nothing more is written after an error
+    error: Option<io::Error>,
+}
+
+impl Recorder {
+    /// Create a recorder writing to a new file.
+    pub fn create(path: &str) -> io::Result<Recorder> {
+        Ok(Recorder {
+            state: Rc::new(RefCell::new(State {
+                out: BufWriter::with_capacity(BUFFER_SIZE, File::create(path)?),
+                error: None,
+            })),
+        })
+    }
+
+    /// Trace callback for `Solver::set_trace`.
+    pub fn hook(&self) -> impl FnMut(&Node) + 'static {
+        let state = self.state.clone();
+        move |node| {
+            let state = &mut *state.borrow_mut();
+            if state.error.is_none() {
+                if let Err(e) = write_node(&mut state.out, node) {
+                    state.error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Flush the trace, reporting the first error which occurred.
+    pub fn finish(self) -> io::Result<()> {
+        let state = &mut *self.state.borrow_mut();
+        match state.error.take() {
+            Some(e) => Err(e),
+            None => state.out.flush(),
+        }
+    }
+}
+
+/// Write a node as a single line of JSON.
+fn write_node<W: Write>(out: &mut W, node: &Node) -> io::Result<()> {
+    write!(out, "{{\"id\":{},\"parent\":{},\"op\":\"{}\",\"a\":{},\"b\":{},",
+        node.id, node.parent, node.op.symbol(), node.a, node.b)?;
+    match node.value {
+        Ok(value) => writeln!(out, "\"value\":{}}}", value),
+        Err(prune) => writeln!(out, "\"pruned\":\"{}\"}}", match prune {
+            Prune::NotPositive => "not_positive",
+            Prune::Fraction => "fraction",
+            Prune::Overflow => "overflow",
+            Prune::TooLarge => "too_large",
+            Prune::Undefined => "undefined",
+        }),
+    }
+}
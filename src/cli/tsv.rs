@@ -0,0 +1,55 @@
+//! `--format tsv`, a solution per line with tab separated fields for `cut`,
+//! `awk` and `sort`, such as
+//!
+//! ```text
+//! (100+3)*75*6/50+25  952  20  952  6  5
+//! ```
+//!
+//! with tabs between the fields.
+
+use std::fmt;
+
+use countdown_numbers::format::SolutionFormatter;
+use countdown_numbers::{Report, Solution};
+
+/// Names of the fields, printed as a header by `--header`.
+pub const FIELDS: [&str; 6] = ["expression", "value", "difficulty", "peak", "tiles", "operations"];
+
+/// The fields of `solution`. The expression is written without spaces,
+/// as in `--format compact`, so no field holds a tab or newline.
+pub fn record(solution: &Solution) -> [String; 6] {
+    [
+        solution.compact(),
+        solution.term.value.to_string(),
+        solution.difficulty.to_string(),
+        solution.peak().to_string(),
+        solution.tiles().to_string(),
+        solution.operations().to_string(),
+    ]
+}
+
+/// `--format tsv`, a record for each solution after a header if asked
+/// for. Records end with a newline, or a NUL with `null` for `xargs -0` and
+/// `sort -z`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tsv {
+    pub header: bool,
+    pub null: bool,
+}
+
+impl SolutionFormatter for Tsv {
+    fn name(&self) -> &str {
+        "tsv"
+    }
+
+    fn write(&self, report: &Report, out: &mut dyn fmt::Write) -> fmt::Result {
+        let end = if self.null { '\0' } else { '\n' };
+        if self.header {
+            write!(out, "{}{}", FIELDS.join("\t"), end)?;
+        }
+        for s in report.solutions.iter() {
+            write!(out, "{}{}", record(s).join("\t"), end)?;
+        }
+        Ok(())
+    }
+}
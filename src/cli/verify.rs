@@ -0,0 +1,140 @@
+//! Checks of the solver's output, for catching bugs in the search.
+
+use std::error::Error;
+
+#[cfg(feature = "oracle")]
+use countdown_numbers::oracle;
+use countdown_numbers::rules::Rules;
+use countdown_numbers::{Dedup, Operator, Report, Solution, Term, TermVisitor};
+
+/// Re-evaluates every solution about to be printed, failing with the
+/// first one which doesn't reach `target` or breaks one of `rules`, or
+/// which uses a number not among `numbers`.
+pub fn output(solutions: &[Solution], numbers: &[u64], target: u64, rules: &dyn Rules)
+    -> Result<(), Box<dyn Error>>
+{
+    for s in solutions.iter() {
+        let fail = |why: String| format!("invalid solution {}: {}", s, why);
+        let value = evaluate(&s.term, rules).map_err(fail)?;
+        if value != target {
+            return Err(fail(format!("evaluates to {}, not {}", value, target)).into());
+        }
+        if !s.term.uses_only(numbers) {
+            return Err(fail("uses numbers which weren't given".to_string()).into());
+        }
+        if !rules.accepts(&s.term, numbers) {
+            return Err(fail("can't be declared under the rules".to_string()).into());
+        }
+    }
+    Ok(())
+}
+
+/// Evaluates a term bottom-up, checking each step is allowed by `rules`
+/// and matches the value stored in the tree.
+fn evaluate(term: &Term, rules: &dyn Rules) -> Result<u64, String> {
+    let mut evaluator = Evaluator { rules, values: Vec::new(), error: None };
+    term.walk(&mut evaluator);
+    match evaluator.error {
+        Some(error) => Err(error),
+        None => Ok(evaluator.values.pop().expect("a term has a value")),
+    }
+}
+
+/// Values of the operands walked so far, and the first mistake found.
+struct Evaluator<'a> {
+    rules: &'a dyn Rules,
+    values: Vec<u64>,
+    error: Option<String>,
+}
+
+impl<'a> TermVisitor for Evaluator<'a> {
+    fn number(&mut self, value: &u64) {
+        if *value == 0 && self.error.is_none() {
+            self.error = Some("uses 0".to_string());
+        }
+        self.values.push(*value);
+    }
+
+    fn leave(&mut self, op: Operator, term: &Term) {
+        let b = self.values.pop().expect("operands are walked first");
+        let a = self.values.pop().expect("operands are walked first");
+        let value = match self.rules.apply(op, &a, &b) {
+            Ok(value) => value,
+            Err(_) => {
+                let error = format!("{} {} {} breaks the rules", a, op.symbol(), b);
+                self.error.get_or_insert(error);
+                term.value
+            },
+        };
+        if value != term.value && self.error.is_none() {
+            self.error = Some(format!("{} is recorded as {}, but is {}",
+                term, term.value, value));
+        }
+        self.values.push(value);
+    }
+}
+
+/// Largest number of starting numbers the oracle is run on.
+#[cfg(feature = "oracle")]
+pub const ORACLE_MAX_NUMBERS: usize = 5;
+
+/// Compares the solutions of a report with those of the reference solver
+/// in [`oracle`], under the same variant rules, failing with the
+/// differences if the sets differ. Unless `dedup` reports repeats, the
+/// solutions are also compared as found, not only canonicalised.
+#[cfg(feature = "oracle")]
+pub fn against_oracle(report: &Report, numbers: &[u64], target: u64, dedup: Dedup,
+    max_intermediate: Option<u64>, must_use: Option<Operator>)
+    -> Result<(), Box<dyn Error>>
+{
+    if numbers.len() > ORACLE_MAX_NUMBERS {
+        return Err(format!("the oracle is only practical for up to {} numbers",
+            ORACLE_MAX_NUMBERS).into());
+    }
+    if report.truncated() {
+        return Err("not every solution was stored, raise --max-solutions \
+                    to compare with the oracle".into());
+    }
+
+    let max_intermediate = max_intermediate.as_ref();
+    let expected = oracle::solve_with(numbers, &target, max_intermediate, must_use);
+    let mut msg = String::new();
+    differences(&mut msg, "", &expected, report.solutions.iter().map(|s| s.canonical()));
+    if dedup == Dedup::Value {
+        let as_found = oracle::solve_as_searched(numbers, &target, max_intermediate, must_use);
+        differences(&mut msg, " as found", &as_found,
+            report.solutions.iter().map(|s| (*s.term).clone()));
+        if msg.is_empty() && report.stats.solutions != as_found.len() {
+            msg = format!("\n  {} solutions counted, not {}", report.stats.solutions,
+                as_found.len());
+        }
+    }
+    if msg.is_empty() {
+        eprintln!("oracle agrees on {} distinct solutions", expected.len());
+        return Ok(());
+    }
+    Err(format!("solutions differ from the oracle:{}", msg).into())
+}
+
+/// Lists the solutions of `found` missing from `expected` and those
+/// unexpected in it on `msg`, one per line, noting `how` they compare.
+#[cfg(feature = "oracle")]
+fn differences<I: Iterator<Item = Term>>(msg: &mut String, how: &str, expected: &[Term], found: I) {
+    let mut found = found.collect::<Vec<_>>();
+    found.sort();
+    found.dedup();
+    for s in expected.iter().filter(|s| !found.contains(s)) {
+        msg.push_str(&format!("\n  missing{} {} = {}", how, s, s.value));
+    }
+    for s in found.iter().filter(|s| !expected.contains(s)) {
+        msg.push_str(&format!("\n  unexpected{} {} = {}", how, s, s.value));
+    }
+}
+
+/// Without the `oracle` feature there's nothing to compare with.
+#[cfg(not(feature = "oracle"))]
+pub fn against_oracle(_: &Report, _: &[u64], _: u64, _: Dedup, _: Option<u64>,
+    _: Option<Operator>) -> Result<(), Box<dyn Error>>
+{
+    Err("built without the oracle feature, rebuild with `--features oracle`".into())
+}
@@ -0,0 +1,87 @@
+//! Watch mode, solving each `target: numbers…` line read from stdin as
+//! soon as it's entered.
+
+use std::error::Error;
+use std::io::{self, BufRead};
+
+use countdown_numbers::Solver;
+
+/// Reads puzzles from stdin until end of input, solving each with a
+/// solver set up by `configure`. Only the recommended solution is printed
+/// when `one` is set. Malformed lines are reported and skipped.
+///
+/// A single solver is set up by `configure` for the first puzzle and
+/// reset for each one after, so the options are only applied once.
+pub fn run<F>(mut configure: F, one: bool) -> Result<(), Box<dyn Error>>
+    where F: FnMut(&mut Solver) -> Result<(), Box<dyn Error>>
+{
+    let stdin = io::stdin();
+    let mut solver: Option<Solver> = None;
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (numbers, target) = match parse_line(&line) {
+            Some(puzzle) => puzzle,
+            None => {
+                eprintln!("expected `target: numbers…`, got `{}`", line.trim());
+                continue;
+            },
+        };
+        let solver = match solver {
+            Some(ref mut solver) => match solver.reset(&numbers[..], target) {
+                Ok(()) => solver,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                },
+            },
+            None => match Solver::new(&numbers[..], target) {
+                Ok(new) => {
+                    let solver = solver.insert(new);
+                    configure(solver)?;
+                    solver
+                },
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                },
+            },
+        };
+        solver.solve();
+
+        let elapsed = solver.elapsed();
+        println!("{} solutions in {}.{:03} seconds", solver.stats().solutions,
+            elapsed.as_secs(), elapsed.subsec_millis());
+        if one {
+            if let Some(s) = solver.recommended() {
+                println!("{}", s);
+            }
+        } else {
+            for s in solver.solutions().iter() {
+                println!("{}", s);
+            }
+        }
+        if solver.solutions().is_empty() {
+            if let Some(c) = solver.closest() {
+                println!("closest {} = {}, {} away",
+                    c, c.value, c.value.abs_diff(target));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses `target: numbers…`, numbers separated by spaces or commas.
+fn parse_line(line: &str) -> Option<(Vec<u64>, u64)> {
+    let i = line.find(':')?;
+    let target = line[..i].trim().parse().ok()?;
+    let numbers = line[i + 1..]
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    Some((numbers, target))
+}
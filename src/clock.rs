@@ -0,0 +1,49 @@
+use core::fmt;
+use core::time::Duration;
+
+/// Source of monotonic time used to measure the search.
+///
+/// The solver never reads the system time itself, so targets without
+/// `std` can plug in a hardware timer, or use [`NoClock`](struct.NoClock.html)
+/// to skip timing altogether.
+pub trait Clock: fmt::Debug {
+    /// Time elapsed since an arbitrary, fixed starting point.
+    fn now(&self) -> Duration;
+}
+
+/// Clock which always reads zero, for when timing is not needed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoClock;
+
+impl Clock for NoClock {
+    fn now(&self) -> Duration {
+        Duration::from_secs(0)
+    }
+}
+
+/// Clock backed by `std::time::Instant`, the default with `std`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct StdClock(::std::time::Instant);
+
+#[cfg(feature = "std")]
+impl StdClock {
+    /// Clock starting at the current instant.
+    pub fn new() -> StdClock {
+        StdClock(::std::time::Instant::now())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+    fn default() -> StdClock {
+        StdClock::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
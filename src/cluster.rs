@@ -0,0 +1,176 @@
+//! Grouping solutions which take the same approach, so a long list can be
+//! summed up by one of each, and measuring how alike two solutions are.
+
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt;
+
+use solution::Solution;
+use term::{Operator, Term};
+use value::Value;
+
+/// Solutions taking the same approach: the same final operation applied
+/// to the same two values. They only differ in how the two values are
+/// made.
+#[derive(Debug, Clone)]
+pub struct Method<'a, V: 'a = u64> {
+    /// The easiest member to find, ties broken by the canonical order
+    pub representative: &'a Solution<V>,
+    /// Every solution taking the approach, the representative included
+    pub members: Vec<&'a Solution<V>>,
+    /// Mean [`similarity`](fn.similarity.html) of the members to the
+    /// representative, 1 when they all calculate the same way
+    pub similarity: f64,
+    /// Each operand of the final operation in canonical form if every
+    /// member makes it the same way, otherwise `None`
+    operands: Option<Operands<V>>,
+}
+
+/// The operands of a final operation, each if it's made the same way by
+/// every member of a method.
+type Operands<V> = (Option<Term<V>>, Option<Term<V>>);
+
+impl<'a, V: Value> Method<'a, V> {
+    /// Number of solutions taking the approach.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Whether there are no solutions, never the case for a method
+    /// returned by [`methods`](fn.methods.html).
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+/// Outlines the approach, such as `(100 * 9) + 52`, writing out each
+/// operand of the final operation which every member makes the same way
+/// and only the value of the others.
+impl<'a, V: Value + fmt::Display> fmt::Display for Method<'a, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let term = &self.representative.term;
+        match (&term.expression, &self.operands) {
+            (&Some((op, ref a, ref b)), &Some((ref ta, ref tb))) => {
+                operand(f, ta, a)?;
+                write!(f, " {} ", op.symbol())?;
+                operand(f, tb, b)?;
+                write!(f, " = {}", term.value)
+            },
+            _ => write!(f, "{} = {}", term, term.value),
+        }
+    }
+}
+
+fn operand<V: fmt::Display>(f: &mut fmt::Formatter, shared: &Option<Term<V>>, term: &Term<V>)
+    -> fmt::Result
+{
+    match *shared {
+        Some(ref t) => write!(f, "{}", t),
+        None => write!(f, "{}", term.value),
+    }
+}
+
+/// What solutions taking the same approach have in common.
+type Key<V> = Option<(Operator, V, V)>;
+
+fn key<V: Value>(solution: &Solution<V>) -> Key<V> {
+    solution.term.expression.as_ref()
+        .map(|&(op, ref a, ref b)| (op, a.value.clone(), b.value.clone()))
+}
+
+/// Groups `solutions` by approach, largest group first, groups of the same
+/// size in the order their first member appears.
+pub fn methods<V: Value>(solutions: &[Solution<V>]) -> Vec<Method<'_, V>> {
+    let mut groups: Vec<(Key<V>, Vec<&Solution<V>>)> = Vec::new();
+    for s in solutions {
+        let k = key(s);
+        match groups.iter_mut().find(|g| g.0 == k) {
+            Some(g) => g.1.push(s),
+            None => groups.push((k, [s].to_vec())),
+        }
+    }
+
+    let mut methods = groups.into_iter().map(|(_, members)| {
+        let representative = *members.iter()
+            .min_by(|a, b| a.difficulty.cmp(&b.difficulty).then_with(|| a.term.cmp(&b.term)))
+            .expect("groups aren't empty");
+        let operands = representative.term.expression.as_ref()
+            .map(|_| (shared(&members, true), shared(&members, false)));
+        let similarity = members.iter()
+            .map(|m| similarity(&m.term, &representative.term))
+            .sum::<f64>() / members.len() as f64;
+        Method { representative, members, similarity, operands }
+    }).collect::<Vec<_>>();
+
+    // Stable, so equally large groups stay in order of appearance
+    methods.sort_by_key(|m| Reverse(m.len()));
+    methods
+}
+
+/// The left or right operand of the final operation in canonical form, if
+/// it's the same for every member.
+fn shared<V: Value>(members: &[&Solution<V>], left: bool) -> Option<Term<V>> {
+    let mut forms = members.iter().filter_map(|m| m.term.expression.as_ref())
+        .map(|(_, a, b)| if left { a } else { b }.as_ref().clone().canonical());
+    let first = forms.next()?;
+    if forms.all(|f| f == first) { Some(first) } else { None }
+}
+
+/// The calculations two expressions make, split by which of them make
+/// each. Calculations are subterms in canonical form, so the order of
+/// operands doesn't matter.
+#[derive(Debug, Clone)]
+pub struct Diff<V = u64> {
+    /// Calculations both make
+    pub shared: Vec<Term<V>>,
+    /// Calculations only the first makes
+    pub only_first: Vec<Term<V>>,
+    /// Calculations only the second makes
+    pub only_second: Vec<Term<V>>,
+}
+
+/// Compares the calculations made by `a` and `b`, each list in the order
+/// the calculations appear in their expression, outermost first.
+pub fn diff<V: Value>(a: &Term<V>, b: &Term<V>) -> Diff<V> {
+    let (a, b) = (a.clone().canonical(), b.clone().canonical());
+    let (mut xs, mut ys) = (Vec::new(), Vec::new());
+    calculations(&a, &mut xs);
+    calculations(&b, &mut ys);
+
+    let mut used = Vec::new();
+    used.resize(ys.len(), false);
+    let (mut shared, mut only_first) = (Vec::new(), Vec::new());
+    for x in xs {
+        match (0..ys.len()).find(|&i| !used[i] && *ys[i] == *x) {
+            Some(i) => {
+                used[i] = true;
+                shared.push(x.clone());
+            },
+            None => only_first.push(x.clone()),
+        }
+    }
+    let only_second = ys.iter().zip(&used).filter(|&(_, &u)| !u).map(|(y, _)| (*y).clone())
+        .collect();
+    Diff { shared, only_first, only_second }
+}
+
+/// How alike two expressions are, from 0 to 1: the share of the
+/// calculations made by either which both make, see [`diff`](fn.diff.html).
+/// Starting numbers on their own are alike if they're equal.
+pub fn similarity<V: Value>(a: &Term<V>, b: &Term<V>) -> f64 {
+    let d = diff(a, b);
+    let all = d.shared.len() + d.only_first.len() + d.only_second.len();
+    if all == 0 {
+        return if a.value == b.value { 1.0 } else { 0.0 };
+    }
+    d.shared.len() as f64 / all as f64
+}
+
+/// Every operation in `term`, as the subterm it makes.
+fn calculations<'a, V>(term: &'a Term<V>, out: &mut Vec<&'a Term<V>>) {
+    if let Some((_, ref a, ref b)) = term.expression {
+        out.push(term);
+        calculations(a, out);
+        calculations(b, out);
+    }
+}
@@ -0,0 +1,33 @@
+use core::fmt;
+
+/// Errors reported by the solver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Fewer than two starting numbers were provided, holds the number given.
+    TooFewNumbers(usize),
+    /// A starting number or the target was zero, only positive integers
+    /// may be used.
+    Zero,
+    /// A solver was built without a target.
+    NoTarget,
+    /// A search run by [`solve_async`](fn.solve_async.html) panicked, in
+    /// its configuration, a custom operation or rules, or the solver. Only
+    /// returned when panics unwind, not with `panic = "abort"`.
+    Panicked,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::TooFewNumbers(n) => {
+                write!(f, "at least two numbers are required, got {}", n)
+            },
+            Error::Zero => f.write_str("numbers and target must be positive"),
+            Error::NoTarget => f.write_str("a target is required"),
+            Error::Panicked => f.write_str("the search panicked"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for Error {}
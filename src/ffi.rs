@@ -0,0 +1,109 @@
+//! C interface to the solver.
+//!
+//! ## Ownership
+//! Every solution string handed out by [`countdown_solve`] is a
+//! NUL-terminated UTF-8 string allocated by this library. The caller owns it
+//! and must release it with [`countdown_string_free`] or, for a whole buffer,
+//! [`countdown_solutions_free`]. Strings must never be passed to the C
+//! `free()`, and the numbers array is only borrowed for the duration of the
+//! call.
+//!
+//! The C header `include/countdown_numbers.h` is generated from this module
+//! with `cbindgen --config cbindgen.toml --output include/countdown_numbers.h`.
+//!
+//! [`countdown_solve`]: fn.countdown_solve.html
+//! [`countdown_string_free`]: fn.countdown_string_free.html
+//! [`countdown_solutions_free`]: fn.countdown_solutions_free.html
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+
+use solver::Solver;
+
+/// Returned by [`countdown_solve`](fn.countdown_solve.html) when a pointer
+/// argument is null, fewer than two numbers are provided or a value is zero.
+pub const COUNTDOWN_ERROR_INVALID_ARGUMENT: i64 = -1;
+
+/// Finds all solutions and writes them, rendered as strings, into a
+/// caller-provided buffer.
+///
+/// At most `solutions_len` strings are written to `solutions`, the remaining
+/// slots are set to null, even when an error is returned. Returns the total
+/// number of solutions found, which may be larger than `solutions_len`, or
+/// a negative error code.
+///
+/// # Safety
+/// `numbers` must point to `numbers_len` readable values and `solutions`
+/// must point to `solutions_len` writable pointers. `solutions` may be null
+/// if `solutions_len` is zero. Every string written must be released with
+/// `countdown_string_free` or `countdown_solutions_free`.
+#[no_mangle]
+pub unsafe extern "C" fn countdown_solve(numbers: *const u64,
+                                         numbers_len: usize,
+                                         target: u64,
+                                         solutions: *mut *mut c_char,
+                                         solutions_len: usize) -> i64
+{
+    if solutions.is_null() && solutions_len > 0 {
+        return COUNTDOWN_ERROR_INVALID_ARGUMENT;
+    }
+    // Every slot not written is null, so the whole buffer can be freed
+    for i in 0..solutions_len {
+        *solutions.add(i) = ptr::null_mut();
+    }
+    if numbers.is_null() {
+        return COUNTDOWN_ERROR_INVALID_ARGUMENT;
+    }
+
+    let numbers = slice::from_raw_parts(numbers, numbers_len);
+    let mut solver = match Solver::new(numbers, target) {
+        Ok(solver) => solver,
+        Err(_) => return COUNTDOWN_ERROR_INVALID_ARGUMENT,
+    };
+    solver.solve();
+
+    for (i, s) in solver.solutions().iter().take(solutions_len).enumerate() {
+        // Rendered terms only contain digits, spaces and operators,
+        // so they never contain an interior NUL byte.
+        let s = CString::new(format!("{}", s.term))
+            .expect("rendered term contains a NUL byte");
+        *solutions.add(i) = s.into_raw();
+    }
+
+    solver.solutions().len() as i64
+}
+
+/// Releases a single string returned by this library. Null is ignored.
+///
+/// # Safety
+/// `s` must be null or a string returned by `countdown_solve` that has not
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn countdown_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Releases the first `len` strings in a buffer filled by `countdown_solve`
+/// and sets each released slot to null. Null slots are skipped, and
+/// `countdown_solve` nulls the slots it doesn't fill, so passing the full
+/// buffer capacity it was given is fine.
+///
+/// # Safety
+/// `solutions` must be null or point to `len` pointers, each of them null or
+/// a string returned by `countdown_solve` that has not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn countdown_solutions_free(solutions: *mut *mut c_char,
+                                                  len: usize)
+{
+    if solutions.is_null() {
+        return;
+    }
+    for slot in slice::from_raw_parts_mut(solutions, len) {
+        countdown_string_free(*slot);
+        *slot = ptr::null_mut();
+    }
+}
@@ -0,0 +1,225 @@
+//! Output formats for the solutions of a search, each a
+//! [`SolutionFormatter`](trait.SolutionFormatter.html) found by name in a
+//! [`Registry`](struct.Registry.html).
+//!
+//! The registry starts with plain text, CSV, LaTeX and Graphviz DOT.
+//! Front ends and downstream crates register their own formats next to
+//! these, or in place of one with the same name.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use solver::Report;
+use term::{Operator, Term, TermVisitor};
+use value::Value;
+
+/// Writes the solutions of a search in some format.
+pub trait SolutionFormatter<V = u64> {
+    /// Name the format is selected by, such as `csv`.
+    fn name(&self) -> &str;
+
+    /// Writes the solutions of `report` to `out`.
+    fn write(&self, report: &Report<V>, out: &mut dyn Write) -> fmt::Result;
+}
+
+/// A solution per line, `((100 + 3) * 9) = 927`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Text;
+
+impl<V: Value> SolutionFormatter<V> for Text {
+    fn name(&self) -> &str {
+        "text"
+    }
+
+    fn write(&self, report: &Report<V>, out: &mut dyn Write) -> fmt::Result {
+        for s in report.solutions.iter() {
+            writeln!(out, "{}", s)?;
+        }
+        Ok(())
+    }
+}
+
+/// Comma separated values with a header, a solution per row.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Csv;
+
+impl<V: Value> SolutionFormatter<V> for Csv {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn write(&self, report: &Report<V>, out: &mut dyn Write) -> fmt::Result {
+        writeln!(out, "expression,value,difficulty,peak,tiles,operations")?;
+        for s in report.solutions.iter() {
+            // Expressions hold no commas or quotes, so need no quoting
+            writeln!(out, "{},{},{},{},{},{}", s.term, s.term.value, s.difficulty, s.peak(),
+                s.tiles(), s.operations())?;
+        }
+        Ok(())
+    }
+}
+
+/// An `align*` environment for LaTeX documents, a solution per line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Latex;
+
+/// Writes a term for [`Latex`](struct.Latex.html), each operand in
+/// parentheses.
+struct LatexTerm<'a> {
+    out: &'a mut dyn Write,
+    /// Operations entered and not yet left
+    depth: usize,
+    /// Outcome of the writes so far, nothing more is written after an error
+    result: fmt::Result,
+}
+
+impl<'a> LatexTerm<'a> {
+    fn write(&mut self, args: fmt::Arguments) {
+        if self.result.is_ok() {
+            self.result = self.out.write_fmt(args);
+        }
+    }
+}
+
+impl<'a, V: Value> TermVisitor<V> for LatexTerm<'a> {
+    fn number(&mut self, value: &V) {
+        self.write(format_args!("{}", value));
+    }
+
+    fn enter(&mut self, _op: Operator, _term: &Term<V>) {
+        if self.depth > 0 {
+            self.write(format_args!("\\left("));
+        }
+        self.depth += 1;
+    }
+
+    fn between(&mut self, op: Operator, _term: &Term<V>) {
+        let symbol = match op {
+            Operator::Addition => "+",
+            Operator::Subtraction => "-",
+            Operator::Multiplication => "\\times",
+            Operator::Division => "\\div",
+            Operator::Custom(c) => c.symbol,
+        };
+        self.write(format_args!(" {} ", symbol));
+    }
+
+    fn leave(&mut self, _op: Operator, _term: &Term<V>) {
+        self.depth -= 1;
+        if self.depth > 0 {
+            self.write(format_args!("\\right)"));
+        }
+    }
+}
+
+impl<V: Value> SolutionFormatter<V> for Latex {
+    fn name(&self) -> &str {
+        "latex"
+    }
+
+    fn write(&self, report: &Report<V>, out: &mut dyn Write) -> fmt::Result {
+        writeln!(out, "\\begin{{align*}}")?;
+        for (i, s) in report.solutions.iter().enumerate() {
+            write!(out, "{} &= ", s.term.value)?;
+            let mut term = LatexTerm { out, depth: 0, result: Ok(()) };
+            s.term.walk(&mut term);
+            term.result?;
+            let end = if i + 1 < report.solutions.len() { " \\\\" } else { "" };
+            writeln!(out, "{}", end)?;
+        }
+        writeln!(out, "\\end{{align*}}")
+    }
+}
+
+/// A Graphviz graph with the expression tree of each solution, render it
+/// with `dot -Tsvg`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dot;
+
+impl Dot {
+    /// Writes the nodes of `term` and the edges to its operands, numbering
+    /// nodes from `next`. Returns the id of the node for `term`.
+    fn term<V: Value>(term: &Term<V>, prefix: usize, next: &mut usize, out: &mut dyn Write)
+        -> Result<usize, fmt::Error>
+    {
+        let id = *next;
+        *next += 1;
+        match term.expression {
+            Some((op, ref a, ref b)) => {
+                writeln!(out, "    n{}_{} [label=\"{}\\n{}\"];", prefix, id, op.symbol(),
+                    term.value)?;
+                for operand in [a, b].iter() {
+                    let child = Dot::term(operand, prefix, next, out)?;
+                    writeln!(out, "    n{}_{} -> n{}_{};", prefix, id, prefix, child)?;
+                }
+            },
+            None => writeln!(out, "    n{}_{} [label=\"{}\", shape=box];", prefix, id,
+                term.value)?,
+        }
+        Ok(id)
+    }
+}
+
+impl<V: Value> SolutionFormatter<V> for Dot {
+    fn name(&self) -> &str {
+        "dot"
+    }
+
+    fn write(&self, report: &Report<V>, out: &mut dyn Write) -> fmt::Result {
+        writeln!(out, "digraph solutions {{")?;
+        for (i, s) in report.solutions.iter().enumerate() {
+            writeln!(out, "  subgraph cluster_{} {{", i)?;
+            writeln!(out, "    label=\"{}\";", s)?;
+            Dot::term(&s.term, i, &mut 0, out)?;
+            writeln!(out, "  }}")?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+/// Formatters by name.
+pub struct Registry<V = u64> {
+    formatters: Vec<Box<dyn SolutionFormatter<V>>>,
+}
+
+impl<V> fmt::Debug for Registry<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl<V: Value> Default for Registry<V> {
+    /// Registry of the built-in formats, text, CSV, LaTeX and DOT.
+    fn default() -> Registry<V> {
+        let mut registry = Registry::new();
+        registry.register(Text);
+        registry.register(Csv);
+        registry.register(Latex);
+        registry.register(Dot);
+        registry
+    }
+}
+
+impl<V> Registry<V> {
+    /// Registry without any formats.
+    pub fn new() -> Registry<V> {
+        Registry { formatters: Vec::new() }
+    }
+
+    /// Adds a format, replacing one with the same name.
+    pub fn register<F: SolutionFormatter<V> + 'static>(&mut self, formatter: F) {
+        self.formatters.retain(|f| f.name() != formatter.name());
+        self.formatters.push(Box::new(formatter));
+    }
+
+    /// The format named `name`.
+    pub fn get(&self, name: &str) -> Option<&dyn SolutionFormatter<V>> {
+        self.formatters.iter().find(|f| f.name() == name).map(|f| &**f)
+    }
+
+    /// Names of the formats, in the order they were registered.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.formatters.iter().map(|f| f.name())
+    }
+}
@@ -0,0 +1,133 @@
+//! A heuristic model of how people solve rounds, used to rank solutions by
+//! how likely a contestant is to find them.
+
+use alloc::vec::Vec;
+use core::fmt;
+use term::{Operator, Term};
+
+/// A solution split the way a person would plan it: a goal near the
+/// target, usually a product, then additions and subtractions to adjust
+/// it to the target.
+#[derive(Debug, Clone)]
+pub struct Plan<'a, V: 'a = u64> {
+    /// The value aimed for, the whole solution if it isn't adjusted
+    pub goal: &'a Term<V>,
+    /// Steps adjusting the goal to the target, in the order they are
+    /// applied
+    pub adjustments: Vec<Adjustment<'a, V>>,
+}
+
+/// Adding or subtracting a term on the way from the goal to the target.
+#[derive(Debug, Clone, Copy)]
+pub struct Adjustment<'a, V: 'a = u64> {
+    /// `Addition` or `Subtraction`
+    pub op: Operator,
+    /// The term added or subtracted
+    pub operand: &'a Term<V>,
+    /// The term after the adjustment
+    pub result: &'a Term<V>,
+}
+
+/// Splits `term` into a goal and the adjustments reaching the target from
+/// it. Subtractions are followed down the minuend and additions down the
+/// operand with more operations, the larger one if tied.
+pub fn plan<'a, V>(term: &'a Term<V>) -> Plan<'a, V> {
+    let mut adjustments = Vec::new();
+    let mut goal = term;
+
+    while let Some((op, ref a, ref b)) = goal.expression {
+        let (main, operand) = match op {
+            Operator::Addition if b.operations() > a.operations() => (b, a),
+            Operator::Addition | Operator::Subtraction => (a, b),
+            Operator::Multiplication | Operator::Division | Operator::Custom(_) => break,
+        };
+        adjustments.push(Adjustment { op, operand, result: goal });
+        goal = main;
+    }
+
+    adjustments.reverse();
+    Plan { goal, adjustments }
+}
+
+/// Heuristic cost of spotting `term` within the time limit, lower is
+/// easier. Solutions of equal cost are equally findable.
+///
+/// Every operation costs a little, division more than the others.
+/// Subtracting or multiplying by a number of several digits is penalized
+/// for each extra digit, and combining two intermediate results costs
+/// extra since both have to be held in mind at once. Deeply nested
+/// expressions are penalized for each level past the second.
+pub fn cost<V: fmt::Display>(term: &Term<V>) -> u32 {
+    let nesting = term.depth().saturating_sub(2) as u32;
+    operation_cost(term) + 2 * nesting
+}
+
+fn operation_cost<V: fmt::Display>(term: &Term<V>) -> u32 {
+    let (op, a, b) = match term.expression {
+        Some((op, ref a, ref b)) => (op, a, b),
+        None => return 0,
+    };
+
+    let mut cost = match op {
+        Operator::Addition => 1,
+        Operator::Subtraction => 2,
+        Operator::Multiplication => 2,
+        Operator::Division | Operator::Custom(_) => 5,
+    };
+
+    match op {
+        Operator::Subtraction | Operator::Division => {
+            cost += 2 * (digits(&b.value) - 1);
+        },
+        Operator::Multiplication => {
+            cost += 2 * (digits(&a.value).min(digits(&b.value)) - 1);
+        },
+        Operator::Addition | Operator::Custom(_) => (),
+    }
+
+    if a.expression.is_some() && b.expression.is_some() {
+        cost += 3;
+    }
+
+    cost + operation_cost(a) + operation_cost(b)
+}
+
+/// Rates a round from 1 to 10 by its solutions, `None` if there are none.
+///
+/// The rating follows the [`cost`](fn.cost.html) of the easiest solution,
+/// a round with many solutions is rated a level easier since one is more
+/// likely to be stumbled upon.
+pub fn rate<'a, V, I>(solutions: I) -> Option<u32>
+    where V: fmt::Display + 'a, I: IntoIterator<Item = &'a Term<V>>
+{
+    let mut count = 0;
+    let mut easiest = None;
+    for s in solutions {
+        let c = cost(s);
+        easiest = Some(easiest.map_or(c, |e: u32| e.min(c)));
+        count += 1;
+    }
+
+    easiest.map(|c| {
+        let level = c.div_ceil(3);
+        let level = if count >= 50 { level.saturating_sub(1) } else { level };
+        level.clamp(1, 10)
+    })
+}
+
+/// Number of decimal digits in `value`, counted from its `Display` output
+/// since `Value` has no conversion to a machine integer.
+fn digits<V: fmt::Display>(value: &V) -> u32 {
+    struct Count(u32);
+
+    impl fmt::Write for Count {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.len() as u32;
+            Ok(())
+        }
+    }
+
+    let mut count = Count(0);
+    let _ = fmt::write(&mut count, format_args!("{}", value));
+    count.0.max(1)
+}
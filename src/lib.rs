@@ -0,0 +1,143 @@
+//! This crate finds all solutions to a numbers round from the popular
+//! British tv show Countdown.
+//!
+//!
+//! ## Rules
+//! The rules of the Countdown Numbers Game are as follow:
+//!
+//! The contestant chooses six numbers from two groups of, 20 small numbers and
+//! 4 large numbers. The numbers consist of two each of numbers 1 through 10.
+//! The 4 large numbers are 25, 50, 75 and 100. The contestant decides how many
+//! large numbers are to be used, from none to all four, the rest will be small
+//! numbers.
+//!
+//! A random three-digit target is generated. The contestants have 30 seconds
+//! to work out a sequence of calculations with the numbers whose final result
+//! is as close to the target number as possible. They may use only the four
+//! basic operations of addition, subtraction, multiplication and division,
+//! and do not have to use all six numbers. Fractions are not allowed, and only
+//! positive integers may be obtained as a result at any stage of the calculation.
+//!
+//!
+//! ## Algorithm and optimizations
+//! The general approach is to recursively combine terms into a binary
+//! expression tree while continuously testing if an expression is a valid
+//! solution. The rules allow for the following optimization:
+//!
+//! When applying an operator to two terms, we only consider the expression
+//! where the terms are from largest to smallest (5 - 3). This a valid since
+//! addition and multiplication is commutative, we don’t allow negative
+//! values at any intermediate step, we don’t allow fractions.
+//!
+//!
+//! ## `no_std`
+//! The solver only needs `alloc`. Without the default `std` feature the
+//! crate is `#![no_std]`, the C and Python interfaces are left out and
+//! searches are untimed unless a [`Clock`](trait.Clock.html) is provided.
+//!
+//!
+//! ## Embedding
+//! Besides the Rust API, the `countdown-numbers-ffi` crate in `ffi/` builds
+//! a `cdylib` exposing a small C interface, see the
+//! [`ffi`](ffi/index.html) module and `include/countdown_numbers.h`. With the `python` feature it doubles as
+//! a Python extension module, see `pyproject.toml`. The `serde` feature
+//! implements `Serialize` and `Deserialize` for [`Term`](struct.Term.html),
+//! [`Solution`](struct.Solution.html) and [`Report`](struct.Report.html).
+//! The `tracing` feature emits spans for the solve phases and an event for
+//! each solution found.
+//!
+//! Servers and GUIs which must stay responsive can await
+//! [`solve_async`](fn.solve_async.html) instead, which searches on a
+//! thread of its own and stops early, keeping what it found, when its
+//! [`CancellationToken`](struct.CancellationToken.html) is cancelled.
+//!
+//!
+//! ## Value types
+//! Terms are calculated in `u64` by default. The solver is generic over
+//! [`Value`](trait.Value.html), which is also implemented for `u128` and,
+//! with the `bigint` feature, `num_bigint::BigUint` for variants with
+//! very large numbers.
+//!
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// Injected by `no_std`, otherwise needed for `core` paths from the crate root
+#[cfg(feature = "std")]
+extern crate core;
+
+#[cfg(feature = "bigint")]
+extern crate num_bigint;
+#[cfg(feature = "bigint")]
+extern crate num_traits;
+#[cfg(feature = "python")]
+extern crate pyo3;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+
+mod builder;
+mod clock;
+pub mod cluster;
+mod error;
+pub mod format;
+pub mod human;
+mod parse;
+pub mod presets;
+#[cfg(feature = "profile")]
+pub mod profile;
+mod reach;
+pub mod rules;
+pub mod scoring;
+pub mod strategy;
+mod term;
+mod solution;
+mod solver;
+mod stack;
+#[cfg(feature = "std")]
+mod task;
+mod value;
+#[cfg(feature = "std")]
+pub mod ffi;
+#[cfg(any(test, feature = "oracle"))]
+pub mod oracle;
+#[cfg(feature = "python")]
+mod python;
+
+#[cfg(feature = "std")]
+pub use clock::StdClock;
+pub use builder::SolverBuilder;
+pub use clock::{Clock, NoClock};
+pub use error::Error;
+pub use parse::{parse, ParseError};
+pub use reach::Reachable;
+pub use term::{Custom, Expr, Operation, Operator, Term, TermVisitor};
+pub use solution::Solution;
+pub use solver::{search_space, Dedup, Engine, Node, Observer, Prune, Report, SearchOrder,
+    Solver, Stats};
+#[cfg(feature = "std")]
+pub use task::{solve_async, solve_async_with, CancellationToken, SolveFuture};
+pub use value::{Value, SMALL_PRIMES};
+
+/// The rules of the Countdown Numbers Game, as printed by `--rules`.
+pub const RULES: &str = "\
+The rules of the Countdown Numbers Game are as follow:
+
+The contestant chooses six numbers from two groups of,
+20 small numbers and 4 large numbers. The numbers consist
+of two each of numbers 1 through 10. The 4 large numbers
+are 25, 50, 75 and 100. The contestant decides how many
+large numbers are to be used, from none to all four,
+the rest will be small numbers.
+
+A random three-digit target is generated. The contestants
+have 30 seconds to work out a sequence of calculations with
+the numbers whose final result is as close to the target
+number as possible. They may use only the four basic
+operations of addition, subtraction, multiplication and
+division, and do not have to use all six numbers.
+Fractions are not allowed, and only positive integers may
+be obtained as a result at any stage of the calculation.
+";
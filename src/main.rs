@@ -1,247 +1,226 @@
-//! This program finds all solutions to a numbers round from the popular
-//! British tv show Countdown.
-//!
-//!
-//! ## Rules
-//! The rules of the Countdown Numbers Game are as follow:
-//!
-//! The contestant chooses six numbers from two groups of, 20 small numbers and
-//! 4 large numbers. The numbers consist of two each of numbers 1 through 10.
-//! The 4 large numbers are 25, 50, 75 and 100. The contestant decides how many
-//! large numbers are to be used, from none to all four, the rest will be small
-//! numbers.
-//!
-//! A random three-digit target is generated. The contestants have 30 seconds
-//! to work out a sequence of calculations with the numbers whose final result
-//! is as close to the target number as possible. They may use only the four
-//! basic operations of addition, subtraction, multiplication and division,
-//! and do not have to use all six numbers. Fractions are not allowed, and only
-//! positive integers may be obtained as a result at any stage of the calculation.
-//!
-//!
-//! ## Algorithm and optimizations
-//! The general approach is to recursively combine terms into a binary
-//! expression tree while continuously testing if an expression is a valid
-//! solution. The rules allow for the following optimization:
-//!
-//! When applying an operator to two terms, we only consider the expression
-//! where the terms are from largest to smallest (5 - 3). This a valid since
-//! addition and multiplication is commutative, we don’t allow negative
-//! values at any intermediate step, we don’t allow fractions.
-//!
+//! Command line interface for the Countdown Numbers Game solver,
+//! see the `countdown_numbers` library crate for the solver itself.
 
 extern crate rand;
+#[macro_use]
 extern crate clap;
+extern crate countdown_numbers;
+extern crate indicatif;
+#[cfg(feature = "parquet")]
+extern crate parquet;
+#[cfg(feature = "sqlite")]
+extern crate rusqlite;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate toml;
 
-use clap::{App, Arg};
-use rand::Rng;
+mod cli;
 
-/// The four basic mathematical operations
-#[derive(Debug, Clone, Copy)]
-enum Operator {
-    Addition,
-    Subtraction,
-    Multiplication,
-    Division,
-}
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use countdown_numbers::{cluster, presets, search_space, Dedup, Operator, Reachable, SearchOrder,
+    Solver};
+use countdown_numbers::rules::{Countdown, Rules};
+use countdown_numbers::strategy::Human;
+use cli::metrics::{self, Metrics, Phases};
+use cli::{advise, analyze, bench, cache, capabilities, compare, curriculum, diff, drill, export, fingerprint, formats, leaderboard, man, multiplayer, one_away, pack, play, preset, random, scan, scoring, simulate, sweep, table, verify, watch};
+use cli::tsv::Tsv;
+use cli::lang;
+use cli::plain;
+use cli::progress::Progress;
+use cli::teach;
+use cli::theme::{self, Role};
+use cli::trace::Recorder;
+use std::env;
+use std::error::Error;
+use std::process;
+use std::time::Instant;
 
-/// Basic mathematical expression with two terms and an operator,
-/// forms a binary expression tree.
-type Expr = (Operator, Box<Term>, Box<Term>);
-
-/// Mathematical Term
-#[derive(Debug, Clone)]
-struct Term {
-    /// Expression used to calculate this term.
-    expression: Option<Expr>,
-    /// Integer value of the term
-    value: usize,
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
 }
 
-
-/// Countdown Numbers game solver
-#[derive(Debug)]
-struct Solver {
-    /// Stack of remaining terms
-    remaining: Vec<Box<Term>>,
-    /// List of solutions found
-    solutions: Vec<Box<Term>>,
-    /// Target number
-    target: usize,
-    // Number of expressions evaluated
-    counter: usize,
+/// clap validator for starting numbers and targets, which must be
+/// positive integers.
+fn validate_positive(s: String) -> Result<(), String> {
+    match s.parse::<u64>() {
+        Ok(0) => Err("must be a positive number, not 0".to_string()),
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("`{}` is not a positive whole number", s)),
+    }
 }
 
-impl std::fmt::Display for Term {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        use Operator::*;
-        match self.expression {
-            Some((ref op, ref a, ref b)) => {
-                match *op {
-                    Addition => write!(f, "({} + {})", a, b),
-                    Subtraction => write!(f, "({} - {})", a, b),
-                    Multiplication => write!(f, "({} * {})", a, b),
-                    Division => write!(f, "({} / {})", a, b),
-                }
-            },
-            None => write!(f, "{}", self.value),
-        }
+/// Parses a difficulty band such as `4-7`, or a single rating, from 1 to 10.
+fn parse_band(s: &str) -> Result<(u32, u32), String> {
+    let err = || format!("`{}` is not a difficulty from 1 to 10 or a range like 4-7", s);
+    let (min, max) = match s.find('-') {
+        Some(i) => (&s[..i], &s[i + 1..]),
+        None => (s, s),
+    };
+    match (min.trim().parse::<u32>(), max.trim().parse::<u32>()) {
+        (Ok(min), Ok(max)) if 1 <= min && min <= max && max <= 10 => Ok((min, max)),
+        _ => Err(err()),
     }
 }
 
-impl PartialEq for Term {
-    fn eq(&self, other: &Term) -> bool {
-        use Operator::*;
+/// Most targets a list given to `parse_targets` may expand to.
+const MAX_TARGETS: u64 = 1_000_000;
 
-        if self.value != other.value {
-            return false;
-        }
+/// Most starting numbers `--targets`, `--scan`, `--table` and `compare`
+/// take. Every subset's values are built, so each number more takes over
+/// ten times as long: nine already take seconds.
+const MAX_REACHABLE_NUMBERS: usize = 8;
 
-        match (&self.expression, &other.expression) {
-            (&Some((ref op1, ref a1, ref b1)),
-             &Some((ref op2, ref a2, ref b2))) =>
-            {
-                match (op1, op2) {
-                    (&Addition, &Addition) => (),
-                    (&Subtraction, &Subtraction) => (),
-                    (&Multiplication, &Multiplication) => (),
-                    (&Division, &Division) => (),
-                    _ => return false,
-                }
+/// Fails unless `numbers` are few enough for `mode` to build the values
+/// reachable from them.
+fn check_reachable(mode: &str, numbers: &[u64]) -> Result<(), String> {
+    if numbers.len() > MAX_REACHABLE_NUMBERS {
+        return Err(format!("{} takes at most {} numbers, got {}",
+            mode, MAX_REACHABLE_NUMBERS, numbers.len()));
+    }
+    Ok(())
+}
 
-                a1.eq(a2) && b1.eq(b2)
+/// Parses a list of targets such as `312,487,952`, where each item may
+/// also be an inclusive range such as `100..999`, of at most
+/// `MAX_TARGETS` targets in all.
+fn parse_targets(s: &str) -> Result<Vec<u64>, String> {
+    let mut targets = Vec::new();
+    for item in s.split(',') {
+        let err = || format!("`{}` is not a positive number or a range like 100..999", item);
+        let (start, end) = match item.find("..") {
+            Some(i) => (&item[..i], &item[i + 2..]),
+            None => (item, item),
+        };
+        match (start.trim().parse::<u64>(), end.trim().parse::<u64>()) {
+            (Ok(start), Ok(end)) if 0 < start && start <= end => {
+                if end - start >= MAX_TARGETS - targets.len() as u64 {
+                    return Err(format!("more than {} targets", thousands(MAX_TARGETS)));
+                }
+                targets.extend(start..=end)
             },
-            (&None, &None) => true,
-            _ => false,
+            _ => return Err(err()),
         }
     }
+    Ok(targets)
 }
 
-impl Solver {
-    /// Initiate Solver
-    fn new(numbers: &[usize], target: usize) -> Solver {
-        let mut remaining = numbers.iter()
-            .map(|i| Box::new(Term{
-                expression: None,
-                value: *i,
-            })).collect::<Vec<_>>();
-
-        remaining.sort_by(|a, b| a.value.cmp(&b.value).reverse());
-
-        Solver {
-            remaining: remaining,
-            solutions: Vec::new(),
-            target: target,
-            counter: 0,
+/// `n` with commas between groups of three digits, such as 7,425.
+fn thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
         }
+        out.push(c);
     }
+    out
+}
 
-    /// Test an expression as a solution, then continue combining terms.
-    fn try_expr(&mut self, expr: Expr) -> Expr {
-        assert!(expr.1.value >= expr.2.value, "terms vector is not sorted");
-
-        // Calculate expression into new term
-        let mut c = Box::new(match expr.0 {
-            Operator::Addition => Term {
-                value: expr.1.value + expr.2.value,
-                expression: Some(expr),
-            },
-            Operator::Subtraction => {
-                // Negative intermediate values are not allowed in countdown 
-                // and zero is not a useful term.
-                if expr.1.value <= expr.2.value {
-                    return expr;
-                }
-                Term {
-                    value: expr.1.value - expr.2.value,
-                    expression: Some(expr),
-                }
-            },
-            Operator::Multiplication => Term {
-                value: expr.1.value * expr.2.value,
-                expression: Some(expr),
-            },
-            Operator::Division => {
-                // Fractions are not allowed in countdown
-                if expr.1.value % expr.2.value != 0 {
-                    return expr;
-                }
-                Term {
-                    value: expr.1.value / expr.2.value,
-                    expression: Some(expr),
-                }
-            },
-        });
-
-        self.counter += 1;
-        
-        // Test if this is a valid solution
-        if c.value == self.target && !self.solutions.contains(&c) {
-            self.solutions.push(c.clone());
-        }
-
-        if self.remaining.len() > 0 {
-            // Find Insert position so self.remaining remains sorted
-            let pos = {
-                let mut pos = 0;
-                let mut iter = self.remaining.iter();
-                while let Some(k) = iter.next() {
-                    if k.value <= c.value {
-                        break;
-                    }
-                    pos += 1;
-                }
-                pos
-            };
+/// clap validator for an operator name, see `Operator::from_name`.
+fn validate_operator(s: String) -> Result<(), String> {
+    match Operator::from_name(&s) {
+        Some(_) => Ok(()),
+        None => Err(format!("`{}` is not an operator, add, sub, mul or div", s)),
+    }
+}
 
-            // Insert new term and continue recursively combining terms.
-            // The stack is returned to its original state after the recursive
-            // call so we can pop our term, deconstruct it and return
-            // the expression when we are done.
-            self.remaining.insert(pos, c);
-            self.solve();
-            c = self.remaining.remove(pos);
-        }
-        c.expression.unwrap()
+/// clap validator for `--format`, one of the formats registered.
+fn validate_format(s: String) -> Result<(), String> {
+    let names = formats::names();
+    if names.contains(&s) {
+        Ok(())
+    } else {
+        Err(format!("`{}` is not a format, {}", s, names.join(", ")))
     }
+}
 
-    /// Finds all valid expressions resulting in the target number.
-    /// Recursively combines two and two terms into a binary expression tree,
-    /// test if it’s a valid solution as we go along.
-    fn solve(&mut self) {
-        for i in 0..self.remaining.len() {
-            let mut a = self.remaining.remove(i);
-            for j in i..self.remaining.len() {
-                let mut expr = (Operator::Addition, a, self.remaining.remove(j));
-                expr = self.try_expr(expr);
+/// Value of the global `arg`, given before or after a subcommand.
+fn global<'a>(matches: &'a ArgMatches, arg: &str) -> Option<&'a str> {
+    let mut value = matches.value_of(arg);
+    let mut m = matches;
+    while let (_, Some(sub)) = m.subcommand() {
+        value = sub.value_of(arg).or(value);
+        m = sub;
+    }
+    value
+}
 
-                expr.0 = Operator::Subtraction;
-                expr = self.try_expr(expr);
+/// Whether the global flag `arg` is given, before or after a subcommand.
+fn global_flag(matches: &ArgMatches, arg: &str) -> bool {
+    let mut present = matches.is_present(arg);
+    let mut m = matches;
+    while let (_, Some(sub)) = m.subcommand() {
+        present |= sub.is_present(arg);
+        m = sub;
+    }
+    present
+}
 
-                expr.0 = Operator::Multiplication;
-                expr = self.try_expr(expr);
+/// Operators named by the values of `arg`.
+fn operators(matches: &ArgMatches, arg: &str) -> Vec<Operator> {
+    matches.values_of(arg).into_iter().flatten()
+        .map(|s| Operator::from_name(s).expect("clap checks operator names"))
+        .collect()
+}
 
-                expr.0 = Operator::Division;
-                expr = self.try_expr(expr);
+/// clap validator for the number of big numbers, from 0 to 4.
+fn validate_num_big(s: String) -> Result<(), String> {
+    match s.parse::<usize>() {
+        Ok(n) if n <= 4 => Ok(()),
+        _ => Err(format!("`{}` is not a number from 0 to 4", s)),
+    }
+}
 
-                self.remaining.insert(j, expr.2);
-                a = expr.1;
-            }
-            self.remaining.insert(i, a);
-        }
+/// Applies the options shared by every way of solving to `solver`.
+fn configure(matches: &ArgMatches, solver: &mut Solver) -> Result<(), Box<dyn Error>> {
+    solver.set_max_solutions(value_t!(matches, "max-solutions", usize)?);
+    if matches.is_present("max-intermediate") {
+        solver.set_max_intermediate(value_t!(matches, "max-intermediate", u64)?);
+    }
+    if matches.is_present("no-dedup") {
+        solver.set_dedup(Dedup::None);
+    }
+    if matches.value_of("ordering") == Some("naive") {
+        solver.set_order(SearchOrder::Naive);
     }
+    if let Some(&op) = operators(matches, "must-use-op").first() {
+        solver.set_must_use(op);
+    }
+    if matches.is_present("sample") {
+        solver.set_sampling(rand::random());
+    }
+    Ok(())
 }
 
-fn main() {
-    let matches = App::new("countdown-numbers")
+/// Command line interface definition, also used to generate the man page.
+/// `--export`, taken by the commands which rate selections.
+fn export_arg() -> Arg<'static, 'static> {
+    Arg::with_name("export")
+        .long("export")
+        .takes_value(true)
+        .value_name("KIND:PATH")
+        .validator(|s| export::parse(&s).map(|_| ()))
+        .help("Also writes the results to a database or file, such as \n\
+              sqlite:results.db or parquet:results.parquet")
+}
+
+fn app() -> App<'static, 'static> {
+    App::new("countdown-numbers")
         .version("0.1.0")
         .author("Henning Ottesen <henning@live.no>")
         .about("Countdown Numbers Game Solver")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(Arg::with_name("random")
             .short("r")
             .takes_value(true)
             .display_order(1)
             .value_name("NUM_BIG_NUMS")
+            .validator(validate_num_big)
             .help("Randomly choose the numbers and the target,\n\
                    overrides provided numbers and target.\n\
                    Takes number of big numbers as value, from 0 to 4.")
@@ -250,89 +229,981 @@ fn main() {
             .long("rules")
             .help("Prints the rules of the Countdown Numbers Game")
         )
+        .arg(Arg::with_name("capabilities")
+            .long("capabilities")
+            .help("Prints the engines, formats, operators, presets and \n\
+                   protocol versions supported, as JSON for other tools")
+        )
+        .arg(Arg::with_name("strict")
+            .long("strict")
+            .help("Rejects puzzles which couldn't occur on the show")
+        )
+        .arg(Arg::with_name("quiet")
+            .short("q")
+            .long("quiet")
+            .help("Don't print notes about puzzles which differ from the show")
+        )
+        .arg(Arg::with_name("metrics")
+            .long("metrics")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Writes metrics of the run as JSON to FILE, or stderr if FILE is -")
+        )
+        .arg(Arg::with_name("trace")
+            .long("trace")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Records every node the search expands to FILE as JSON lines")
+        )
+        .arg(Arg::with_name("max-solutions")
+            .long("max-solutions")
+            .takes_value(true)
+            .value_name("N")
+            .default_value("10000")
+            .validator(validate_positive)
+            .help("Stores and prints at most N solutions, further solutions are only counted")
+        )
+        .arg(Arg::with_name("sample")
+            .long("sample")
+            .help("Past --max-solutions, keeps a uniform random sample of \n\
+                   all solutions instead of the first ones")
+        )
+        .arg(Arg::with_name("no-dedup")
+            .long("no-dedup")
+            .help("Reports every solution as found, without merging solutions \n\
+                   which only differ in the copy of a repeated number used")
+        )
+        .arg(Arg::with_name("ordering")
+            .long("ordering")
+            .takes_value(true)
+            .possible_values(&["learned", "naive"])
+            .default_value("learned")
+            .help("Order pairs of numbers and operators are tried in, learned from \n\
+                   those leading to solutions most often, or largest numbers first")
+        )
+        .arg(Arg::with_name("max-intermediate")
+            .long("max-intermediate")
+            .visible_alias("max-peak")
+            .takes_value(true)
+            .value_name("MAX")
+            .validator(validate_positive)
+            .help("Variant rule, no value in a calculation may exceed MAX, so only \n\
+                   solutions which can be worked out without a calculator are found")
+        )
+        .arg(Arg::with_name("with")
+            .long("with")
+            .takes_value(true)
+            .multiple(true)
+            .use_delimiter(true)
+            .number_of_values(1)
+            .value_name("OP")
+            .validator(validate_operator)
+            .help("Shows only solutions using OP, one of add, sub, mul or div, \n\
+                   the search itself is unchanged")
+        )
+        .arg(Arg::with_name("without")
+            .long("without")
+            .takes_value(true)
+            .multiple(true)
+            .use_delimiter(true)
+            .number_of_values(1)
+            .value_name("OP")
+            .validator(validate_operator)
+            .help("Shows only solutions avoiding OP, such as div to find a \n\
+                   division-free route")
+        )
+        .arg(Arg::with_name("methods")
+            .long("methods")
+            .conflicts_with("one")
+            .help("Prints one solution for each approach, with how many \n\
+                   variations of it there are, instead of every solution")
+        )
+        .arg(Arg::with_name("tiles-exactly")
+            .long("tiles-exactly")
+            .takes_value(true)
+            .value_name("N")
+            .validator(validate_positive)
+            .help("Shows only solutions using exactly N of the starting numbers")
+        )
+        .arg(Arg::with_name("must-use-op")
+            .long("must-use-op")
+            .takes_value(true)
+            .value_name("OP")
+            .validator(validate_operator)
+            .help("Only counts solutions using OP as solutions, for drills \n\
+                   practising add, sub, mul or div")
+        )
+        .arg(Arg::with_name("best")
+            .long("best")
+            .takes_value(true)
+            .value_name("K")
+            .validator(validate_positive)
+            .help("Finds only the K solutions with the fewest operations, \n\
+                   ties broken by the smaller largest intermediate value")
+        )
+        .arg(Arg::with_name("one")
+            .long("one")
+            .help("Prints only the most elegant solution, using the fewest \n\
+                   numbers and the smallest values, avoiding division")
+        )
+        .arg(Arg::with_name("sort")
+            .long("sort")
+            .takes_value(true)
+            .value_name("ORDER")
+            .possible_values(&["canonical", "human"])
+            .default_value("canonical")
+            .help("Order of the printed solutions, human lists the ones \n\
+                   a person is most likely to spot first")
+        )
+        .arg(Arg::with_name("teach")
+            .long("teach")
+            .help("Explains how to find the solution --one would print, \n\
+                   from a goal near the target to the steps adjusting it")
+        )
+        .arg(Arg::with_name("one-away")
+            .long("one-away")
+            .help("Shows how to make the values one either side of the \n\
+                   target, and whether stopping one away was forced")
+        )
+        .arg(Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .value_name("FORMAT")
+            .validator(validate_format)
+            .default_value("text")
+            .help("Output format, json prints only a report of the search, \n\
+                   compact a single line for chat bots, tsv a solution \n\
+                   per line with tab separated fields, and csv, latex and \n\
+                   dot the solutions as CSV, a LaTeX align* or a Graphviz \n\
+                   graph")
+        )
+        .arg(Arg::with_name("header")
+            .long("header")
+            .help("Starts --format tsv with a line naming the fields")
+        )
+        .arg(Arg::with_name("null")
+            .long("null")
+            .short("z")
+            .help("Ends the lines of --format tsv with NUL instead of newline")
+        )
+        .arg(Arg::with_name("verify-against-oracle")
+            .long("verify-against-oracle")
+            .help("Checks the solutions against a slow reference solver, \n\
+                   for up to 5 numbers, needs the oracle feature")
+        )
+        .arg(Arg::with_name("verify-output")
+            .long("verify-output")
+            .help("Re-evaluates every solution before it's printed, \n\
+                   failing if one breaks the rules")
+        )
+        .arg(Arg::with_name("fingerprint")
+            .long("fingerprint")
+            .help("Prints a hash of the set of solutions, to compare runs \n\
+                   across versions and machines")
+        )
+        .arg(Arg::with_name("scoring")
+            .long("scoring")
+            .takes_value(true)
+            .global(true)
+            .value_name("SCHEME")
+            .default_value("official")
+            .help("How answers are scored: official 10/7/5, linear losing \n\
+                   a point per step from 10, linear:N from N, or a JSON \n\
+                   or TOML file of bands")
+        )
+        .arg(Arg::with_name("theme")
+            .long("theme")
+            .takes_value(true)
+            .global(true)
+            .value_name("THEME")
+            .possible_values(&theme::NAMES)
+            .help("Colour theme for output on a terminal, also read from \n\
+                   $COUNTDOWN_THEME, monochrome styles without colour")
+        )
+        .arg(Arg::with_name("lang")
+            .long("lang")
+            .takes_value(true)
+            .global(true)
+            .value_name("LANG")
+            .possible_values(&lang::CODES)
+            .help("Language of the rules, play mode and --teach, also read \n\
+                   from $COUNTDOWN_LANG or $LANG")
+        )
+        .arg(Arg::with_name("plain")
+            .long("plain")
+            .global(true)
+            .help("Output for screen readers: no colour, no progress bars, \n\
+                   only ASCII and operators as words")
+        )
+        .arg(Arg::with_name("targets")
+            .long("targets")
+            .takes_value(true)
+            .value_name("TARGETS")
+            .validator(|s| parse_targets(&s).map(|_| ()))
+            .help("Solves for each of a list of targets such as 312,487,952 \n\
+                   or 100..999, giving one expression per target. Every \n\
+                   positional argument is then a starting number")
+        )
+        .arg(Arg::with_name("scan")
+            .long("scan")
+            .takes_value(true)
+            .value_name("TARGETS")
+            .conflicts_with("targets")
+            .validator(|s| parse_targets(&s).map(|_| ()))
+            .help("Maps which of a range of targets such as 100..999 are \n\
+                   reached exactly, within 5 or within 10. Every \n\
+                   positional argument is then a starting number")
+        )
+        .arg(Arg::with_name("table")
+            .long("table")
+            .takes_value(true)
+            .value_name("TARGETS")
+            .conflicts_with_all(&["targets", "scan"])
+            .validator(|s| parse_targets(&s).map(|_| ()))
+            .help("Lists every reachable one of a range of targets such as \n\
+                   100..999 with a shortest expression for each. Every \n\
+                   positional argument is then a starting number")
+        )
+        .arg(Arg::with_name("no-cache")
+            .long("no-cache")
+            .help("Neither reads nor writes the cache of results, see `cache`")
+        )
+        .arg(Arg::with_name("watch")
+            .long("watch")
+            .help("Reads puzzles as `target: numbers…` lines from stdin, \n\
+                   solving each as soon as it's entered")
+        )
         .arg(Arg::with_name("target")
-            .required_unless_one(&["random", "rules"])
+            .required_unless_one(&["random", "rules", "watch", "capabilities"])
             .index(1)
             .number_of_values(1)
             .takes_value(true)
             .value_name("TARGET")
+            .validator(validate_positive)
             .help("Target number")
         )
         .arg(Arg::with_name("numbers")
-            .required_unless_one(&["random", "rules"])
+            .required_unless_one(&["random", "rules", "watch", "capabilities"])
             .index(2)
             .min_values(2)
             .number_of_values(1)
             .takes_value(true)
             .multiple(true)
             .value_name("NUMBER")
+            .validator(validate_positive)
             .help("Starting numbers, at least two numbers must be provided")
         )
-        .get_matches();
+        .subcommand(SubCommand::with_name("preset")
+            .about("Solves a well-known round by name, lists them without a name")
+            .arg(Arg::with_name("check")
+                .long("check")
+                .help("Solves every preset and checks the number of solutions found")
+            )
+            .arg(Arg::with_name("name")
+                .index(1)
+                .value_name("NAME")
+                .help("Name of the round")
+            )
+        )
+        .subcommand(SubCommand::with_name("bench")
+            .about("Times the solver on a fixed set of representative rounds")
+            .arg(Arg::with_name("engine")
+                .long("engine")
+                .takes_value(true)
+                .possible_values(bench::Engine::NAMES)
+                .default_value("exhaustive")
+                .help("Search strategy to time")
+            )
+            .arg(Arg::with_name("compare")
+                .long("compare")
+                .conflicts_with("engine")
+                .help("Times every engine and compares their answers side by side")
+            )
+            .arg(Arg::with_name("runs")
+                .long("runs")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("3")
+                .validator(validate_positive)
+                .help("Times each round is solved")
+            )
+            .arg(Arg::with_name("profile-ordering")
+                .long("profile-ordering")
+                .takes_value(true)
+                .value_name("ROUNDS")
+                .conflicts_with_all(&["engine", "compare"])
+                .validator(validate_positive)
+                .help("Counts which pairs and operators lead to solutions over random \n\
+                       rounds instead of timing, needs the profile feature")
+            )
+        )
+        .subcommand(SubCommand::with_name("cache")
+            .about("Manages the cache of results, kept in $COUNTDOWN_CACHE_DIR \n\
+                    or ~/.cache/countdown-numbers")
+            .subcommand(SubCommand::with_name("clear")
+                .about("Removes every cached result")
+            )
+        )
+        .subcommand(SubCommand::with_name("advise")
+            .about("Recommends how many large numbers to ask for, by simulating \n\
+                    random selections against every target")
+            .arg(Arg::with_name("goal")
+                .long("goal")
+                .takes_value(true)
+                .value_name("GOAL")
+                .possible_values(&["exact", "within-10"])
+                .default_value("exact")
+                .help("Whether to reach the target exactly or just get within 10")
+            )
+            .arg(Arg::with_name("samples")
+                .long("samples")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("200")
+                .help("Selections simulated for each number of large numbers")
+            )
+        )
+        .subcommand(SubCommand::with_name("analyze")
+            .about("Statistics over every selection which could occur on the show")
+            .subcommand(SubCommand::with_name("heatmap")
+                .about("Prints the chance of reaching a target exactly, by number of \n\
+                        large numbers and target range, as CSV")
+                .arg(Arg::with_name("bin")
+                    .long("bin")
+                    .takes_value(true)
+                    .value_name("N")
+                    .default_value("100")
+                    .help("Targets in each range")
+                )
+                .arg(Arg::with_name("within")
+                    .long("within")
+                    .takes_value(true)
+                    .value_name("D")
+                    .default_value("0")
+                    .help("Counts a target as reached within D of it")
+                )
+                .arg(export_arg())
+            )
+            .subcommand(SubCommand::with_name("distances")
+                .about("Prints the share of targets reached within each distance \n\
+                        from 0 to 10, for a selection or every selection")
+                .arg(Arg::with_name("numbers")
+                    .multiple(true)
+                    .value_name("NUMBER")
+                )
+                .arg(export_arg())
+            )
+            .subcommand(SubCommand::with_name("richness")
+                .about("Prints how many distinct values a selection reaches")
+                .arg(Arg::with_name("numbers")
+                    .required(true)
+                    .multiple(true)
+                    .value_name("NUMBER")
+                )
+                .arg(export_arg())
+            )
+            .subcommand(SubCommand::with_name("unsolvable")
+                .about("Lists the selections which can't make TARGET exactly")
+                .arg(Arg::with_name("limit")
+                    .long("limit")
+                    .takes_value(true)
+                    .value_name("N")
+                    .help("Stops after N selections")
+                )
+                .arg(Arg::with_name("target").required(true).value_name("TARGET"))
+                .arg(export_arg())
+            )
+            .subcommand(SubCommand::with_name("distribution")
+                .about("Prints how the number of solutions of a selection is \n\
+                        distributed across the targets 100 to 999")
+                .arg(Arg::with_name("numbers")
+                    .required(true)
+                    .multiple(true)
+                    .value_name("NUMBER")
+                )
+                .arg(export_arg())
+            )
+        )
+        .subcommand(SubCommand::with_name("compare")
+            .about("Compares two selections across every target, such as \n\
+                    100,75,6,3,2,1 against 50,25,9,8,7,4")
+            .arg(Arg::with_name("a").required(true).value_name("NUMBERS"))
+            .arg(Arg::with_name("b").required(true).value_name("NUMBERS"))
+        )
+        .subcommand(SubCommand::with_name("simulate")
+            .about("Estimates the points a perfect solver expects per round for \n\
+                    each number of large numbers, under --scoring")
+            .arg(Arg::with_name("samples")
+                .long("samples")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("200")
+                .help("Selections simulated for each number of large numbers")
+            )
+            .arg(export_arg())
+        )
+        .subcommand(SubCommand::with_name("simulate-strategy")
+            .about("Compares how often simple strategies contestants use reach \n\
+                    the target, or get within 5 or 10, over random games")
+            .arg(Arg::with_name("games")
+                .long("games")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("1000")
+                .help("Games simulated")
+            )
+            .arg(Arg::with_name("large")
+                .long("large")
+                .takes_value(true)
+                .value_name("NUM_BIG_NUMS")
+                .validator(validate_num_big)
+                .help("Large numbers in every game, otherwise from 0 to 4 at random")
+            )
+        )
+        .subcommand(SubCommand::with_name("stats")
+            .about("Reproduces published statistics of the game, or shows the leaderboard")
+            .arg(Arg::with_name("leaderboard")
+                .long("leaderboard")
+                .help("Shows the best drill sessions and fastest exact solves on this machine")
+            )
+            .subcommand(SubCommand::with_name("official")
+                .about("Prints how many selection and target pairs can be solved \n\
+                        exactly, by number of large numbers")
+            )
+        )
+        .subcommand(SubCommand::with_name("drill")
+            .about("Plays a series of timed rounds back to back, then reports on the session")
+            .arg(Arg::with_name("op")
+                .long("op")
+                .takes_value(true)
+                .value_name("OP")
+                .validator(validate_operator)
+                .help("Only puzzles which can't be solved without OP, one of add, sub, \n\
+                       mul or div, to practise the operations people avoid")
+            )
+            .arg(Arg::with_name("rounds")
+                .long("rounds")
+                .visible_alias("count")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("10")
+                .validator(validate_positive)
+                .help("Number of rounds")
+            )
+            .arg(Arg::with_name("seconds")
+                .long("seconds")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .default_value("30")
+                .validator(validate_positive)
+                .help("Time allowed for each round")
+            )
+            .arg(Arg::with_name("list")
+                .long("list")
+                .help("Prints the puzzles instead of playing them")
+            )
+            .arg(Arg::with_name("file")
+                .value_name("FILE")
+                .conflicts_with("list")
+                .help("Writes the puzzles as a pack, TOML if FILE ends in .toml, \n\
+                       instead of playing them")
+            )
+            .arg(Arg::with_name("player")
+                .long("player")
+                .takes_value(true)
+                .value_name("NAME")
+                .help("Name to enter on the leaderboard, the login name by default")
+            )
+        )
+        .subcommand(SubCommand::with_name("curriculum")
+            .about("Writes a worksheet of puzzles growing harder, with an answer key")
+            .arg(Arg::with_name("length")
+                .long("length")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("20")
+                .validator(validate_positive)
+                .help("Number of puzzles")
+            )
+            .arg(Arg::with_name("difficulty")
+                .long("difficulty")
+                .takes_value(true)
+                .value_name("MIN-MAX")
+                .default_value("1-10")
+                .validator(|s| parse_band(&s).map(|_| ()))
+                .help("Difficulty of the first and last puzzles, from 1 to 10")
+            )
+            .arg(Arg::with_name("curve")
+                .long("curve")
+                .takes_value(true)
+                .possible_values(&curriculum::Curve::NAMES)
+                .default_value("linear")
+                .help("How the difficulty climbs, gentle leaves most of it to the end \n\
+                       and steep makes most of it early")
+            )
+            .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["text", "csv"])
+                .default_value("text")
+                .help("Printable text or CSV")
+            )
+            .arg(Arg::with_name("file")
+                .value_name("FILE")
+                .help("Writes the worksheet to FILE instead of printing it")
+            )
+        )
+        .subcommand(SubCommand::with_name("play")
+            .about("Plays random rounds, scoring each answer as on the show, \n\
+                    and records them in the stats file")
+            .arg(Arg::with_name("rounds")
+                .long("rounds")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("10")
+                .validator(validate_positive)
+                .help("Number of rounds")
+            )
+            .arg(Arg::with_name("adaptive")
+                .long("adaptive")
+                .help("Draws each round as difficult as recent results suggest, \n\
+                       a level up after doing well and a level down after struggling")
+            )
+            .arg(Arg::with_name("opponent")
+                .long("opponent")
+                .help("Plays against a solver which searches the way a person would")
+            )
+        )
+        .subcommand(SubCommand::with_name("host")
+            .about("Hosts a game over the network, every player who joins gets \n\
+                    the same rounds at once and sees everyone's scores")
+            .arg(Arg::with_name("port")
+                .long("port")
+                .takes_value(true)
+                .value_name("PORT")
+                .default_value("7952")
+                .validator(validate_positive)
+                .help("TCP port to listen on")
+            )
+            .arg(Arg::with_name("players")
+                .long("players")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("2")
+                .validator(validate_positive)
+                .help("Players to wait for before starting")
+            )
+            .arg(Arg::with_name("rounds")
+                .long("rounds")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("5")
+                .validator(validate_positive)
+                .help("Number of rounds")
+            )
+            .arg(Arg::with_name("seconds")
+                .long("seconds")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .default_value("30")
+                .validator(validate_positive)
+                .help("Time allowed for each round")
+            )
+        )
+        .subcommand(SubCommand::with_name("join")
+            .about("Joins a game hosted with host")
+            .arg(Arg::with_name("address")
+                .required(true)
+                .value_name("HOST:PORT")
+            )
+            .arg(Arg::with_name("name")
+                .long("name")
+                .takes_value(true)
+                .value_name("NAME")
+                .help("Name shown to the other players, the login name by default")
+            )
+        )
+        .subcommand(SubCommand::with_name("diff-solutions")
+            .about("Describes how two expressions for the same puzzle differ, \n\
+                    such as \"(100 * 9) + 50 + 2\" and \"(9 * 100) + (50 + 2)\"")
+            .arg(Arg::with_name("a").required(true).value_name("EXPRESSION"))
+            .arg(Arg::with_name("b").required(true).value_name("EXPRESSION"))
+        )
+        .subcommand(SubCommand::with_name("sweep")
+            .about("Rates every selection which could occur on the show against \n\
+                    every target, writing CSV to FILE")
+            .arg(Arg::with_name("resume")
+                .long("resume")
+                .help("Carries on from the last checkpoint of an interrupted sweep")
+            )
+            .arg(export_arg())
+            .arg(Arg::with_name("file").required(true).value_name("FILE"))
+        )
+        .subcommand(SubCommand::with_name("mangen")
+            .setting(AppSettings::Hidden)
+            .about("Prints a man page in roff format")
+        )
+        .subcommand(SubCommand::with_name("pack")
+            .about("Solves or plays a puzzle pack, a set of rounds stored as JSON or TOML")
+            .subcommand(SubCommand::with_name("solve")
+                .about("Solves every puzzle in a pack")
+                .arg(Arg::with_name("file").required(true).value_name("FILE"))
+            )
+            .subcommand(SubCommand::with_name("play")
+                .about("Plays through a pack, scoring each answer as on the show")
+                .arg(Arg::with_name("opponent")
+                    .long("opponent")
+                    .help("Plays against a solver which searches the way a person would")
+                )
+                .arg(Arg::with_name("file").required(true).value_name("FILE"))
+            )
+            .subcommand(SubCommand::with_name("generate")
+                .about("Writes a pack of random solvable rounds, TOML if FILE ends in .toml")
+                .arg(Arg::with_name("count")
+                    .long("count")
+                    .takes_value(true)
+                    .value_name("N")
+                    .default_value("20")
+                    .validator(validate_positive)
+                    .help("Number of puzzles")
+                )
+                .arg(Arg::with_name("difficulty")
+                    .long("difficulty")
+                    .takes_value(true)
+                    .value_name("MIN-MAX")
+                    .default_value("1-10")
+                    .validator(|s| parse_band(&s).map(|_| ()))
+                    .help("Range of difficulty ratings, from 1 to 10")
+                )
+                .arg(Arg::with_name("file").required(true).value_name("FILE"))
+            )
+        )
+}
 
-    if matches.is_present("rules") {
-        println!("The rules of the Countdown Numbers Game are as follow:\n\n\
-
-                  The contestant chooses six numbers from two groups of,\n\
-                  20 small numbers and 4 large numbers. The numbers consist\n\
-                  of two each of numbers 1 through 10. The 4 large numbers\n\
-                  are 25, 50, 75 and 100. The contestant decides how many\n\
-                  large numbers are to be used, from none to all four,\n\
-                  the rest will be small numbers.\n\n\
-
-                  A random three-digit target is generated. The contestants\n\
-                  have 30 seconds to work out a sequence of calculations with\n\
-                  the numbers whose final result is as close to the target\n\
-                  number as possible. They may use only the four basic\n\
-                  operations of addition, subtraction, multiplication and\n\
-                  division, and do not have to use all six numbers.\n\
-                  Fractions are not allowed, and only positive integers may\n\
-                  be obtained as a result at any stage of the calculation.\n\
-                  ");
-        return;
-    }
-
-    let (numbers, target) = match matches.value_of("random")
-        .map(|s| s.parse::<usize>().expect("Number of big numbers is not a number"))
-    {
-        Some(num_big) => {
-            assert!(num_big <= 4, "Number of big numbers must not be more then 4");
+/// Solves `numbers` for several targets at once from the values reachable
+/// from them, printing one expression for each target or the closest.
+fn solve_targets(numbers: &[u64], targets: &[u64]) -> Result<(), Box<dyn Error>> {
+    let start = Instant::now();
+    let reachable = Reachable::new(numbers);
+    let elapsed = start.elapsed();
+    println!("{} values reachable, found in {}.{:09} seconds",
+        reachable.values().len(), elapsed.as_secs(), elapsed.subsec_nanos());
 
-            let mut small = (1usize..11).flat_map(|i| vec![i, i]).collect::<Vec<_>>();
-            let mut big = vec![100, 75, 50, 25];
+    for &target in targets.iter() {
+        match reachable.expression(&target) {
+            Some(t) => println!("{}: {} = {}", target, t, t.value),
+            None => {
+                let closest = reachable.closest(&target)
+                    .and_then(|v| reachable.expression(v))
+                    .expect("numbers reach at least themselves");
+                println!("{}: no solution, closest {} = {}, {} away", target,
+                    closest, closest.value, closest.value.abs_diff(target));
+            },
+        }
+    }
+    Ok(())
+}
 
-            let mut rng = rand::thread_rng();
+fn run() -> Result<(), Box<dyn Error>> {
+    let start_time = Instant::now();
+    let matches = app().get_matches();
 
-            rng.shuffle(&mut small[..]);
-            rng.shuffle(&mut big[..]);
+    if global_flag(&matches, "plain") {
+        plain::set();
+    }
+    let name = global(&matches, "theme").map(str::to_string)
+        .or_else(|| env::var("COUNTDOWN_THEME").ok())
+        .unwrap_or_else(|| theme::THEMES[0].name.to_string());
+    theme::set(theme::find(&name).ok_or_else(|| {
+        format!("`{}` is not a theme, {}", name, theme::NAMES.join(", "))
+    })?);
+
+    let code = global(&matches, "lang").map(str::to_string)
+        .or_else(lang::from_env)
+        .unwrap_or_else(|| lang::CATALOGS[0].code.to_string());
+    lang::set(lang::find(&code).ok_or_else(|| {
+        format!("`{}` is not a language, {}", code, lang::CODES.join(", "))
+    })?);
+
+    if matches.is_present("mangen") {
+        print!("{}", man::render(app, countdown_numbers::RULES));
+        return Ok(());
+    }
+
+    if let Some(sub) = matches.subcommand_matches("bench") {
+        let engine = bench::Engine::from_name(sub.value_of("engine").unwrap())
+            .expect("clap checks the engine name");
+        if sub.is_present("profile-ordering") {
+            return bench::profile_ordering(value_t!(sub, "profile-ordering", usize)?);
+        }
+        let runs = value_t!(sub, "runs", u32)?;
+        if sub.is_present("compare") {
+            return bench::compare(runs);
+        }
+        return bench::run(engine, runs);
+    }
 
-            let target = rng.gen_range(101, 1000);
-            (big.into_iter().take(num_big)
-                .chain(small.into_iter().take(6 - num_big)).collect(), target)
+    match matches.subcommand_matches("cache").map(|m| m.subcommand_name()) {
+        Some(Some("clear")) => {
+            println!("Removed {} cached results", cache::clear()?);
+            return Ok(());
         },
-        None => {
-            let numbers = matches.values_of("numbers")
-                .expect("Numbers arguments are missing")
-                .map(|s| s.parse::<usize>()
-                    .expect("A number argument is not a valid number"))
-                .collect::<Vec<usize>>();
+        Some(_) => return Err("cache needs a command, clear".into()),
+        None => (),
+    }
 
-            let target = matches.value_of("target")
-                .expect("Target argument is missing")
-                .parse::<usize>()
-                .expect("Target argument is not a valid number");
+    if let Some(sub) = matches.subcommand_matches("advise") {
+        let goal = match sub.value_of("goal") {
+            Some("within-10") => advise::Goal::Within10,
+            _ => advise::Goal::Exact,
+        };
+        return advise::advise(goal, value_t!(sub, "samples", usize)?);
+    }
 
-            assert!(numbers.len() >= 2, "at least two numbers are required");
+    let export_of = |sub: &ArgMatches| sub.value_of("export").map(export::parse).transpose();
+    match matches.subcommand_matches("analyze").map(|m| m.subcommand()) {
+        Some(("heatmap", Some(sub))) => {
+            return analyze::heatmap(value_t!(sub, "bin", u64)?, value_t!(sub, "within", u64)?,
+                export_of(sub)?.as_ref());
+        },
+        Some(("distances", Some(sub))) => {
+            let numbers = match sub.values_of("numbers") {
+                Some(_) => Some(values_t!(sub, "numbers", u64)?),
+                None => None,
+            };
+            return analyze::distances(numbers.as_ref().map(|n| &n[..]), export_of(sub)?.as_ref());
+        },
+        Some(("distribution", Some(sub))) => {
+            return analyze::distribution(&values_t!(sub, "numbers", u64)?,
+                export_of(sub)?.as_ref());
+        },
+        Some(("richness", Some(sub))) => {
+            return analyze::richness(&values_t!(sub, "numbers", u64)?, export_of(sub)?.as_ref());
+        },
+        Some(("unsolvable", Some(sub))) => {
+            let limit = match sub.value_of("limit") {
+                Some(_) => Some(value_t!(sub, "limit", usize)?),
+                None => None,
+            };
+            return analyze::unsolvable(value_t!(sub, "target", u64)?, limit,
+                export_of(sub)?.as_ref());
+        },
+        Some(_) => {
+            return Err("analyze needs a command, heatmap, distances, \
+                distribution, richness or unsolvable".into());
+        },
+        None => (),
+    }
+
+    if let Some(sub) = matches.subcommand_matches("simulate") {
+        let scoring = scoring::parse(sub.value_of("scoring").unwrap())?;
+        return simulate::simulate(&*scoring, value_t!(sub, "samples", usize)?,
+            export_of(sub)?.as_ref());
+    }
+
+    if let Some(sub) = matches.subcommand_matches("simulate-strategy") {
+        let large = match sub.value_of("large") {
+            Some(_) => Some(value_t!(sub, "large", usize)?),
+            None => None,
+        };
+        return simulate::strategies(value_t!(sub, "games", usize)?, large);
+    }
+
+    match matches.subcommand_matches("stats") {
+        Some(sub) if sub.is_present("leaderboard") => {
+            leaderboard::load()?.print();
+            return Ok(());
+        },
+        Some(sub) if sub.subcommand_name() == Some("official") => {
+            return analyze::official();
+        },
+        Some(_) => return Err("stats needs a command, official, or --leaderboard".into()),
+        None => (),
+    }
+
+    if let Some(sub) = matches.subcommand_matches("compare") {
+        let a = compare::parse_selection(sub.value_of("a").unwrap())?;
+        let b = compare::parse_selection(sub.value_of("b").unwrap())?;
+        check_reachable("compare", &a[..])?;
+        check_reachable("compare", &b[..])?;
+        compare::compare(&a[..], &b[..]);
+        return Ok(());
+    }
+
+    if let Some(sub) = matches.subcommand_matches("drill") {
+        let op = operators(sub, "op").first().cloned();
+        let pack = drill::puzzles(op, value_t!(sub, "rounds", usize)?)?;
+        if let Some(file) = sub.value_of("file") {
+            return pack::save(file, &pack);
+        }
+        if sub.is_present("list") {
+            drill::print(&pack);
+            return Ok(());
+        }
+        let scoring = scoring::parse(sub.value_of("scoring").unwrap())?;
+        let player = sub.value_of("player").map(str::to_string)
+            .unwrap_or_else(leaderboard::player);
+        return drill::session(&pack, value_t!(sub, "seconds", u64)?, &*scoring, &player);
+    }
+
+    if let Some(sub) = matches.subcommand_matches("curriculum") {
+        let (min, max) = parse_band(sub.value_of("difficulty").unwrap())?;
+        let curve = curriculum::Curve::from_name(sub.value_of("curve").unwrap())
+            .expect("clap checks curve names");
+        let exercises = curriculum::generate(value_t!(sub, "length", usize)?, min, max, curve)?;
+        let worksheet = match sub.value_of("format") {
+            Some("csv") => curriculum::csv(&exercises),
+            _ => curriculum::text(&exercises),
+        };
+        return curriculum::save(sub.value_of("file"), &worksheet);
+    }
+
+    if let Some(sub) = matches.subcommand_matches("play") {
+        let scoring = scoring::parse(sub.value_of("scoring").unwrap())?;
+        let opponent = if sub.is_present("opponent") { Some(Human::default()) } else { None };
+        return play::random(value_t!(sub, "rounds", usize)?, sub.is_present("adaptive"),
+            &*scoring, opponent.as_ref());
+    }
+
+    if let Some(sub) = matches.subcommand_matches("host") {
+        let scoring = scoring::parse(sub.value_of("scoring").unwrap())?;
+        return multiplayer::host(value_t!(sub, "port", u16)?, value_t!(sub, "players", usize)?,
+            value_t!(sub, "rounds", usize)?, value_t!(sub, "seconds", u64)?, &*scoring);
+    }
+
+    if let Some(sub) = matches.subcommand_matches("join") {
+        let name = sub.value_of("name").map(str::to_string)
+            .unwrap_or_else(leaderboard::player);
+        return multiplayer::join(sub.value_of("address").unwrap(), &name);
+    }
+
+    if let Some(sub) = matches.subcommand_matches("diff-solutions") {
+        return diff::print(sub.value_of("a").unwrap(), sub.value_of("b").unwrap());
+    }
+
+    if let Some(sub) = matches.subcommand_matches("sweep") {
+        return sweep::run(sub.value_of("file").unwrap(), sub.is_present("resume"),
+            export_of(sub)?.as_ref());
+    }
+
+    if matches.is_present("capabilities") {
+        println!("{}", serde_json::to_string_pretty(&capabilities::describe())?);
+        return Ok(());
+    }
+
+    if matches.is_present("rules") {
+        print!("{}", lang::text().rules);
+        return Ok(());
+    }
+
+    match matches.subcommand_matches("pack").map(|m| m.subcommand()) {
+        Some(("solve", Some(sub))) => {
+            return pack::solve(&pack::load(sub.value_of("file").unwrap())?);
+        },
+        Some(("play", Some(sub))) => {
+            let scoring = scoring::parse(sub.value_of("scoring").unwrap())?;
+            let opponent = if sub.is_present("opponent") { Some(Human::default()) } else { None };
+            return play::play(&pack::load(sub.value_of("file").unwrap())?, &*scoring,
+                opponent.as_ref());
+        },
+        Some(("generate", Some(sub))) => {
+            let count = value_t!(sub, "count", usize)?;
+            let (min, max) = parse_band(sub.value_of("difficulty").unwrap())?;
+            return pack::save(sub.value_of("file").unwrap(),
+                &pack::generate(count, min, max)?);
+        },
+        Some(_) => return Err("pack needs a command, solve, play or generate".into()),
+        None => (),
+    }
+
+    if matches.is_present("watch") {
+        return watch::run(|solver| configure(&matches, solver), matches.is_present("one"));
+    }
+
+    let preset = match matches.subcommand_matches("preset") {
+        Some(sub) if sub.is_present("check") => return preset::check(),
+        Some(sub) => match sub.value_of("name") {
+            Some(name) => Some(presets::find(name).ok_or_else(|| {
+                format!("no preset named `{}`, run `preset` to list them", name)
+            })?),
+            None => {
+                preset::list();
+                return Ok(());
+            },
+        },
+        None => None,
+    };
+
+    let positional = preset.is_none() && !matches.is_present("random");
+    let (numbers, target) = match (preset, matches.value_of("random")) {
+        (Some(preset), _) => (preset.numbers.to_vec(), preset.target),
+        (None, Some(_)) => {
+            let num_big = value_t!(matches, "random", usize)?;
+            random::round(&mut rand::thread_rng(), num_big)
+        },
+        (None, None) => {
+            let numbers = values_t!(matches, "numbers", u64)?;
+            let target = value_t!(matches, "target", u64)?;
 
             (numbers, target)
         }
     };
 
-    // convert numbers to string and join together
+    // With --targets, --scan and --table there is no target argument, the
+    // first number was taken as one. Random and preset rounds bring their
+    // own target, which these ignore.
+    let selection = {
+        let mut selection = numbers.clone();
+        if positional {
+            selection.insert(0, target);
+        }
+        selection
+    };
+    for mode in ["targets", "scan", "table"].iter().filter(|&&m| matches.is_present(m)) {
+        check_reachable(&format!("--{}", mode), &selection[..])?;
+    }
+    if let Some(targets) = matches.value_of("targets") {
+        return solve_targets(&selection[..], &parse_targets(targets)?);
+    }
+    if let Some(targets) = matches.value_of("scan") {
+        let targets = parse_targets(targets)?;
+        scan::print(&Reachable::covering(&selection[..], &targets[..]), &targets[..]);
+        return Ok(());
+    }
+    if let Some(targets) = matches.value_of("table") {
+        table::print(&Reachable::new(&selection[..]), &parse_targets(targets)?);
+        return Ok(());
+    }
+
+    let game: &dyn Rules = &Countdown;
+    let violations = game.violations(&numbers[..], target);
+    if !violations.is_empty() {
+        if matches.is_present("strict") {
+            let mut msg = "not an official Countdown round:".to_string();
+            for v in violations.iter() {
+                msg.push_str(&format!("\n  - {}", v));
+            }
+            return Err(msg.into());
+        } else if !matches.is_present("quiet") {
+            eprintln!("note: this puzzle couldn't occur on the show, \
+                       so results may not transfer to real rounds:");
+            for v in violations.iter() {
+                eprintln!("  - {}", v);
+            }
+        }
+    }
+
+    let mut solver = Solver::new(&numbers[..], target)?;
+
+    // convert numbers to string and join together, largest first so the
+    // output doesn't depend on the order the numbers were given in
     let numbers_str = {
+        let mut sorted = numbers.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+
         let mut numbers_str = String::new();
         let mut first = true;
-        for s in numbers.iter() {
+        for s in sorted.iter() {
             if first {
                 first = false;
             } else {
@@ -343,19 +1214,227 @@ fn main() {
         numbers_str
     };
 
-    println!("Starting numbers: [{}], target: {}", numbers_str, target);
-    
-    let mut solver = Solver::new(&numbers[..], target);
+    let text = matches.value_of("format") == Some("text");
+    if text {
+        println!("{}", theme::paint(Role::Heading,
+            format!("Starting numbers: [{}], target: {}", numbers_str, target)));
+        println!("Search space: at most {} expressions", search_space(numbers.len()));
+    }
+
+    // Searches which record or check more than the report, or which are
+    // random, aren't cached
+    let cache_key = if matches.is_present("no-cache") || matches.is_present("sample")
+        || matches.is_present("trace") || matches.is_present("verify-against-oracle")
+    {
+        None
+    } else {
+        let options = format!("max-solutions={:?} no-dedup={} max-intermediate={:?} best={:?} \
+                               must-use-op={:?} ordering={:?}",
+            matches.value_of("max-solutions"), matches.is_present("no-dedup"),
+            matches.value_of("max-intermediate"), matches.value_of("best"),
+            matches.value_of("must-use-op").and_then(Operator::from_name),
+            matches.value_of("ordering"));
+        Some(cache::key(&numbers[..], target, &options))
+    };
+    let cached = cache_key.as_ref().and_then(|key| cache::load(key));
+    let is_cached = cached.is_some();
+
+    let setup_time = start_time.elapsed();
+    let report = match cached {
+        Some(report) => report,
+        None => {
+            solver.set_observer(Progress::new(numbers.len()));
+
+            let recorder = match matches.value_of("trace") {
+                Some(path) => Some(Recorder::create(path)?),
+                None => None,
+            };
+            if let Some(ref recorder) = recorder {
+                solver.set_trace(recorder.hook());
+            }
+
+            configure(&matches, &mut solver)?;
+
+            match matches.value_of("best") {
+                Some(_) => solver.solve_best(value_t!(matches, "best", usize)?),
+                None => solver.solve(),
+            }
+            if let Some(recorder) = recorder {
+                recorder.finish()?;
+            }
+
+            let report = solver.report();
+            if let Some(ref key) = cache_key {
+                cache::store(key, &report);
+            }
+            report
+        },
+    };
+    if matches.is_present("verify-against-oracle") {
+        let max_intermediate = match matches.value_of("max-intermediate") {
+            Some(_) => Some(value_t!(matches, "max-intermediate", u64)?),
+            None => None,
+        };
+        let dedup = if matches.is_present("no-dedup") { Dedup::None } else { Dedup::Value };
+        verify::against_oracle(&report, &numbers[..], target, dedup, max_intermediate,
+            operators(&matches, "must-use-op").first().cloned())?;
+    }
+    let elapsed = report.elapsed;
+    let output_start = Instant::now();
+
+    let (with, without) = (operators(&matches, "with"), operators(&matches, "without"));
+    let tiles = match matches.value_of("tiles-exactly") {
+        Some(_) => Some(value_t!(matches, "tiles-exactly", usize)?),
+        None => None,
+    };
+    let filtered = !with.is_empty() || !without.is_empty() || tiles.is_some();
+    let mut kept = report.clone();
+    kept.solutions.retain(|s| {
+        with.iter().all(|&op| s.count(op) > 0) && without.iter().all(|&op| s.count(op) == 0) &&
+            tiles.is_none_or(|n| s.tiles() == n)
+    });
+
+    let mut shown = kept.solutions.clone();
+    if matches.is_present("one") {
+        shown = kept.recommended().cloned().into_iter().collect();
+    } else if matches.value_of("sort") == Some("human") {
+        // stable, so equally findable solutions stay in canonical order
+        shown.sort_by_key(|s| s.difficulty);
+    }
+
+    if matches.is_present("verify-output") {
+        verify::output(&shown[..], &numbers[..], target, game)?;
+    }
+
+    let stats = &report.stats;
+    if !text {
+        let tsv = Tsv { header: matches.is_present("header"), null: matches.is_present("null") };
+        let registry = formats::registry(tsv);
+        let formatter = registry.get(matches.value_of("format").unwrap())
+            .expect("clap checks the format");
+        // Only the solutions to show, counting those passing the filters
+        let mut report = kept.clone();
+        if filtered {
+            report.stats.solutions = kept.solutions.len();
+        }
+        report.solutions = shown;
+        let mut out = String::new();
+        formatter.write(&report, &mut out)?;
+        print!("{}", out);
+    } else {
+        println!("{} Valid expressions, found {} Solutions in {}.{:09} seconds{}",
+            stats.expressions, stats.solutions,
+            elapsed.as_secs(), elapsed.subsec_nanos(),
+            if is_cached { ", from the cache" } else { "" });
+
+        if matches.is_present("one") {
+            // only one solution is printed, however many were stored
+        } else if matches.is_present("best") {
+            println!("Showing the {} best of these solutions", report.solutions.len());
+        } else if report.truncated() {
+            println!("Showing only {} {} solutions, raise --max-solutions to see more \
+                      (solutions past the limit may be counted more than once)",
+                if matches.is_present("sample") { "a random sample of" } else { "the first" },
+                report.solutions.len());
+        }
+
+        if filtered {
+            println!("{} of these pass --with, --without and --tiles-exactly",
+                kept.solutions.len());
+        }
+
+        if let Some(first) = stats.first_solution {
+            println!("First solution found after {}.{:09} seconds",
+                first.as_secs(), first.subsec_nanos());
+        }
+
+        if stats.too_large > 0 {
+            println!("{} branches pruned for exceeding --max-intermediate",
+                stats.too_large);
+        }
+
+        if stats.overflows > 0 {
+            println!("{} branches pruned because a value overflowed",
+                stats.overflows);
+        }
+
+        if matches.is_present("methods") {
+            for method in cluster::methods(&shown[..]) {
+                match method.len() {
+                    1 => println!("{}", method),
+                    n => println!("{} variations of {}", n, method),
+                }
+            }
+        } else {
+            for s in shown.iter() {
+                let shown = format!("{} = {}", plain::expression(&s.term), s.term.value);
+                println!("{}  (difficulty {}, peaks at {})", theme::paint(Role::Solution, shown),
+                    s.difficulty, thousands(*s.peak()));
+            }
+        }
 
-    let start_time = std::time::Instant::now();
-    solver.solve();
-    let elapsed = start_time.elapsed();
+        if matches.is_present("fingerprint") {
+            if report.truncated() {
+                eprintln!("note: the fingerprint only covers the stored solutions, \
+                           raise --max-solutions to cover them all");
+            }
+            println!("Fingerprint: {}",
+                fingerprint::fingerprint(report.solutions.iter().map(|s| &*s.term)));
+        }
 
-    println!("{} Valid expressions, found {} Solutions in {}.{:09} seconds",
-        solver.counter, solver.solutions.len(),
-        elapsed.as_secs(), elapsed.subsec_nanos());
+        if stats.solutions == 0 {
+            if let Some(ref c) = report.closest {
+                let distance = c.value.abs_diff(target);
+                let scoring = scoring::parse(matches.value_of("scoring").unwrap())?;
+                println!("Closest: {} = {}, {}", theme::paint(Role::Solution, plain::expression(c)),
+                    c.value,
+                    theme::paint(Role::for_distance(distance),
+                        format!("{} away, scoring {} points", distance, scoring.score(distance))));
+            }
+            println!("No solution. Certificate of the search:");
+            println!("  {} expressions evaluated", stats.expressions);
+            println!("  {} branches pruned as not positive, {} as fractions, \
+                      {} for exceeding --max-intermediate, {} for overflowing",
+                stats.not_positive, stats.fractions, stats.too_large, stats.overflows);
+            println!("  {} stacks of terms skipped as they were searched before",
+                stats.transpositions);
+            if report.exhaustive() {
+                println!("  The search was exhaustive, no limit cut it short");
+            } else {
+                println!("  The search was not exhaustive, {} branches overflowed \
+                          and weren't explored", stats.overflows);
+            }
+        }
 
-    for s in solver.solutions.iter() {
-        println!("{} = {}", s, s.value);
+        if matches.is_present("teach") {
+            match kept.recommended() {
+                Some(s) => teach::print(&s.term, target),
+                None => println!("{}", lang::text().no_solution),
+            }
+        }
+
+        if matches.is_present("one-away") {
+            one_away::print(&numbers[..], target, stats.solutions > 0,
+                |solver| configure(&matches, solver))?;
+        }
     }
+
+    if let Some(path) = matches.value_of("metrics") {
+        metrics::write(path, &Metrics {
+            version: env!("CARGO_PKG_VERSION"),
+            numbers: &numbers[..],
+            target,
+            solutions: stats.solutions,
+            stats,
+            wall_time: start_time.elapsed().as_secs_f64(),
+            phases: Phases {
+                setup: setup_time.as_secs_f64(),
+                solve: elapsed.as_secs_f64(),
+                output: output_start.elapsed().as_secs_f64(),
+            },
+            memory_high_water_kb: metrics::memory_high_water_kb(),
+        })?;
+    }
+
+    Ok(())
 }
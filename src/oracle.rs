@@ -0,0 +1,201 @@
+//! A deliberately simple reference solver, built with the `oracle`
+//! feature to check the real one against.
+//!
+//! It tries every operator on every ordered pair of terms, with no
+//! pruning beyond the rules and no ordering tricks, so it's slow but easy
+//! to convince yourself of. Only practical for up to five numbers.
+//!
+//! Tests build it without the feature, to check the solver against it.
+
+use alloc::vec::Vec;
+
+use parse;
+use term::{Operator, Term};
+use value::Value;
+
+/// Every distinct solution in its [canonical
+/// form](../struct.Term.html#method.canonical), in the canonical order.
+/// Like the solver, a starting number equal to the target doesn't count
+/// as a solution.
+pub fn solve<V: Value>(numbers: &[V], target: &V) -> Vec<Term<V>> {
+    solve_with(numbers, target, None, None)
+}
+
+/// Like [`solve`](fn.solve.html), under the variant rules of
+/// [`Solver::set_max_intermediate`](../struct.Solver.html#method.set_max_intermediate)
+/// and [`Solver::set_must_use`](../struct.Solver.html#method.set_must_use).
+pub fn solve_with<V: Value>(numbers: &[V], target: &V, max_intermediate: Option<&V>,
+    must_use: Option<Operator>) -> Vec<Term<V>>
+{
+    let variant = Variant { target, max_intermediate, must_use };
+    let mut found = every(numbers, &variant).into_iter()
+        .map(Term::canonical)
+        .collect::<Vec<_>>();
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Every distinct solution as [`Solver::solve`](../struct.Solver.html#method.solve)
+/// reports them with [`Dedup::Value`](../enum.Dedup.html#variant.Value),
+/// not canonicalised, in the canonical order, under the same variant rules
+/// as [`solve_with`](fn.solve_with.html).
+///
+/// The solver puts the larger operand first. It stacks a term it makes
+/// before the terms equal to it, so of two equal operands it puts either
+/// first, unless only one is a starting number, which then goes second.
+pub fn solve_as_searched<V: Value>(numbers: &[V], target: &V, max_intermediate: Option<&V>,
+    must_use: Option<Operator>) -> Vec<Term<V>>
+{
+    let variant = Variant { target, max_intermediate, must_use };
+    let mut found = every(numbers, &variant).into_iter()
+        .filter(searched)
+        .collect::<Vec<_>>();
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Whether every operation of `term` has its operands in an order the
+/// solver tries.
+fn searched<V: Value>(term: &Term<V>) -> bool {
+    match term.expression {
+        None => true,
+        Some((_, ref a, ref b)) => {
+            (a.value > b.value ||
+                a.value == b.value && !(a.expression.is_none() && b.expression.is_some())) &&
+                searched(a) && searched(b)
+        },
+    }
+}
+
+/// Every solution found along every path, repeats and all.
+fn every<V: Value>(numbers: &[V], variant: &Variant<V>) -> Vec<Term<V>> {
+    let terms = numbers.iter()
+        .map(|n| Term { expression: None, value: n.clone() })
+        .collect::<Vec<_>>();
+    let mut found = Vec::new();
+    combine(&terms, variant, &mut found);
+    found
+}
+
+/// What counts as a solution, and how far expressions may grow.
+struct Variant<'a, V: 'a> {
+    target: &'a V,
+    max_intermediate: Option<&'a V>,
+    must_use: Option<Operator>,
+}
+
+fn combine<V: Value>(terms: &[Term<V>], variant: &Variant<V>, found: &mut Vec<Term<V>>) {
+    for i in 0..terms.len() {
+        for j in 0..terms.len() {
+            if i == j {
+                continue;
+            }
+            for &op in Operator::ALL.iter() {
+                let term = match parse::apply(op, terms[i].clone(), terms[j].clone()) {
+                    Ok(term) => term,
+                    Err(_) => continue,
+                };
+                if variant.max_intermediate.is_some_and(|max| term.value > *max) {
+                    continue;
+                }
+                if term.value == *variant.target &&
+                    variant.must_use.is_none_or(|op| term.count(op) > 0)
+                {
+                    found.push(term.clone());
+                }
+
+                let mut rest = terms.iter().enumerate()
+                    .filter(|&(k, _)| k != i && k != j)
+                    .map(|(_, t)| t.clone())
+                    .collect::<Vec<_>>();
+                rest.push(term);
+                combine(&rest, variant, found);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use solver::{Dedup, SearchOrder, Solver};
+
+    const PUZZLES: [([u64; 5], u64); 6] = [
+        ([25, 50, 75, 10, 3], 250),
+        ([100, 7, 7, 3, 2], 703),
+        ([50, 6, 4, 2, 2], 312),
+        ([75, 25, 9, 8, 1], 600),
+        ([1, 2, 3, 4, 5], 24),
+        // (6 / 2) and (2 + 1) are equal, so both go first
+        ([100, 6, 2, 2, 1], 600),
+    ];
+
+    /// Checks the solver, set up by `configure`, finds the same distinct
+    /// solutions as the oracle on every puzzle, both canonicalised and as
+    /// found. Unless `dedup` reports repeats, it also counts as many.
+    fn agrees<F: Fn(&mut Solver)>(configure: F, dedup: Dedup, max_intermediate: Option<u64>,
+        must_use: Option<Operator>)
+    {
+        for &(numbers, target) in PUZZLES.iter() {
+            let mut solver = Solver::new(&numbers, target).unwrap();
+            solver.set_dedup(dedup);
+            configure(&mut solver);
+            solver.solve();
+            let max_intermediate = max_intermediate.as_ref();
+
+            let mut found = solver.solutions().iter()
+                .map(|s| s.canonical())
+                .collect::<Vec<_>>();
+            found.sort();
+            found.dedup();
+            let expected = solve_with(&numbers, &target, max_intermediate, must_use);
+            assert!(!expected.is_empty(), "{:?} {} has no solutions", numbers, target);
+            assert_eq!(found, expected, "{:?} {}", numbers, target);
+
+            let mut found = solver.solutions().iter()
+                .map(|s| (*s.term).clone())
+                .collect::<Vec<_>>();
+            found.dedup();
+            let expected = solve_as_searched(&numbers, &target, max_intermediate, must_use);
+            assert_eq!(found, expected, "{:?} {} as found", numbers, target);
+            if dedup == Dedup::Value {
+                assert_eq!(solver.stats().solutions, expected.len(), "{:?} {}", numbers, target);
+            }
+        }
+    }
+
+    #[test]
+    fn learned_order() {
+        agrees(|_| (), Dedup::Value, None, None);
+    }
+
+    #[test]
+    fn naive_order() {
+        agrees(|s| s.set_order(SearchOrder::Naive), Dedup::Value, None, None);
+    }
+
+    #[test]
+    fn no_dedup() {
+        agrees(|_| (), Dedup::None, None, None);
+    }
+
+    #[test]
+    fn naive_order_no_dedup() {
+        agrees(|s| s.set_order(SearchOrder::Naive), Dedup::None, None, None);
+    }
+
+    #[test]
+    fn max_intermediate() {
+        agrees(|s| s.set_max_intermediate(800), Dedup::Value, Some(800), None);
+    }
+
+    #[test]
+    fn must_use() {
+        agrees(|s| s.set_must_use(Operator::Multiplication), Dedup::Value, None,
+            Some(Operator::Multiplication));
+    }
+}
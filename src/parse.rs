@@ -0,0 +1,162 @@
+//! Parsing expressions typed by players, with the usual precedence of
+//! `*` and `/` over `+` and `-`.
+
+use alloc::boxed::Box;
+use core::fmt;
+use core::iter::Peekable;
+use core::str::{CharIndices, FromStr};
+
+use term::{Operator, Term};
+use value::Value;
+
+/// Why an expression couldn't be parsed or breaks the rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// An unexpected character, holds its byte offset.
+    Unexpected(usize),
+    /// The expression ended early.
+    End,
+    /// A number, or the result of a subtraction, isn't positive.
+    NotPositive,
+    /// A division doesn't come out even.
+    Fraction,
+    /// A value is too large for the value type.
+    Overflow,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Unexpected(at) => {
+                write!(f, "unexpected character at position {}", at + 1)
+            },
+            ParseError::End => f.write_str("the expression is incomplete"),
+            ParseError::NotPositive => {
+                f.write_str("only positive numbers may be used or calculated")
+            },
+            ParseError::Fraction => f.write_str("fractions are not allowed"),
+            ParseError::Overflow => f.write_str("a value is too large"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for ParseError {}
+
+/// Parses and evaluates an expression such as `(100 + 6) * 3`, checking
+/// every step against the rules. Besides `*` and `/`, `x`, `×` and `÷`
+/// are accepted.
+pub fn parse<V: Value + FromStr>(s: &str) -> Result<Term<V>, ParseError> {
+    let mut parser = Parser { s, chars: s.char_indices().peekable() };
+    let term = parser.sum()?;
+    match parser.next() {
+        Some((at, _)) => Err(ParseError::Unexpected(at)),
+        None => Ok(term),
+    }
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    /// Next character which isn't whitespace.
+    fn next(&mut self) -> Option<(usize, char)> {
+        self.skip_whitespace();
+        self.chars.next()
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|&(_, c)| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    /// Terms joined by `+` and `-`.
+    fn sum<V: Value + FromStr>(&mut self) -> Result<Term<V>, ParseError> {
+        let mut term = self.product()?;
+        loop {
+            let op = match self.peek() {
+                Some('+') => Operator::Addition,
+                Some('-') => Operator::Subtraction,
+                _ => return Ok(term),
+            };
+            self.next();
+            term = apply(op, term, self.product()?)?;
+        }
+    }
+
+    /// Factors joined by `*` and `/`.
+    fn product<V: Value + FromStr>(&mut self) -> Result<Term<V>, ParseError> {
+        let mut term = self.factor()?;
+        loop {
+            let op = match self.peek() {
+                Some('*') | Some('x') | Some('×') => Operator::Multiplication,
+                Some('/') | Some('÷') => Operator::Division,
+                _ => return Ok(term),
+            };
+            self.next();
+            term = apply(op, term, self.factor()?)?;
+        }
+    }
+
+    /// A number or a parenthesized sum.
+    fn factor<V: Value + FromStr>(&mut self) -> Result<Term<V>, ParseError> {
+        match self.next() {
+            Some((_, '(')) => {
+                let term = self.sum()?;
+                match self.next() {
+                    Some((_, ')')) => Ok(term),
+                    Some((at, _)) => Err(ParseError::Unexpected(at)),
+                    None => Err(ParseError::End),
+                }
+            },
+            Some((start, c)) if c.is_ascii_digit() => {
+                let mut end = start + 1;
+                while let Some(&(i, c)) = self.chars.peek() {
+                    if !c.is_ascii_digit() {
+                        break;
+                    }
+                    end = i + 1;
+                    self.chars.next();
+                }
+                let value = self.s[start..end].parse::<V>()
+                    .map_err(|_| ParseError::Overflow)?;
+                if value.is_zero() {
+                    return Err(ParseError::NotPositive);
+                }
+                Ok(Term { expression: None, value })
+            },
+            Some((at, _)) => Err(ParseError::Unexpected(at)),
+            None => Err(ParseError::End),
+        }
+    }
+}
+
+/// Combines two terms, checking the result against the rules.
+pub(crate) fn apply<V: Value>(op: Operator, a: Term<V>, b: Term<V>)
+    -> Result<Term<V>, ParseError>
+{
+    let value = match op {
+        Operator::Addition => a.value.checked_add(&b.value)
+            .ok_or(ParseError::Overflow)?,
+        Operator::Subtraction if a.value <= b.value => {
+            return Err(ParseError::NotPositive);
+        },
+        Operator::Subtraction => a.value.sub(&b.value),
+        Operator::Multiplication => a.value.checked_mul(&b.value)
+            .ok_or(ParseError::Overflow)?,
+        Operator::Division if !a.value.is_multiple_of(&b.value) => {
+            return Err(ParseError::Fraction);
+        },
+        Operator::Division => a.value.div(&b.value),
+        Operator::Custom(c) => unreachable!("only the basic operators are parsed, not {}", c.name),
+    };
+    Ok(Term { expression: Some((op, Box::new(a), Box::new(b))), value })
+}
@@ -0,0 +1,69 @@
+//! Well-known rounds bundled by name, to show the solver on the classic
+//! cases and to serve as a regression corpus.
+
+/// A bundled round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preset {
+    /// Name the round is run by
+    pub name: &'static str,
+    /// What makes the round interesting
+    pub description: &'static str,
+    /// Starting numbers
+    pub numbers: &'static [u64],
+    /// Target number
+    pub target: u64,
+    /// Number of solutions the solver finds with the default settings
+    pub solutions: usize,
+}
+
+/// All bundled rounds.
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "james-martin",
+        description: "The 1997 round James Martin solved on air, widely \
+            thought impossible at the time",
+        numbers: &[100, 75, 50, 25, 6, 3],
+        target: 952,
+        solutions: 7,
+    },
+    Preset {
+        name: "readme",
+        description: "The example round from the README",
+        numbers: &[100, 75, 50, 25, 9, 8],
+        target: 952,
+        solutions: 10,
+    },
+    Preset {
+        name: "all-small",
+        description: "Six small numbers reaching a high target",
+        numbers: &[9, 8, 7, 4, 3, 2],
+        target: 987,
+        solutions: 23,
+    },
+    Preset {
+        name: "pairs",
+        description: "Three pairs of small numbers, only a handful of ways",
+        numbers: &[9, 9, 8, 8, 7, 7],
+        target: 831,
+        solutions: 4,
+    },
+    Preset {
+        name: "four-large",
+        description: "Four large numbers and two ones, with no exact solution",
+        numbers: &[100, 75, 50, 25, 1, 1],
+        target: 813,
+        solutions: 0,
+    },
+    Preset {
+        name: "smallest-tiles",
+        description: "The six smallest tiles can't reach the largest targets",
+        numbers: &[3, 3, 2, 2, 1, 1],
+        target: 999,
+        solutions: 0,
+    },
+];
+
+/// Looks up a bundled round by name.
+pub fn find(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|p| p.name == name)
+}
@@ -0,0 +1,93 @@
+//! Statistics on which choices of the search lead to solutions, which the
+//! order of [`SearchOrder::Learned`](../enum.SearchOrder.html) was learned
+//! from. Collected with
+//! [`Solver::set_profiling`](../struct.Solver.html#method.set_profiling).
+
+use alloc::vec::Vec;
+
+use stack::INLINE;
+use term::Operator;
+
+/// A choice made at a node of the search: combining the terms at `pair`
+/// of the `terms` left, largest first, with `op`.
+#[derive(Debug, Clone, Copy)]
+pub struct Choice {
+    pub terms: usize,
+    pub pair: (usize, usize),
+    pub op: Operator,
+}
+
+/// Counts of the choices on the paths to solutions, over one or more
+/// searches. Only stacks of up to 16 terms are counted.
+#[derive(Debug, Clone)]
+pub struct OrderProfile {
+    solutions: u64,
+    /// Solutions each basic operator led to, in `Operator::ALL` order
+    operators: [u64; 4],
+    /// Solutions each pair led to, indexed by terms left and the pair
+    pairs: Vec<u64>,
+}
+
+impl Default for OrderProfile {
+    fn default() -> OrderProfile {
+        OrderProfile {
+            solutions: 0,
+            operators: [0; 4],
+            pairs: alloc::vec![0; INLINE * INLINE * INLINE],
+        }
+    }
+}
+
+impl OrderProfile {
+    /// Number of solutions counted.
+    pub fn solutions(&self) -> u64 {
+        self.solutions
+    }
+
+    /// Number of solutions whose path combined terms with `op`, once for
+    /// each time it did. Custom operators aren't counted.
+    pub fn operator(&self, op: Operator) -> u64 {
+        Operator::ALL.iter().position(|&o| o == op).map_or(0, |i| self.operators[i])
+    }
+
+    /// Number of solutions whose path combined the `i`-th and `j`-th
+    /// largest of `terms` terms left, counting from 0.
+    pub fn pair(&self, terms: usize, i: usize, j: usize) -> u64 {
+        if terms > INLINE || i >= terms || j >= terms {
+            return 0;
+        }
+        self.pairs[(terms - 1) * INLINE * INLINE + i * INLINE + j]
+    }
+
+    /// The basic operators, those most often on the paths to solutions
+    /// first.
+    pub fn operators(&self) -> [Operator; 4] {
+        let mut ops = Operator::ALL;
+        ops.sort_by_key(|&op| core::cmp::Reverse(self.operator(op)));
+        ops
+    }
+
+    /// Adds the counts of `other` to these.
+    pub fn merge(&mut self, other: &OrderProfile) {
+        self.solutions += other.solutions;
+        for (a, b) in self.operators.iter_mut().zip(other.operators.iter()) {
+            *a += b;
+        }
+        for (a, b) in self.pairs.iter_mut().zip(other.pairs.iter()) {
+            *a += b;
+        }
+    }
+
+    /// Counts a solution reached through `path`.
+    pub(crate) fn record(&mut self, path: &[Choice]) {
+        self.solutions += 1;
+        for c in path {
+            if let Some(i) = Operator::ALL.iter().position(|&o| o == c.op) {
+                self.operators[i] += 1;
+            }
+            if c.terms <= INLINE {
+                self.pairs[(c.terms - 1) * INLINE * INLINE + c.pair.0 * INLINE + c.pair.1] += 1;
+            }
+        }
+    }
+}
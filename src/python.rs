@@ -0,0 +1,69 @@
+//! Python bindings, built with the `python` feature.
+//!
+//! ```python
+//! import countdown_numbers
+//! countdown_numbers.solve([25, 50, 75, 100, 3, 6], 952)
+//! ```
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use solver::Solver;
+
+/// Runs the solver to completion on a set of numbers.
+fn run(numbers: Vec<u64>, target: u64) -> PyResult<Solver> {
+    let mut solver = Solver::new(&numbers[..], target)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    solver.solve();
+    Ok(solver)
+}
+
+/// Finds all solutions, returned as a list of expression strings.
+#[pyfunction]
+fn solve(numbers: Vec<u64>, target: u64) -> PyResult<Vec<String>> {
+    let solver = run(numbers, target)?;
+    Ok(solver.solutions().iter().map(|s| s.term.to_string()).collect())
+}
+
+/// Finds the expression closest to the target, returned as an
+/// `(expression, value)` tuple.
+#[pyfunction]
+fn closest(numbers: Vec<u64>, target: u64) -> PyResult<(String, u64)> {
+    let solver = run(numbers, target)?;
+    let closest = solver.closest().expect("solver has at least two numbers");
+    Ok((closest.to_string(), closest.value))
+}
+
+/// Solves a round and returns a dict describing the search: the number of
+/// `expressions` evaluated, branches pruned by `overflows`, the `solutions`
+/// found, and the `closest` expression with its `value` and `distance` from
+/// the target.
+#[pyfunction]
+fn analyze(py: Python, numbers: Vec<u64>, target: u64)
+    -> PyResult<Bound<PyDict>>
+{
+    let solver = run(numbers, target)?;
+    let closest = solver.closest().expect("solver has at least two numbers");
+    let distance = closest.value.abs_diff(target);
+
+    let dict = PyDict::new(py);
+    dict.set_item("target", target)?;
+    dict.set_item("expressions", solver.stats().expressions)?;
+    dict.set_item("overflows", solver.stats().overflows)?;
+    dict.set_item("solutions", solver.solutions().iter()
+        .map(|s| s.term.to_string()).collect::<Vec<_>>())?;
+    dict.set_item("closest", closest.to_string())?;
+    dict.set_item("value", closest.value)?;
+    dict.set_item("distance", distance)?;
+    Ok(dict)
+}
+
+/// Countdown Numbers Game solver
+#[pymodule]
+fn countdown_numbers(m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(python::solve, m)?)?;
+    m.add_function(wrap_pyfunction!(python::closest, m)?)?;
+    m.add_function(wrap_pyfunction!(python::analyze, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,298 @@
+use alloc::boxed::Box;
+use alloc::collections::btree_map::{self, BTreeMap, Entry};
+use alloc::vec::Vec;
+
+use term::{Operator, Term};
+use value::Value;
+
+/// How a value was first reached from a subset of the starting numbers.
+#[derive(Debug, Clone)]
+enum Step<V> {
+    /// A starting number
+    Number,
+    /// An operator applied to a value reached from each of two disjoint
+    /// subsets, given as bitmasks, the larger value first
+    Operation(Operator, usize, V, usize, V),
+}
+
+/// Every value reachable from a set of starting numbers, with a way to
+/// reach each.
+///
+/// Built by dynamic programming over the subsets of the numbers: the
+/// values of a subset are those of its numbers combined with each
+/// operator across every split into two smaller subsets. Unlike
+/// [`Solver`](struct.Solver.html), which enumerates expressions, each
+/// value is kept once per subset, so answering many targets for the same
+/// numbers costs one build. Only practical for a handful of numbers, the
+/// number of subsets doubles with each one.
+#[derive(Debug, Clone)]
+pub struct Reachable<V = u64> {
+    /// Values reached from each subset, indexed by bitmask
+    subsets: Vec<BTreeMap<V, Step<V>>>,
+    /// Values reached from any subset, with the smallest subset reaching
+    /// each
+    values: BTreeMap<V, usize>,
+}
+
+impl<V: Value> Reachable<V> {
+    /// Finds every value reachable from `numbers`.
+    ///
+    /// # Panics
+    /// If there are more numbers than bits in a `usize`.
+    pub fn new(numbers: &[V]) -> Reachable<V> {
+        assert!(numbers.len() < usize::BITS as usize, "too many numbers");
+        Reachable::from_subsets(build(numbers, 1 << numbers.len(), None))
+    }
+
+    /// Finds the values reachable from `numbers` like [`new`](#method.new),
+    /// stopping as soon as every one of `targets` is reached, since more
+    /// values can't change how they're reached. Only answers about the
+    /// targets are complete then: values and closest values beyond them
+    /// may be missing. The targets' expressions are still the shortest.
+    ///
+    /// # Panics
+    /// If there are more numbers than bits in a `usize`.
+    pub fn covering(numbers: &[V], targets: &[V]) -> Reachable<V> {
+        assert!(numbers.len() < usize::BITS as usize, "too many numbers");
+        let mut cover = Cover::new(targets);
+        Reachable::from_subsets(build(numbers, 1 << numbers.len(), Some(&mut cover)))
+    }
+
+    /// An expression reaching `target` from `numbers`, `None` if there is
+    /// none, found by meeting in the middle.
+    ///
+    /// The values of every subset but the whole set are built as in
+    /// [`new`](#method.new), then for each split of the whole set into two,
+    /// the value each value of one side needs from the other is looked up
+    /// rather than combining every pair. Quicker than `new` for a single
+    /// target, since the largest subset is never built.
+    ///
+    /// # Panics
+    /// If there are more numbers than bits in a `usize`.
+    pub fn meet(numbers: &[V], target: &V) -> Option<Term<V>> {
+        assert!(numbers.len() < usize::BITS as usize, "too many numbers");
+        if numbers.len() < 2 {
+            return Reachable::new(numbers).expression(target);
+        }
+        let all = (1usize << numbers.len()) - 1;
+        let reachable = Reachable::from_subsets(build(numbers, all, None));
+        if let Some(term) = reachable.expression(target) {
+            return Some(term);
+        }
+
+        let subsets = &reachable.subsets;
+        let mut a = (all - 1) & all;
+        while a > 0 {
+            let b = all ^ a;
+            for x in subsets[a].keys() {
+                for &op in Operator::ALL.iter() {
+                    // Both orders, the side of `x` isn't always the larger
+                    for &x_first in [true, false].iter() {
+                        let y = match needed(op, x, target, x_first) {
+                            Some(ref y) if subsets[b].contains_key(y) => y.clone(),
+                            _ => continue,
+                        };
+                        let (tx, ty) = (reachable.term(a, x), reachable.term(b, &y));
+                        let (hi, lo) = if x_first { (tx, ty) } else { (ty, tx) };
+                        return Some(Term {
+                            expression: Some((op, Box::new(hi), Box::new(lo))),
+                            value: target.clone(),
+                        });
+                    }
+                }
+            }
+            a = (a - 1) & all;
+        }
+        None
+    }
+
+    fn from_subsets(subsets: Vec<BTreeMap<V, Step<V>>>) -> Reachable<V> {
+        let mut values = BTreeMap::new();
+        for (mask, reached) in subsets.iter().enumerate() {
+            for v in reached.keys() {
+                let best = values.entry(v.clone()).or_insert(mask);
+                if mask.count_ones() < best.count_ones() {
+                    *best = mask;
+                }
+            }
+        }
+
+        Reachable { subsets, values }
+    }
+
+    /// Whether `value` can be reached, a starting number counts.
+    pub fn contains(&self, value: &V) -> bool {
+        self.values.contains_key(value)
+    }
+
+    /// Every reachable value, smallest first.
+    pub fn values(&self) -> btree_map::Keys<'_, V, usize> {
+        self.values.keys()
+    }
+
+    /// The reachable value closest to `target`, the smaller if two are as
+    /// close.
+    pub fn closest(&self, target: &V) -> Option<&V> {
+        let below = self.values.range(..=target.clone()).next_back().map(|(v, _)| v);
+        let above = self.values.range(target.clone()..).next().map(|(v, _)| v);
+        match (below, above) {
+            (Some(b), Some(a)) if a.distance(target) < b.distance(target) => Some(a),
+            (Some(b), _) => Some(b),
+            (None, a) => a,
+        }
+    }
+
+    /// An expression reaching `value` using as few numbers as possible,
+    /// `None` if it can't be reached.
+    pub fn expression(&self, value: &V) -> Option<Term<V>> {
+        self.values.get(value).map(|&mask| self.term(mask, value))
+    }
+
+    fn term(&self, mask: usize, value: &V) -> Term<V> {
+        match self.subsets[mask][value] {
+            Step::Number => Term { expression: None, value: value.clone() },
+            Step::Operation(op, a, ref x, b, ref y) => Term {
+                expression: Some((op,
+                    Box::new(self.term(a, x)),
+                    Box::new(self.term(b, y)))),
+                value: value.clone(),
+            },
+        }
+    }
+}
+
+/// Targets of [`Reachable::covering`](struct.Reachable.html#method.covering)
+/// and which of them are reached so far.
+struct Cover<V> {
+    /// The targets, sorted and without repeats
+    targets: Vec<V>,
+    /// Bit `i` set once the `i`-th target is reached
+    reached: Vec<u64>,
+    /// Targets not reached yet
+    left: usize,
+}
+
+impl<V: Value> Cover<V> {
+    fn new(targets: &[V]) -> Cover<V> {
+        let mut targets = targets.to_vec();
+        targets.sort();
+        targets.dedup();
+        let reached = alloc::vec![0; targets.len().div_ceil(64)];
+        let left = targets.len();
+        Cover { targets, reached, left }
+    }
+
+    /// Notes that `value` is reached.
+    fn mark(&mut self, value: &V) {
+        if let Ok(i) = self.targets.binary_search(value) {
+            let bit = 1 << (i % 64);
+            if self.reached[i / 64] & bit == 0 {
+                self.reached[i / 64] |= bit;
+                self.left -= 1;
+            }
+        }
+    }
+
+    fn done(&self) -> bool {
+        self.left == 0
+    }
+}
+
+/// Values reached from each subset of `numbers` with a bitmask below
+/// `masks`, indexed by bitmask.
+///
+/// Subsets are built level by level, those of one number first, then of
+/// two and so on, so every value is first reached with as few numbers, and
+/// so operations, as it can be. Stops once `cover` is done, if given,
+/// leaving the last subset partly built and those after it empty; the
+/// levels below are complete, so the values reached still have their
+/// shortest expressions.
+fn build<V: Value>(numbers: &[V], masks: usize, mut cover: Option<&mut Cover<V>>)
+    -> Vec<BTreeMap<V, Step<V>>>
+{
+    let mut subsets = (0..masks).map(|_| BTreeMap::new()).collect::<Vec<_>>();
+
+    for size in 1..=numbers.len() as u32 {
+        for mask in (1..masks).filter(|m| m.count_ones() == size) {
+            let mut reached = BTreeMap::new();
+            if size == 1 {
+                let i = mask.trailing_zeros() as usize;
+                if let Some(ref mut cover) = cover {
+                    cover.mark(&numbers[i]);
+                }
+                reached.insert(numbers[i].clone(), Step::Number);
+            }
+
+            // Each split into two non-empty subsets, once
+            let mut a = (mask - 1) & mask;
+            while a > 0 && !cover.as_ref().is_some_and(|c| c.done()) {
+                let b = mask ^ a;
+                if a < b {
+                    combine(&subsets, a, b, &mut reached, cover.as_deref_mut());
+                }
+                a = (a - 1) & mask;
+            }
+            subsets[mask] = reached;
+            if cover.as_ref().is_some_and(|c| c.done()) {
+                return subsets;
+            }
+        }
+    }
+    subsets
+}
+
+/// The value `y` for which `x op y`, or `y op x` unless `x_first`, is
+/// `target` under the rules, if any.
+fn needed<V: Value>(op: Operator, x: &V, target: &V, x_first: bool) -> Option<V> {
+    let y = match (op, x_first) {
+        (Operator::Addition, _) if target > x => target.sub(x),
+        (Operator::Subtraction, true) if x > target => x.sub(target),
+        (Operator::Subtraction, false) => target.checked_add(x)?,
+        (Operator::Multiplication, _) if target.is_multiple_of(x) => target.div(x),
+        (Operator::Division, true) if x.is_multiple_of(target) => x.div(target),
+        (Operator::Division, false) => target.checked_mul(x)?,
+        _ => return None,
+    };
+    // The larger operand goes first, as everywhere else
+    let ordered = if x_first { *x >= y } else { y >= *x };
+    if ordered && !y.is_zero() { Some(y) } else { None }
+}
+
+/// Adds every value reached by combining a value of subset `a` with one of
+/// subset `b` to `reached`, marking new ones in `cover` and stopping once
+/// it's done, checked after each value of `a`.
+fn combine<V: Value>(subsets: &[BTreeMap<V, Step<V>>], a: usize, b: usize,
+    reached: &mut BTreeMap<V, Step<V>>, mut cover: Option<&mut Cover<V>>)
+{
+    for x in subsets[a].keys() {
+        for y in subsets[b].keys() {
+            let (hi, lo) = if x >= y { ((a, x), (b, y)) } else { ((b, y), (a, x)) };
+            for &op in Operator::ALL.iter() {
+                if let Some(v) = apply(op, hi.1, lo.1) {
+                    if let Entry::Vacant(e) = reached.entry(v) {
+                        if let Some(ref mut cover) = cover {
+                            cover.mark(e.key());
+                        }
+                        e.insert(Step::Operation(op, hi.0, hi.1.clone(), lo.0, lo.1.clone()));
+                    }
+                }
+            }
+        }
+        if cover.as_ref().is_some_and(|c| c.done()) {
+            return;
+        }
+    }
+}
+
+/// `a op b` for `a >= b`, `None` if the rules don't allow it or it
+/// overflows.
+fn apply<V: Value>(op: Operator, a: &V, b: &V) -> Option<V> {
+    match op {
+        Operator::Addition => a.checked_add(b),
+        Operator::Subtraction if a > b => Some(a.sub(b)),
+        Operator::Subtraction => None,
+        Operator::Multiplication => a.checked_mul(b),
+        Operator::Division if a.is_multiple_of(b) => Some(a.div(b)),
+        Operator::Division | Operator::Custom(_) => None,
+    }
+}
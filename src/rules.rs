@@ -0,0 +1,278 @@
+//! The rules of the show: the tile selection, used to tell official
+//! rounds apart from freeform puzzles, and the [`Rules`](trait.Rules.html)
+//! the solver and verifier follow, of which variants of the game are other
+//! implementations.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use solver::Prune;
+use term::{Operator, Term};
+use value::Value;
+
+/// The small numbers, each of which appears twice in the pool.
+pub const SMALL_NUMBERS: [u64; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+
+/// The large numbers, each of which appears once in the pool.
+pub const LARGE_NUMBERS: [u64; 4] = [25, 50, 75, 100];
+
+/// Number of tiles in a round.
+pub const TILES: usize = 6;
+
+/// Smallest target the show generates.
+pub const MIN_TARGET: u64 = 100;
+
+/// Largest target the show generates.
+pub const MAX_TARGET: u64 = 999;
+
+/// A way a puzzle differs from a round which could occur on the show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// A round has six tiles, holds the number of tiles given.
+    TileCount(usize),
+    /// A small number appears more than twice, holds the number and how
+    /// many times it appears.
+    SmallRepeated(u64, usize),
+    /// A large number appears more than once, holds the number and how
+    /// many times it appears.
+    LargeRepeated(u64, usize),
+    /// A number which is in neither pool.
+    NotATile(u64),
+    /// A target which isn't a three-digit number.
+    Target(u64),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Violation::TileCount(n) => {
+                write!(f, "a round has {} numbers, not {}", TILES, n)
+            },
+            Violation::SmallRepeated(n, count) => {
+                write!(f, "{} is used {} times, but there are only two of each \
+                    small number", n, count)
+            },
+            Violation::LargeRepeated(n, count) => {
+                write!(f, "{} is used {} times, but there is only one of each \
+                    large number", n, count)
+            },
+            Violation::NotATile(n) => {
+                write!(f, "{} is not a tile, the small numbers are 1 to 10 \
+                    and the large numbers are 25, 50, 75 and 100", n)
+            },
+            Violation::Target(t) => {
+                write!(f, "target {} is not a three-digit number from {} to {}",
+                    t, MIN_TARGET, MAX_TARGET)
+            },
+        }
+    }
+}
+
+/// Constraints on the calculations and puzzles of a game. The solver asks
+/// them whenever it combines two terms, and the verifier checks solutions
+/// against them.
+pub trait Rules<V = u64> {
+    /// `a op b` for a basic operator, or why the rules don't allow it. The
+    /// solver passes the larger operand first, others may not.
+    fn apply(&self, op: Operator, a: &V, b: &V) -> Result<V, Prune>;
+
+    /// Whether `apply` is the arithmetic of
+    /// [`Countdown`](struct.Countdown.html). The solver then evaluates
+    /// terms without calling it, checking subtraction and division for
+    /// every pair of terms at once. No by default.
+    fn countdown_arithmetic(&self) -> bool {
+        false
+    }
+
+    /// Whether `term` may be declared in a puzzle of `numbers`, beyond
+    /// using each number at most once. Any term may by default.
+    fn accepts(&self, _term: &Term<V>, _numbers: &[V]) -> bool {
+        true
+    }
+
+    /// Every way the puzzle breaks the rules, none by default.
+    fn violations(&self, _numbers: &[u64], _target: u64) -> Vec<Violation> {
+        Vec::new()
+    }
+}
+
+/// The rules of the show: only positive integers at every step, and
+/// puzzles drawn from the tiles with a three-digit target. The default of
+/// the solver.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Countdown;
+
+impl<V: Value> Rules<V> for Countdown {
+    fn apply(&self, op: Operator, a: &V, b: &V) -> Result<V, Prune> {
+        match op {
+            Operator::Addition => a.checked_add(b).ok_or(Prune::Overflow),
+            // Negative intermediate values are not allowed in countdown
+            // and zero is not a useful term.
+            Operator::Subtraction if a <= b => Err(Prune::NotPositive),
+            Operator::Subtraction => Ok(a.sub(b)),
+            Operator::Multiplication => a.checked_mul(b).ok_or(Prune::Overflow),
+            // Fractions are not allowed in countdown
+            Operator::Division if !a.is_multiple_of(b) => Err(Prune::Fraction),
+            Operator::Division => Ok(a.div(b)),
+            Operator::Custom(_) => Err(Prune::Undefined),
+        }
+    }
+
+    fn countdown_arithmetic(&self) -> bool {
+        true
+    }
+
+    fn violations(&self, numbers: &[u64], target: u64) -> Vec<Violation> {
+        violations(numbers, target)
+    }
+}
+
+/// The calculations of the show on any numbers and target.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Freeform;
+
+impl<V: Value> Rules<V> for Freeform {
+    fn apply(&self, op: Operator, a: &V, b: &V) -> Result<V, Prune> {
+        Countdown.apply(op, a, b)
+    }
+
+    fn countdown_arithmetic(&self) -> bool {
+        true
+    }
+}
+
+/// The rules of the show, except a solution must use every number.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllTiles;
+
+impl<V: Value> Rules<V> for AllTiles {
+    fn apply(&self, op: Operator, a: &V, b: &V) -> Result<V, Prune> {
+        Countdown.apply(op, a, b)
+    }
+
+    fn countdown_arithmetic(&self) -> bool {
+        true
+    }
+
+    fn accepts(&self, term: &Term<V>, numbers: &[V]) -> bool {
+        term.tiles() == numbers.len()
+    }
+
+    fn violations(&self, numbers: &[u64], target: u64) -> Vec<Violation> {
+        violations(numbers, target)
+    }
+}
+
+/// Points scored on the show for a declaration `distance` away from the
+/// target: 10 for reaching it, 7 within 5 and 5 within 10.
+pub fn score(distance: u64) -> u32 {
+    match distance {
+        0 => 10,
+        1..=5 => 7,
+        6..=10 => 5,
+        _ => 0,
+    }
+}
+
+/// Checks a puzzle against the selection rules of the show, returns every
+/// rule it breaks. An empty list means the round could occur on the show.
+pub fn violations(numbers: &[u64], target: u64) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    if numbers.len() != TILES {
+        violations.push(Violation::TileCount(numbers.len()));
+    }
+
+    let mut seen = Vec::new();
+    for &n in numbers {
+        if seen.contains(&n) {
+            continue;
+        }
+        seen.push(n);
+
+        let count = numbers.iter().filter(|&&m| m == n).count();
+        if SMALL_NUMBERS.contains(&n) {
+            if count > 2 {
+                violations.push(Violation::SmallRepeated(n, count));
+            }
+        } else if LARGE_NUMBERS.contains(&n) {
+            if count > 1 {
+                violations.push(Violation::LargeRepeated(n, count));
+            }
+        } else {
+            violations.push(Violation::NotATile(n));
+        }
+    }
+
+    if !(MIN_TARGET..=MAX_TARGET).contains(&target) {
+        violations.push(Violation::Target(target));
+    }
+
+    violations
+}
+
+/// Every distinct selection of tiles which could occur on the show, 13243
+/// in all. Each lists its large numbers then its small numbers, largest
+/// first, and they are ordered by the number of large numbers, so a
+/// selection's position in the list never changes.
+pub fn selections() -> Vec<Vec<u64>> {
+    let mut selections = Vec::new();
+    for large in 0..=LARGE_NUMBERS.len() {
+        let mut bigs = Vec::new();
+        large_subsets(large, LARGE_NUMBERS.len(), &mut bigs, &mut |bigs| {
+            let mut small = Vec::new();
+            small_multisets(TILES - large, SMALL_NUMBERS.len(), &mut small, &mut |small| {
+                let mut selection = bigs.to_vec();
+                selection.extend_from_slice(small);
+                selections.push(selection);
+            });
+        });
+    }
+    selections
+}
+
+/// Number of ways `selection` can be drawn from the pool, how much more
+/// likely it is than a selection which can only be drawn one way. Each
+/// small number repeated in the selection can only be drawn one way,
+/// the others either of two.
+pub fn draws(selection: &[u64]) -> u64 {
+    SMALL_NUMBERS.iter()
+        .filter(|&n| selection.iter().filter(|&m| m == n).count() == 1)
+        .fold(1, |ways, _| ways * 2)
+}
+
+/// Calls `f` with each way of choosing `k` of the first `n` large numbers,
+/// each once.
+fn large_subsets<F: FnMut(&[u64])>(k: usize, n: usize, chosen: &mut Vec<u64>, f: &mut F) {
+    if k == 0 {
+        return f(chosen);
+    }
+    // Largest first, the pool is in ascending order
+    for i in (k - 1..n).rev() {
+        chosen.push(LARGE_NUMBERS[i]);
+        large_subsets(k - 1, i, chosen, f);
+        chosen.pop();
+    }
+}
+
+/// Calls `f` with each way of choosing `k` of the first `n` small numbers,
+/// each at most twice.
+fn small_multisets<F: FnMut(&[u64])>(k: usize, n: usize, chosen: &mut Vec<u64>, f: &mut F) {
+    if k == 0 {
+        return f(chosen);
+    }
+    for i in (0..n).rev() {
+        for copies in (1..=2).rev() {
+            if copies > k {
+                continue;
+            }
+            for _ in 0..copies {
+                chosen.push(SMALL_NUMBERS[i]);
+            }
+            small_multisets(k - copies, i, chosen, f);
+            for _ in 0..copies {
+                chosen.pop();
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! How declarations are scored by their distance from the target.
+
+use alloc::vec::Vec;
+
+use rules;
+
+/// A way of scoring a declaration by how far it is from the target.
+pub trait ScoringScheme {
+    /// Points for a declaration `distance` away from the target.
+    fn score(&self, distance: u64) -> u32;
+    /// Points for reaching the target, the most a declaration can score.
+    fn max(&self) -> u32 {
+        self.score(0)
+    }
+}
+
+/// The scoring of the show, see [`rules::score`](../rules/fn.score.html).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Official;
+
+impl ScoringScheme for Official {
+    fn score(&self, distance: u64) -> u32 {
+        rules::score(distance)
+    }
+}
+
+/// A point lost for every step away from the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Linear {
+    /// Points for reaching the target
+    pub points: u32,
+}
+
+impl ScoringScheme for Linear {
+    fn score(&self, distance: u64) -> u32 {
+        if distance >= u64::from(self.points) {
+            0
+        } else {
+            self.points - distance as u32
+        }
+    }
+}
+
+/// Points for declarations within given distances, such as a teacher's
+/// own version of the official bands.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Bands {
+    /// The bands, in any order. A declaration scores the points of the
+    /// narrowest band it's within, nothing if it's outside all of them.
+    pub bands: Vec<Band>,
+}
+
+/// A band of [`Bands`](struct.Bands.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Band {
+    /// Largest distance from the target in the band
+    pub within: u64,
+    /// Points for a declaration in the band
+    pub points: u32,
+}
+
+impl ScoringScheme for Bands {
+    fn score(&self, distance: u64) -> u32 {
+        self.bands.iter()
+            .filter(|b| distance <= b.within)
+            .min_by_key(|b| b.within)
+            .map_or(0, |b| b.points)
+    }
+
+    fn max(&self) -> u32 {
+        self.bands.iter().map(|b| b.points).max().unwrap_or(0)
+    }
+}
@@ -0,0 +1,106 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::fmt;
+
+use human;
+use term::{Operator, Term};
+
+/// A solution along with metrics describing it, as returned by the solver.
+///
+/// The expression is shared rather than copied, so cloning a solution is
+/// cheap and solutions can be handed between threads as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Solution<V = u64> {
+    /// Expression reaching the target
+    pub term: Arc<Term<V>>,
+    /// How hard the solution is for a person to find, lower is easier,
+    /// see [`human::cost`](human/fn.cost.html)
+    pub difficulty: u32,
+    /// Which of the starting numbers are used, see
+    /// [`Term::usage`](struct.Term.html#method.usage)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub usage: u64,
+}
+
+impl<V: fmt::Display + PartialEq> Solution<V> {
+    /// Wrap a term reaching the target in a puzzle of `numbers`,
+    /// calculating its metrics.
+    pub fn new(term: Term<V>, numbers: &[V]) -> Solution<V> {
+        let difficulty = human::cost(&term);
+        let usage = term.usage(numbers);
+        Solution { term: Arc::new(term), difficulty, usage }
+    }
+}
+
+impl<V: fmt::Display> Solution<V> {
+    /// Wrap a term reaching the target whose usage is already known.
+    pub fn with_usage(term: Term<V>, usage: u64) -> Solution<V> {
+        let difficulty = human::cost(&term);
+        Solution { term: Arc::new(term), difficulty, usage }
+    }
+}
+
+impl<V> Solution<V> {
+    /// Levels of operations in the expression, see
+    /// [`Term::depth`](struct.Term.html#method.depth).
+    pub fn depth(&self) -> usize {
+        self.term.depth()
+    }
+
+    /// Number of operations in the expression.
+    pub fn operations(&self) -> usize {
+        self.term.operations()
+    }
+
+    /// Number of times `op` is used in the expression.
+    pub fn count(&self, op: Operator) -> usize {
+        self.term.count(op)
+    }
+
+    /// Number of times each operator is used, in the order of
+    /// [`Operator::ALL`](enum.Operator.html#associatedconstant.ALL).
+    pub fn counts(&self) -> [usize; 4] {
+        self.term.counts()
+    }
+
+    /// Number of starting numbers used.
+    pub fn tiles(&self) -> usize {
+        self.term.tiles()
+    }
+
+    /// Whether the starting number at `index` is used.
+    pub fn uses(&self, index: usize) -> bool {
+        index < 64 && self.usage & 1 << index != 0
+    }
+}
+
+impl<V: fmt::Display> Solution<V> {
+    /// The expression without redundant spaces or parentheses, see
+    /// [`Term::compact`](struct.Term.html#method.compact).
+    pub fn compact(&self) -> String {
+        self.term.compact()
+    }
+}
+
+impl<V: Ord> Solution<V> {
+    /// Largest intermediate value, see
+    /// [`Term::peak`](struct.Term.html#method.peak).
+    pub fn peak(&self) -> &V {
+        self.term.peak()
+    }
+}
+
+impl<V: Ord + Clone> Solution<V> {
+    /// The expression in canonical form, see
+    /// [`Term::canonical`](struct.Term.html#method.canonical).
+    pub fn canonical(&self) -> Term<V> {
+        (*self.term).clone().canonical()
+    }
+}
+
+impl<V: fmt::Display> fmt::Display for Solution<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} = {}", self.term, self.term.value)
+    }
+}
@@ -0,0 +1,1385 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::time::Duration;
+
+use builder::SolverBuilder;
+use clock::Clock;
+use error::Error;
+use reach::Reachable;
+use rules::{Countdown, Rules};
+use solution::Solution;
+use stack::{Tag, TermStack, Transpositions, INLINE};
+use term::{Custom, Expr, Operation, Operator, Term};
+use value::Value;
+#[cfg(feature = "profile")]
+use profile::{Choice, OrderProfile};
+
+/// Countdown Numbers game solver
+///
+/// Combining different pairs of terms in either order can leave the same
+/// stack of terms, from which the search would find the same expressions
+/// again. Each stack is hashed Zobrist style: every starting number has a
+/// random key, every term a hash mixed from its operator and operands, and
+/// the stack the sum of its terms' hashes, updated as terms are inserted
+/// and removed. The sum only says where to look: a stack is skipped if
+/// exactly the same one, terms in the same order, was expanded before.
+/// A new term goes before those equal to it, so equal terms can be stacked
+/// in either order, which puts a different one first when they're
+/// combined: stacks only differing in it are both searched. Nothing is
+/// skipped if every search order is asked for with
+/// [`Dedup::None`](enum.Dedup.html#variant.None).
+#[derive(Debug)]
+pub struct Solver<V = u64> {
+    /// Stack of remaining terms, boxed so terms can be moved in and out
+    /// of expressions without reallocating.
+    remaining: TermStack<V>,
+    /// List of solutions found
+    solutions: Vec<Solution<V>>,
+    /// Starting numbers, in the order given
+    numbers: Vec<V>,
+    /// Closest term to the target found so far
+    closest: Option<Box<Term<V>>>,
+    /// Target number
+    target: V,
+    /// Search statistics
+    stats: Stats,
+    /// Clock used to time the search
+    clock: Box<dyn Clock>,
+    /// Time spent in `solve()`
+    elapsed: Duration,
+    /// Clock reading when `solve()` started
+    started: Duration,
+    /// Called as top-level branches complete
+    progress: Option<ProgressHook>,
+    /// Number of top-level branches completed
+    branches_done: usize,
+    /// Number of top-level branches in the search
+    branches: usize,
+    /// Recursion depth of the search, 1 at the top level
+    depth: usize,
+    /// Called for every expanded node
+    trace: Option<TraceHook<V>>,
+    /// Number of nodes expanded, used as node ids
+    nodes: u64,
+    /// Id of the node being expanded, 0 at the top level
+    parent: u64,
+    /// Maximum number of solutions stored
+    max_solutions: Option<usize>,
+    /// Random numbers for reservoir sampling past the solution limit
+    sampler: Option<XorShift>,
+    /// How duplicate solutions are detected
+    dedup: Dedup,
+    order: SearchOrder,
+    engine: Engine,
+    /// Largest value any term may take
+    max_intermediate: Option<V>,
+    /// Largest number of operations any term may have
+    max_operations: Option<usize>,
+    /// Operator every solution must use
+    must_use: Option<Operator>,
+    /// Operators no term may use
+    forbidden: Vec<Operator>,
+    /// Number of starting numbers every solution must use
+    tiles: Option<usize>,
+    /// Starting numbers every solution must use, as a mask
+    required_tiles: u16,
+    /// Starting numbers no term may use, as a mask
+    excluded_tiles: u16,
+    /// Asked whether to stop the search early
+    cancel: Option<CancelHook>,
+    /// Told about solutions, progress and the end of the search
+    observer: Option<ObserverHook<V>>,
+    /// Operations tried after the basic four
+    custom: Operations<V>,
+    /// Rules of the game being solved
+    rules: GameRules<V>,
+    /// Whether the rules use the arithmetic of Countdown, asked once
+    countdown_arithmetic: bool,
+    /// Hashes of the stacks of terms expanded so far
+    transpositions: Transpositions,
+    /// Targets of `solve_each` and their solutions so far, by target
+    each: Vec<(V, Vec<Solution<V>>)>,
+    /// Choices on the paths to solutions, if collected
+    #[cfg(feature = "profile")]
+    profile: Option<OrderProfile>,
+    /// Choice made at each depth of the search, while profiling
+    #[cfg(feature = "profile")]
+    path: Vec<Choice>,
+}
+
+/// How the solver decides two solutions are the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dedup {
+    /// Solutions with the same tree of operators and values are the same.
+    /// Starting numbers are compared by value, so when a number appears
+    /// twice, solutions which only differ in which copy was used are
+    /// reported once. This is the semantic dedup, there is no separate
+    /// mode for it. The default.
+    #[default]
+    Value,
+    /// Every solution is reported as found, including the same expression
+    /// reached through different search orders or with a different copy
+    /// of a repeated number.
+    None,
+}
+
+/// How [`Solver::solve`](struct.Solver.html#method.solve) searches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Engine {
+    /// Enumerates every expression, finding every solution. The default,
+    /// and the only engine honouring the options and rules of the solver.
+    #[default]
+    Exhaustive,
+    /// Builds the values of every subset of the numbers with
+    /// [`Reachable::new`](struct.Reachable.html#method.new), finding one
+    /// shortest solution, or else the closest value. Quicker for many
+    /// numbers, but only uses the basic operators with the show's
+    /// arithmetic.
+    SubsetDp,
+    /// [`Reachable::meet`](struct.Reachable.html#method.meet), finding one
+    /// solution without building the values of the whole set. Quicker still
+    /// when there is a solution, but a miss only leaves the closest
+    /// starting number. Also limited to the basic operators.
+    MeetInTheMiddle,
+}
+
+/// Order the search tries pairs of terms and operators in, which decides
+/// how soon solutions are found but not which are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchOrder {
+    /// Choices which most often lead to solutions first, learned from
+    /// random rounds. The default.
+    #[default]
+    Learned,
+    /// Pairs from the largest terms down and operators in
+    /// [`Operator::ALL`](enum.Operator.html#associatedconstant.ALL) order.
+    Naive,
+}
+
+/// Basic operators in the order `SearchOrder::Learned` tries them.
+const LEARNED_OPERATORS: [Operator; 4] = [Operator::Addition, Operator::Multiplication,
+    Operator::Subtraction, Operator::Division];
+
+/// Pairs of each number of terms left in the order `SearchOrder::Learned`
+/// tries them, learned with `bench --profile-ordering 2000`. More terms
+/// than the six of a round are tried in the naive order.
+const LEARNED_PAIRS: [&[(u8, u8)]; 7] = [
+    &[],
+    &[],
+    &[(0, 1)],
+    &[(0, 2), (0, 1), (1, 2)],
+    &[(0, 3), (0, 2), (1, 3), (1, 2), (0, 1), (2, 3)],
+    &[(0, 4), (0, 2), (0, 3), (1, 3), (1, 4), (1, 2), (2, 4), (2, 3), (0, 1), (3, 4)],
+    &[(1, 3), (0, 2), (0, 5), (0, 3), (0, 4), (1, 2), (1, 5), (1, 4), (2, 4), (2, 5), (3, 5),
+        (2, 3), (4, 5), (0, 1), (3, 4)],
+];
+
+/// Which pairs of terms subtraction and division are defined for, see
+/// `Solver::defined`.
+type Defined = ([u16; INLINE], [u16; INLINE]);
+
+/// Small xorshift64* generator, enough for sampling without pulling in a
+/// random number crate.
+#[derive(Debug, Clone)]
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> XorShift {
+        // The state must never be zero
+        XorShift(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Random number in `0..n`
+    fn below(&mut self, n: usize) -> usize {
+        ((u128::from(self.next()) * n as u128) >> 64) as usize
+    }
+}
+
+/// Why an expression was pruned from the search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prune {
+    /// A subtraction with a negative or zero result
+    NotPositive,
+    /// A division with a remainder
+    Fraction,
+    /// The value didn't fit in the value type
+    Overflow,
+    /// The value exceeded the maximum intermediate value
+    TooLarge,
+    /// A custom operation had no value for the operands
+    Undefined,
+}
+
+/// A node expanded by the search, as passed to the trace callback.
+#[derive(Debug)]
+pub struct Node<'a, V: 'a = u64> {
+    /// Id of the node, ids count up from 1 in the order nodes are expanded
+    pub id: u64,
+    /// Id of the node whose term this node extends, 0 at the top level
+    pub parent: u64,
+    /// Operator applied
+    pub op: Operator,
+    /// Value of the left-hand, larger operand
+    pub a: &'a V,
+    /// Value of the right-hand, smaller operand
+    pub b: &'a V,
+    /// Value of the new term, or why it was pruned
+    pub value: Result<&'a V, Prune>,
+}
+
+/// Trace callback, receives every expanded node.
+type TraceFn<V> = dyn FnMut(&Node<V>);
+
+/// Boxed trace callback.
+struct TraceHook<V>(Box<TraceFn<V>>);
+
+impl<V> fmt::Debug for TraceHook<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("TraceHook")
+    }
+}
+
+/// Progress callback, receives completed and total top-level branches.
+struct ProgressHook(Box<dyn FnMut(usize, usize)>);
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
+
+/// Receives events from a search as it runs, for front ends reporting on
+/// it. Every method does nothing by default.
+pub trait Observer<V = u64> {
+    /// Called with each solution as it's found, including solutions past
+    /// the limit set by [`set_max_solutions`](struct.Solver.html#method.set_max_solutions)
+    /// which aren't stored.
+    fn on_solution(&mut self, _solution: &Solution<V>) {}
+
+    /// Called as each top-level branch completes, with the number of nodes
+    /// expanded so far and the most operations a term may have, which
+    /// grows as [`solve_best`](struct.Solver.html#method.solve_best)
+    /// deepens.
+    fn on_progress(&mut self, _nodes: u64, _depth: usize) {}
+
+    /// Called once the search is over, finished or cancelled.
+    fn on_finish(&mut self, _stats: &Stats) {}
+}
+
+/// Boxed observer.
+struct ObserverHook<V>(Box<dyn Observer<V>>);
+
+impl<V> fmt::Debug for ObserverHook<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ObserverHook")
+    }
+}
+
+/// Operations added with `add_operator`.
+struct Operations<V>(Vec<Box<dyn Operation<V>>>);
+
+impl<V> fmt::Debug for Operations<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.0.iter().map(|o| o.name())).finish()
+    }
+}
+
+/// Rules set with `set_rules`.
+struct GameRules<V>(Box<dyn Rules<V>>);
+
+impl<V> fmt::Debug for GameRules<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("GameRules")
+    }
+}
+
+/// Cancellation callback, returns true once the search should stop.
+struct CancelHook(Box<dyn Fn() -> bool>);
+
+impl fmt::Debug for CancelHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("CancelHook")
+    }
+}
+
+/// Statistics collected during a search.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stats {
+    /// Number of valid expressions evaluated
+    pub expressions: usize,
+    /// Number of branches pruned because a subtraction wasn't positive
+    pub not_positive: usize,
+    /// Number of branches pruned because a division left a fraction
+    pub fractions: usize,
+    /// Number of branches pruned because a value overflowed
+    pub overflows: usize,
+    /// Number of branches pruned for exceeding the maximum intermediate value
+    pub too_large: usize,
+    /// Number of branches pruned because a custom operation had no value
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub undefined: usize,
+    /// Time from the start of the search until the first solution was found
+    pub first_solution: Option<Duration>,
+    /// Number of solutions found, including those not stored once the
+    /// solution limit was reached. Past the limit, a solution found twice
+    /// through different search paths may be counted twice.
+    pub solutions: usize,
+    /// Number of stacks of terms skipped as they were expanded before, see
+    /// [`Solver`](struct.Solver.html)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub transpositions: usize,
+    /// Whether the search was cancelled before it finished, so the other
+    /// statistics only cover part of it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cancelled: bool,
+}
+
+/// Outcome of a finished search, detached from the solver so it can be
+/// kept, persisted or sent elsewhere.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Report<V = u64> {
+    /// Starting numbers
+    pub numbers: Vec<V>,
+    /// Target number
+    pub target: V,
+    /// Solutions found
+    pub solutions: Vec<Solution<V>>,
+    /// Term closest to the target
+    pub closest: Option<Term<V>>,
+    /// Search statistics
+    pub stats: Stats,
+    /// Time spent searching
+    pub elapsed: Duration,
+}
+
+impl<V: Value> Report<V> {
+    /// Whether solutions were found but not stored because of the limit.
+    pub fn truncated(&self) -> bool {
+        self.stats.solutions > self.solutions.len()
+    }
+
+    /// Whether the search covered every expression the rules allow, see
+    /// [`Solver::exhaustive`](struct.Solver.html#method.exhaustive).
+    pub fn exhaustive(&self) -> bool {
+        self.stats.overflows == 0 && !self.stats.cancelled
+    }
+
+    /// The most elegant solution, see
+    /// [`Solver::recommended`](struct.Solver.html#method.recommended).
+    pub fn recommended(&self) -> Option<&Solution<V>> {
+        self.solutions.iter().min_by(|a, b| cmp_elegance(&a.term, &b.term))
+    }
+}
+
+/// Orders solutions by elegance, most elegant first.
+fn cmp_elegance<V: Ord>(a: &Term<V>, b: &Term<V>) -> Ordering {
+    a.operations().cmp(&b.operations())
+        .then_with(|| a.count(Operator::Division).cmp(&b.count(Operator::Division)))
+        .then_with(|| a.peak().cmp(b.peak()))
+        .then_with(|| a.cmp(b))
+}
+
+/// The stack of starting terms of a search, failing unless there are at
+/// least two numbers and all values are positive.
+fn starting_terms<V: Value>(numbers: &[V], target: &V) -> Result<TermStack<V>, Error> {
+    if numbers.len() < 2 {
+        return Err(Error::TooFewNumbers(numbers.len()));
+    }
+    if target.is_zero() || numbers.iter().any(|n| n.is_zero()) {
+        return Err(Error::Zero);
+    }
+
+    // Each number is masked by its position as given, before sorting
+    let mut remaining = numbers.iter().enumerate()
+        .map(|(i, n)| (Box::new(Term{
+            expression: None,
+            value: n.clone(),
+        }), tag(i, n))).collect::<Vec<_>>();
+
+    remaining.sort_by(|a, b| a.0.value.cmp(&b.0.value).reverse());
+    Ok(TermStack::new(remaining))
+}
+
+impl<V: Value> Solver<V> {
+    /// Initiate Solver, fails unless there are at least two numbers and
+    /// all values are positive.
+    pub fn new(numbers: &[V], target: V) -> Result<Solver<V>, Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("setup", tiles = numbers.len()).entered();
+
+        let mut solver = Solver {
+            remaining: starting_terms(numbers, &target)?,
+            solutions: Vec::new(),
+            numbers: numbers.to_vec(),
+            closest: None,
+            target,
+            stats: Stats::default(),
+            clock: default_clock(),
+            elapsed: Duration::from_secs(0),
+            started: Duration::from_secs(0),
+            progress: None,
+            branches_done: 0,
+            branches: 0,
+            depth: 0,
+            trace: None,
+            nodes: 0,
+            parent: 0,
+            max_solutions: None,
+            sampler: None,
+            dedup: Dedup::default(),
+            order: SearchOrder::default(),
+            engine: Engine::default(),
+            max_intermediate: None,
+            max_operations: None,
+            must_use: None,
+            forbidden: Vec::new(),
+            tiles: None,
+            required_tiles: 0,
+            excluded_tiles: 0,
+            cancel: None,
+            observer: None,
+            custom: Operations(Vec::new()),
+            rules: GameRules(Box::new(Countdown)),
+            countdown_arithmetic: true,
+            transpositions: Transpositions::default(),
+            each: Vec::new(),
+            #[cfg(feature = "profile")]
+            profile: None,
+            #[cfg(feature = "profile")]
+            path: Vec::new(),
+        };
+        solver.closest = solver.closest_number();
+        Ok(solver)
+    }
+
+    /// Starts over with new numbers and target, failing like
+    /// [`new`](#method.new). Options, hooks, operators and rules are kept,
+    /// so a long-lived solver is set up once rather than for each puzzle.
+    /// Only the masks of tiles required or excluded are cleared, they
+    /// refer to positions in the old numbers. Nothing a search learns
+    /// carries over, it all depends on the target.
+    pub fn reset(&mut self, numbers: &[V], target: V) -> Result<(), Error> {
+        self.remaining = starting_terms(numbers, &target)?;
+        self.numbers = numbers.to_vec();
+        self.target = target;
+        self.solutions.clear();
+        self.stats = Stats::default();
+        self.nodes = 0;
+        self.parent = 0;
+        self.elapsed = Duration::from_secs(0);
+        self.required_tiles = 0;
+        self.excluded_tiles = 0;
+        self.each.clear();
+        self.closest = self.closest_number();
+        Ok(())
+    }
+
+    /// Builder taking the numbers, target and options one at a time.
+    pub fn builder() -> SolverBuilder<V> where V: 'static {
+        SolverBuilder::new()
+    }
+
+    /// Replace the clock used to time the search.
+    pub fn set_clock<C: Clock + 'static>(&mut self, clock: C) {
+        self.clock = Box::new(clock);
+    }
+
+    /// Target number
+    pub fn target(&self) -> &V {
+        &self.target
+    }
+
+    /// Solutions found so far
+    pub fn solutions(&self) -> &[Solution<V>] {
+        &self.solutions[..]
+    }
+
+    /// The most elegant solution found so far, the one a person would
+    /// most likely want to see: fewest starting numbers used, then no
+    /// division if avoidable, then the smallest intermediate values.
+    pub fn recommended(&self) -> Option<&Solution<V>> {
+        self.solutions.iter().min_by(|a, b| cmp_elegance(&a.term, &b.term))
+    }
+
+    /// Term closest to the target found so far, an exact solution if any
+    /// were found.
+    pub fn closest(&self) -> Option<&Term<V>> {
+        self.closest.as_deref()
+    }
+
+    /// Set a callback to report progress, called with the number of
+    /// completed and total top-level branches each time one completes. A
+    /// top-level branch is a pair of starting numbers and an operator.
+    pub fn set_progress<F: FnMut(usize, usize) + 'static>(&mut self, f: F) {
+        self.progress = Some(ProgressHook(Box::new(f)));
+    }
+
+    /// Set a callback receiving every node the search expands, including
+    /// pruned ones. Meant for recording and visualizing the search, the
+    /// callback runs in the hot loop so it should be cheap.
+    pub fn set_trace<F: FnMut(&Node<V>) + 'static>(&mut self, f: F) {
+        self.trace = Some(TraceHook(Box::new(f)));
+    }
+
+    /// Limit how many solutions are stored. Once the limit is reached the
+    /// search continues and counts further solutions in
+    /// [`Stats::solutions`](struct.Stats.html#structfield.solutions), but
+    /// doesn't keep them.
+    pub fn set_max_solutions(&mut self, limit: usize) {
+        self.max_solutions = Some(limit);
+    }
+
+    /// Keep a uniform random sample of all solutions found once the solution
+    /// limit is reached, instead of the first ones, using reservoir
+    /// sampling seeded with `seed`.
+    pub fn set_sampling(&mut self, seed: u64) {
+        self.sampler = Some(XorShift::new(seed));
+    }
+
+    /// Set the order choices are tried in, `SearchOrder::Learned` by
+    /// default.
+    pub fn set_order(&mut self, order: SearchOrder) {
+        self.order = order;
+    }
+
+    /// Set the engine [`solve()`](#method.solve) searches with,
+    /// `Engine::Exhaustive` by default. The other methods of solving always
+    /// enumerate.
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.engine = engine;
+    }
+
+    /// Count the choices on the paths to the solutions found, see
+    /// [`profile()`](#method.profile).
+    #[cfg(feature = "profile")]
+    pub fn set_profiling(&mut self, on: bool) {
+        self.profile = if on { Some(OrderProfile::default()) } else { None };
+    }
+
+    /// Choices on the paths to the solutions found, if profiling. Only
+    /// solutions reported once are counted, like in
+    /// [`solutions()`](#method.solutions).
+    #[cfg(feature = "profile")]
+    pub fn profile(&self) -> Option<&OrderProfile> {
+        self.profile.as_ref()
+    }
+
+    /// Set how duplicate solutions are detected, `Dedup::Value` by default.
+    pub fn set_dedup(&mut self, dedup: Dedup) {
+        self.dedup = dedup;
+    }
+
+    /// Prune every expression whose value exceeds `max`, a variant rule
+    /// for puzzles meant to be solved by mental arithmetic.
+    pub fn set_max_intermediate(&mut self, max: V) {
+        self.max_intermediate = Some(max);
+    }
+
+    /// Only count expressions using `op` at least once as solutions, or as
+    /// the closest term, for drills practising an operation. Other
+    /// expressions are still extended, since using one more number may
+    /// bring `op` in.
+    pub fn set_must_use(&mut self, op: Operator) {
+        self.must_use = Some(op);
+    }
+
+    /// Never apply any of `ops`, so only expressions without them are
+    /// searched. A search without solutions then proves the puzzle needs
+    /// one of them.
+    pub fn set_forbidden(&mut self, ops: &[Operator]) {
+        self.forbidden = ops.to_vec();
+    }
+
+    /// Only count expressions using exactly `n` starting numbers as
+    /// solutions, or as the closest term.
+    pub fn set_tiles(&mut self, n: usize) {
+        self.tiles = Some(n);
+    }
+
+    /// Only count expressions using every starting number in `mask` as
+    /// solutions, or as the closest term. Bit `i` stands for the `i`-th
+    /// number given, so only puzzles of up to 16 numbers can require them.
+    pub fn set_required_tiles(&mut self, mask: u16) {
+        self.required_tiles = mask;
+    }
+
+    /// Never use the starting numbers in `mask`, bit `i` standing for the
+    /// `i`-th number given. Puzzles of more than 16 numbers can't exclude
+    /// them.
+    pub fn set_excluded_tiles(&mut self, mask: u16) {
+        self.excluded_tiles = mask;
+    }
+
+    /// Set a callback asked now and then during the search whether to
+    /// stop. Once it returns true the search unwinds, keeping what it
+    /// found so far, and [`Stats::cancelled`](struct.Stats.html#structfield.cancelled)
+    /// is set.
+    pub fn set_cancel<F: Fn() -> bool + 'static>(&mut self, f: F) {
+        self.cancel = Some(CancelHook(Box::new(f)));
+    }
+
+    /// Play a variant of the game, [`Countdown`](rules/struct.Countdown.html)
+    /// by default.
+    pub fn set_rules<R: Rules<V> + 'static>(&mut self, rules: R) {
+        self.countdown_arithmetic = rules.countdown_arithmetic();
+        self.rules = GameRules(Box::new(rules));
+    }
+
+    /// Also combine terms with `op`, after the basic operations. Both
+    /// orders of the operands are tried unless it's commutative.
+    pub fn add_operator<O: Operation<V> + 'static>(&mut self, op: O) {
+        self.custom.0.push(Box::new(op));
+    }
+
+    /// Set an observer told about each solution, the progress of the
+    /// search and its end, replacing any set before.
+    pub fn set_observer<O: Observer<V> + 'static>(&mut self, observer: O) {
+        self.observer = Some(ObserverHook(Box::new(observer)));
+    }
+
+    /// Whether solutions were found but not stored because of the limit.
+    pub fn truncated(&self) -> bool {
+        self.stats.solutions > self.solutions.len()
+    }
+
+    /// Whether the search covered every expression the rules allow, so a
+    /// search without solutions proves there are none. Only branches
+    /// pruned because a value overflowed, or left when the search was
+    /// cancelled, are left out. The cap set by
+    /// [`set_max_intermediate`](#method.set_max_intermediate) and the
+    /// operators left out by [`set_forbidden`](#method.set_forbidden)
+    /// count as rules.
+    pub fn exhaustive(&self) -> bool {
+        self.stats.overflows == 0 && !self.stats.cancelled
+    }
+
+    /// Statistics of the search so far
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Number of nodes expanded so far, pruned ones included.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Time spent in `solve()`, as measured by the solver's clock.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Report of the search so far.
+    pub fn report(&self) -> Report<V> {
+        Report {
+            numbers: self.numbers.clone(),
+            target: self.target.clone(),
+            solutions: self.solutions.clone(),
+            closest: self.closest.as_ref().map(|t| (**t).clone()),
+            stats: self.stats.clone(),
+            elapsed: self.elapsed,
+        }
+    }
+
+    /// Test an expression with the given value, or why it's pruned, as a
+    /// solution, then continue combining terms.
+    fn try_expr(&mut self, expr: Expr<V>, tags: (Tag, Tag), value: Result<V, Prune>)
+        -> Expr<V>
+    {
+        let mask = tags.0.mask | tags.1.mask;
+        assert!(expr.1.value >= expr.2.value || matches!(expr.0, Operator::Custom(_)),
+            "terms vector is not sorted");
+
+        // Variant rule leaving operators out, nothing past them is visited
+        if self.forbidden.contains(&expr.0) || mask & self.excluded_tiles != 0 {
+            return expr;
+        }
+
+        // Iterative deepening, terms can't grow past the current depth
+        if let Some(max) = self.max_operations {
+            if expr.1.operations() + expr.2.operations() + 1 > max {
+                return expr;
+            }
+        }
+
+        // Variant rule capping intermediate values
+        let value = match (value, &self.max_intermediate) {
+            (Ok(ref v), Some(max)) if v > max => Err(Prune::TooLarge),
+            (value, _) => value,
+        };
+
+        self.nodes += 1;
+        let id = self.nodes;
+        if let Some(ref mut trace) = self.trace {
+            (trace.0)(&Node {
+                id,
+                parent: self.parent,
+                op: expr.0,
+                a: &expr.1.value,
+                b: &expr.2.value,
+                value: value.as_ref().map_err(|p| *p),
+            });
+        }
+
+        let mut c = match value {
+            Ok(value) => Box::new(Term {
+                value,
+                expression: Some(expr),
+            }),
+            Err(Prune::Overflow) => {
+                self.stats.overflows += 1;
+                return expr;
+            },
+            Err(Prune::TooLarge) => {
+                self.stats.too_large += 1;
+                return expr;
+            },
+            Err(Prune::NotPositive) => {
+                self.stats.not_positive += 1;
+                return expr;
+            },
+            Err(Prune::Fraction) => {
+                self.stats.fractions += 1;
+                return expr;
+            },
+            Err(Prune::Undefined) => {
+                self.stats.undefined += 1;
+                return expr;
+            },
+        };
+
+        self.stats.expressions += 1;
+        
+        // Test if this is a valid solution
+        let accepted = self.accepts(&c, mask);
+        let tiles = self.tiles_of(&c, mask);
+        // Equal trees use as many numbers, which rules most out cheaply
+        if c.value == self.target && accepted &&
+            (self.dedup == Dedup::None || !self.solutions.iter()
+                .any(|s| s.tiles() == tiles && *s.term == *c))
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(solution = %c, expressions = self.stats.expressions,
+                "solution found");
+            if self.stats.first_solution.is_none() {
+                self.stats.first_solution = Some(self.clock.now() - self.started);
+            }
+            self.stats.solutions += 1;
+            #[cfg(feature = "profile")]
+            {
+                if let Some(ref mut profile) = self.profile {
+                    profile.record(&self.path);
+                }
+            }
+            let found = self.stats.solutions;
+            let slot = match self.max_solutions {
+                // Reservoir sampling, the n-th solution replaces a random
+                // stored one with probability max / n
+                Some(max) if self.solutions.len() >= max => {
+                    self.sampler.as_mut().map(|s| s.below(found)).filter(|&i| i < max)
+                },
+                _ => Some(self.solutions.len()),
+            };
+            // Metrics are only worked out for solutions someone sees
+            if slot.is_some() || self.observer.is_some() {
+                let solution = if self.remaining.tagged() {
+                    Solution::with_usage((*c).clone(), u64::from(mask))
+                } else {
+                    Solution::new((*c).clone(), &self.numbers)
+                };
+                if let Some(ref mut observer) = self.observer {
+                    observer.0.on_solution(&solution);
+                }
+                match slot {
+                    Some(i) if i < self.solutions.len() => self.solutions[i] = solution,
+                    Some(_) => self.solutions.push(solution),
+                    None => {},
+                }
+            }
+        }
+
+        if accepted && !self.each.is_empty() {
+            self.record_each(&c, mask);
+        }
+
+        // Remember the closest term in case there is no exact solution,
+        // which the rules must accept too
+        if accepted &&
+            self.closest.as_ref().is_none_or(|t| {
+                c.value.distance(&self.target) < t.value.distance(&self.target)
+            })
+        {
+            self.closest = Some(c.clone());
+        }
+
+        if !self.remaining.is_empty() {
+            // Find Insert position so self.remaining remains sorted
+            let pos = (0..self.remaining.len())
+                .find(|&k| self.remaining.get(k).value <= c.value)
+                .unwrap_or(self.remaining.len());
+
+            // Insert new term and continue recursively combining terms.
+            // The stack is returned to its original state after the recursive
+            // call so we can pop our term, deconstruct it and return
+            // the expression when we are done.
+            let tag = match c.expression {
+                Some((op, _, _)) if self.remaining.tagged() => Tag {
+                    mask,
+                    hash: combine(op_key(op), tags.0.hash, tags.1.hash),
+                    // A product is divided by the primes of its factors
+                    primes: if op == Operator::Multiplication {
+                        tags.0.primes | tags.1.primes
+                    } else {
+                        c.value.small_primes()
+                    },
+                    ..self.code(op, tags)
+                },
+                _ => Tag::default(),
+            };
+            self.remaining.insert(pos, c, tag);
+            if self.expand() {
+                let parent = self.parent;
+                self.parent = id;
+                self.search();
+                self.parent = parent;
+            }
+            c = self.remaining.remove(pos).0;
+        }
+        c.expression.unwrap()
+    }
+
+    /// Whether to search the stack of terms, which isn't worth it for a
+    /// single term or a stack expanded before.
+    fn expand(&mut self) -> bool {
+        if self.remaining.len() < 2 {
+            return false;
+        }
+        if self.dedup == Dedup::None || !self.remaining.tagged() {
+            return true;
+        }
+        match self.remaining.key() {
+            Some(key) if !self.transpositions.insert(self.remaining.state(), key) => {
+                self.stats.transpositions += 1;
+                false
+            },
+            _ => true,
+        }
+    }
+
+    /// A tag with just the code of a term made by `op` from operands
+    /// tagged `a` and `b`: theirs in postfix, then the operator's index,
+    /// in as few bits as the basic and custom operators take, after a 0.
+    fn code(&self, op: Operator, (a, b): (Tag, Tag)) -> Tag {
+        let ops = Operator::ALL.len() + self.custom.0.len();
+        let width = 1 + usize::BITS - (ops - 1).leading_zeros();
+        let bits = u32::from(a.bits) + u32::from(b.bits) + width;
+        if a.bits == 0 || b.bits == 0 || bits > u128::BITS {
+            return Tag::default();
+        }
+        let index = match op {
+            Operator::Custom(c) => Operator::ALL.len() +
+                self.custom.0.iter().position(|o| o.name() == c.name).expect("a custom operator"),
+            op => Operator::ALL.iter().position(|&o| o == op).expect("a basic operator"),
+        };
+        Tag {
+            code: (a.code << b.bits | b.code) << width | index as u128,
+            bits: bits as u8,
+            ..Tag::default()
+        }
+    }
+
+    /// Number of starting numbers `term` uses, from its mask if tracked.
+    fn tiles_of(&self, term: &Term<V>, mask: u16) -> usize {
+        if self.remaining.tagged() { mask.count_ones() as usize } else { term.tiles() }
+    }
+
+    /// Whether `term`, using the starting numbers in `mask`, may be a
+    /// solution or the closest term.
+    fn accepts(&self, term: &Term<V>, mask: u16) -> bool {
+        self.must_use.is_none_or(|op| term.count(op) > 0) &&
+            self.tiles.is_none_or(|n| self.tiles_of(term, mask) == n) &&
+            mask & self.required_tiles == self.required_tiles &&
+            mask & self.excluded_tiles == 0 &&
+            self.rules.0.accepts(term, &self.numbers)
+    }
+
+    /// The starting number closest to the target which may be declared on
+    /// its own, the closest term before any are combined.
+    fn closest_number(&self) -> Option<Box<Term<V>>> {
+        self.numbers.iter().enumerate()
+            .map(|(i, n)| (Box::new(Term { expression: None, value: n.clone() }), tag(i, n)))
+            .filter(|&(ref t, tag)| self.accepts(t, tag.mask))
+            .min_by_key(|t| t.0.value.distance(&self.target))
+            .map(|t| t.0)
+    }
+
+    /// Finds all valid expressions resulting in the target number. The
+    /// solutions are sorted in the canonical order of terms afterwards.
+    /// Other engines than `Engine::Exhaustive` find at most one, see
+    /// [`set_engine`](#method.set_engine).
+    pub fn solve(&mut self) {
+        if self.engine != Engine::Exhaustive {
+            return self.solve_reachable();
+        }
+        let n = self.remaining.len();
+        self.branches = n * (n - 1) / 2 * self.operations();
+        self.branches_done = 0;
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("solve", target = %self.target,
+            tiles = n, branches = self.branches).entered();
+
+        self.started = self.clock.now();
+        self.closest = self.closest_number();
+        self.transpositions.clear();
+        self.search();
+        self.solutions.sort_by(|a, b| a.term.cmp(&b.term));
+        self.elapsed = self.clock.now() - self.started;
+        self.finish();
+    }
+
+    /// Solves with [`Reachable`](struct.Reachable.html), for the engines
+    /// which don't enumerate.
+    fn solve_reachable(&mut self) {
+        self.started = self.clock.now();
+        self.closest = self.closest_number();
+        let (solution, closest) = match self.engine {
+            Engine::SubsetDp => {
+                let reachable = Reachable::new(&self.numbers[..]);
+                (reachable.expression(&self.target),
+                    reachable.closest(&self.target).and_then(|v| reachable.expression(v)))
+            },
+            Engine::MeetInTheMiddle => {
+                let solution = Reachable::meet(&self.numbers[..], &self.target);
+                (solution.clone(), solution)
+            },
+            Engine::Exhaustive => unreachable!("the exhaustive engine enumerates"),
+        };
+
+        if let Some(c) = closest {
+            if self.closest.as_ref().is_none_or(|t| {
+                c.value.distance(&self.target) < t.value.distance(&self.target)
+            }) {
+                self.closest = Some(Box::new(c));
+            }
+        }
+        if let Some(term) = solution {
+            self.solutions.push(Solution::new(term, &self.numbers));
+            self.stats.solutions += 1;
+        }
+        self.elapsed = self.clock.now() - self.started;
+        self.finish();
+    }
+
+    /// Finds the solutions of each of `targets` in a single search, sharing
+    /// the work of combining terms between them instead of searching once
+    /// per target. Returns them in the order of `targets`, each sorted like
+    /// [`solutions()`](#method.solutions). Every solution is kept whatever
+    /// the limit, and the observer isn't told of them. The solver's own
+    /// target is solved as by [`solve()`](#method.solve).
+    pub fn solve_each(&mut self, targets: &[V]) -> Vec<Vec<Solution<V>>> {
+        let mut each = targets.to_vec();
+        each.sort();
+        each.dedup();
+        self.each = each.into_iter().map(|t| (t, Vec::new())).collect();
+        self.solve();
+
+        let each = core::mem::take(&mut self.each);
+        targets.iter().map(|t| {
+            let k = each.binary_search_by(|e| e.0.cmp(t)).expect("every target is searched");
+            each[k].1.clone()
+        }).collect()
+    }
+
+    /// Stores `term` with the solutions of its value, if that's one of the
+    /// targets of [`solve_each`](#method.solve_each). Each target's
+    /// solutions are kept sorted, so an equal tree, the only kind the
+    /// order ties with it, is found by a binary search.
+    fn record_each(&mut self, term: &Term<V>, mask: u16) {
+        let k = match self.each.binary_search_by(|e| e.0.cmp(&term.value)) {
+            Ok(k) => k,
+            Err(_) => return,
+        };
+        let pos = match self.each[k].1.binary_search_by(|s| (*s.term).cmp(term)) {
+            Ok(_) if self.dedup == Dedup::Value => return,
+            Ok(pos) | Err(pos) => pos,
+        };
+        let solution = if self.remaining.tagged() {
+            Solution::with_usage(term.clone(), u64::from(mask))
+        } else {
+            Solution::new(term.clone(), &self.numbers)
+        };
+        self.each[k].1.insert(pos, solution);
+    }
+
+    /// Finds the `k` solutions with the fewest operations, ties broken by
+    /// the smaller peak value, then the canonical order.
+    ///
+    /// Searches by iterative deepening: first allowing terms of a single
+    /// operation, then two and so on, stopping at the first depth with at
+    /// least `k` solutions. Puzzles with short solutions are answered
+    /// without enumerating every expression. The solution count in
+    /// [`stats()`](#method.stats) is for the final depth searched.
+    pub fn solve_best(&mut self, k: usize) {
+        let n = self.remaining.len();
+        self.started = self.clock.now();
+        self.closest = self.closest_number();
+
+        for depth in 1..n {
+            self.branches = n * (n - 1) / 2 * self.operations();
+            self.branches_done = 0;
+            self.solutions.clear();
+            self.stats.solutions = 0;
+            self.max_operations = Some(depth);
+            // Stacks expanded in shallower passes weren't searched as deep
+            self.transpositions.clear();
+            self.search();
+            if self.solutions.len() >= k || self.stats.cancelled {
+                break;
+            }
+        }
+
+        self.max_operations = None;
+        self.solutions.sort_by(|a, b| {
+            a.operations().cmp(&b.operations())
+                .then_with(|| a.peak().cmp(b.peak()))
+                .then_with(|| a.term.cmp(&b.term))
+        });
+        self.solutions.truncate(k);
+        self.elapsed = self.clock.now() - self.started;
+        self.finish();
+    }
+
+    /// Recursively combines two and two terms into a binary expression tree,
+    /// test if it’s a valid solution as we go along.
+    fn search(&mut self) {
+        self.depth += 1;
+        let n = self.remaining.len();
+        let defined = self.defined(n);
+        for p in 0..n * (n.max(1) - 1) / 2 {
+            if self.cancelled() {
+                break;
+            }
+            let (i, j) = self.pair(n, p);
+            let (b, b_tag) = self.remaining.remove(j);
+            let (a, a_tag) = self.remaining.remove(i);
+            let mut expr = (Operator::Addition, a, b);
+            for k in 0..Operator::ALL.len() {
+                let op = self.operator(k);
+                #[cfg(feature = "tracing")]
+                let _span = if self.depth == 1 {
+                    tracing::trace_span!("branch", a = %expr.1, b = %expr.2,
+                        op = ?op).entered()
+                } else {
+                    tracing::Span::none().entered()
+                };
+
+                #[cfg(feature = "profile")]
+                self.choose(Choice { terms: n, pair: (i, j), op });
+                expr.0 = op;
+                let value = self.evaluate(op, &expr.1.value, &expr.2.value, &defined, (i, j));
+                match value {
+                    // Without a trace pruned expressions are only counted
+                    Err(prune) if defined.is_some() => {
+                        self.count_pruned(&expr, a_tag.mask | b_tag.mask, prune);
+                    },
+                    value => expr = self.try_expr(expr, (a_tag, b_tag), value),
+                }
+                if self.depth == 1 {
+                    self.branch_done();
+                }
+            }
+            for k in 0..self.custom.0.len() {
+                let custom = Custom::of(&*self.custom.0[k]);
+                expr.0 = Operator::Custom(custom);
+                #[cfg(feature = "profile")]
+                self.choose(Choice { terms: n, pair: (i, j), op: expr.0 });
+                let value = self.apply_custom(custom, &expr.1.value, &expr.2.value);
+                expr = self.try_expr(expr, (a_tag, b_tag), value);
+                if !custom.commutative {
+                    let value = self.apply_custom(custom, &expr.2.value, &expr.1.value);
+                    let (op, b, a) = self.try_expr((expr.0, expr.2, expr.1), (b_tag, a_tag),
+                        value);
+                    expr = (op, a, b);
+                }
+                if self.depth == 1 {
+                    self.branch_done();
+                }
+            }
+
+            self.remaining.insert(i, expr.1, a_tag);
+            self.remaining.insert(j, expr.2, b_tag);
+        }
+        self.depth -= 1;
+    }
+
+    /// Which pairs of the `n` terms left subtraction and division are
+    /// defined for, bit `j` of row `i` for the `i`-th and `j`-th terms.
+    /// Checked for every pair at once, ruling most divisions out by the
+    /// terms' small primes before dividing, when the rules allow it and no
+    /// trace needs the pruned nodes.
+    fn defined(&self, n: usize) -> Option<Defined> {
+        if !self.countdown_arithmetic || self.trace.is_some() || !self.remaining.tagged() {
+            return None;
+        }
+        let mut sub = [0u16; INLINE];
+        let mut div = [0u16; INLINE];
+        for i in 0..n {
+            let a = &self.remaining.get(i).value;
+            let a_primes = self.remaining.tag(i).primes;
+            for j in i + 1..n {
+                let b = &self.remaining.get(j).value;
+                sub[i] |= u16::from(a > b) << j;
+                // Without dividing, a divisor can't have a small prime the
+                // dividend lacks, nor be more than half of it unless equal
+                let possible = self.remaining.tag(j).primes & !a_primes == 0 &&
+                    (a == b || b.checked_add(b).is_some_and(|b2| *a >= b2));
+                div[i] |= u16::from(possible && a.is_multiple_of(b)) << j;
+            }
+        }
+        Some((sub, div))
+    }
+
+    /// `a op b` for a basic operator and the `i`-th and `j`-th terms, or
+    /// why it's pruned. Evaluated directly from what
+    /// [`defined`](#method.defined) found, if it checked the pairs.
+    fn evaluate(&self, op: Operator, a: &V, b: &V, defined: &Option<Defined>,
+        (i, j): (usize, usize)) -> Result<V, Prune>
+    {
+        let (sub, div) = match *defined {
+            Some((ref sub, ref div)) => (sub[i] >> j & 1 == 1, div[i] >> j & 1 == 1),
+            None => return self.rules.0.apply(op, a, b),
+        };
+        match op {
+            Operator::Addition => a.checked_add(b).ok_or(Prune::Overflow),
+            Operator::Subtraction if sub => Ok(a.sub(b)),
+            Operator::Subtraction => Err(Prune::NotPositive),
+            Operator::Multiplication => a.checked_mul(b).ok_or(Prune::Overflow),
+            Operator::Division if div => Ok(a.div(b)),
+            Operator::Division => Err(Prune::Fraction),
+            Operator::Custom(_) => Err(Prune::Undefined),
+        }
+    }
+
+    /// `a op b` for a custom operator, or why it's pruned.
+    fn apply_custom(&self, op: Custom, a: &V, b: &V) -> Result<V, Prune> {
+        self.custom.0.iter().find(|o| o.name() == op.name)
+            .and_then(|o| o.apply(a, b))
+            .ok_or(Prune::Undefined)
+    }
+
+    /// Counts an expression pruned without a trace, as `try_expr` would
+    /// have.
+    fn count_pruned(&mut self, expr: &Expr<V>, mask: u16, prune: Prune) {
+        if self.forbidden.contains(&expr.0) || mask & self.excluded_tiles != 0 {
+            return;
+        }
+        if let Some(max) = self.max_operations {
+            if expr.1.operations() + expr.2.operations() + 1 > max {
+                return;
+            }
+        }
+        self.nodes += 1;
+        match prune {
+            Prune::Overflow => self.stats.overflows += 1,
+            Prune::TooLarge => self.stats.too_large += 1,
+            Prune::NotPositive => self.stats.not_positive += 1,
+            Prune::Fraction => self.stats.fractions += 1,
+            Prune::Undefined => self.stats.undefined += 1,
+        }
+    }
+
+    /// The `p`-th pair of the `n` terms left to try, as positions with the
+    /// larger term first.
+    fn pair(&self, n: usize, p: usize) -> (usize, usize) {
+        if self.order == SearchOrder::Learned && n < LEARNED_PAIRS.len() {
+            let (i, j) = LEARNED_PAIRS[n][p];
+            return (i as usize, j as usize);
+        }
+        // Pairs in order, (0, 1), (0, 2) .. (1, 2) ..
+        let mut i = 0;
+        let mut p = p;
+        while p >= n - 1 - i {
+            p -= n - 1 - i;
+            i += 1;
+        }
+        (i, i + 1 + p)
+    }
+
+    /// The `k`-th basic operator to try.
+    fn operator(&self, k: usize) -> Operator {
+        match self.order {
+            SearchOrder::Learned => LEARNED_OPERATORS[k],
+            SearchOrder::Naive => Operator::ALL[k],
+        }
+    }
+
+    /// Notes the choice made at the current depth, while profiling.
+    #[cfg(feature = "profile")]
+    fn choose(&mut self, choice: Choice) {
+        if self.profile.is_some() {
+            self.path.truncate(self.depth - 1);
+            self.path.push(choice);
+        }
+    }
+
+    /// Number of operations tried on each pair of terms.
+    fn operations(&self) -> usize {
+        Operator::ALL.len() + self.custom.0.len()
+    }
+
+    /// Whether the search was cancelled, asking the callback until it is.
+    fn cancelled(&mut self) -> bool {
+        if !self.stats.cancelled {
+            if let Some(ref cancel) = self.cancel {
+                self.stats.cancelled = (cancel.0)();
+            }
+        }
+        self.stats.cancelled
+    }
+
+    /// Count a completed top-level branch and report progress.
+    fn branch_done(&mut self) {
+        self.branches_done += 1;
+        if let Some(ref mut progress) = self.progress {
+            (progress.0)(self.branches_done, self.branches);
+        }
+        if let Some(ref mut observer) = self.observer {
+            let depth = self.max_operations.unwrap_or(self.numbers.len() - 1);
+            observer.0.on_progress(self.nodes, depth);
+        }
+    }
+
+    /// Tell the observer the search is over.
+    fn finish(&mut self) {
+        if let Some(ref mut observer) = self.observer {
+            observer.0.on_finish(&self.stats);
+        }
+    }
+}
+
+/// Tag of the `i`-th starting number given: its bit, if tracked, its
+/// Zobrist key and the small primes dividing it.
+fn tag<V: Value>(i: usize, value: &V) -> Tag {
+    Tag {
+        mask: if i < INLINE { 1 << i } else { 0 },
+        hash: mix(i as u64 + 1),
+        primes: value.small_primes(),
+        code: 0b10000 | i as u128,
+        bits: if i < INLINE { 5 } else { 0 },
+    }
+}
+
+/// Hash of a term made by the operator keyed `op` from operands hashed `a`
+/// and `b`, which changes when they're swapped.
+fn combine(op: u64, a: u64, b: u64) -> u64 {
+    mix(a ^ mix(b ^ op))
+}
+
+/// Key of an operator for hashing, custom ones keyed by name.
+fn op_key(op: Operator) -> u64 {
+    match op {
+        // FNV-1a
+        Operator::Custom(c) => c.name.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+            (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        }),
+        op => Operator::ALL.iter().position(|&o| o == op).expect("basic operator") as u64,
+    }
+}
+
+/// The splitmix64 finalizer, spreading every input bit over the output.
+fn mix(mut x: u64) -> u64 {
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Upper bound on the number of expressions evaluated when solving with
+/// the given number of starting numbers, saturating at `u64::MAX`.
+///
+/// Each step combines one of the `k * (k - 1) / 2` unordered pairs of the
+/// `k` remaining terms, as the larger term always goes first, with one of
+/// the four operators, then continues with `k - 1` terms. The actual count
+/// is lower since negative, zero, fractional and overflowing results are
+/// pruned along the way.
+pub fn search_space(tiles: usize) -> u64 {
+    let mut total: u64 = 0;
+    for k in 2..tiles as u64 + 1 {
+        let branches = (k * (k - 1) / 2).saturating_mul(Operator::ALL.len() as u64);
+        total = branches.saturating_mul(total.saturating_add(1));
+    }
+    total
+}
+
+/// Clock used unless another one is set, `StdClock` with `std`.
+#[cfg(feature = "std")]
+fn default_clock() -> Box<dyn Clock> {
+    Box::new(::clock::StdClock::new())
+}
+
+/// Clock used unless another one is set, `NoClock` without `std`.
+#[cfg(not(feature = "std"))]
+fn default_clock() -> Box<dyn Clock> {
+    Box::new(::clock::NoClock)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    /// Distinct solutions as trees, as `Dedup::Value` should report them.
+    fn solutions(numbers: &[u64], target: u64, dedup: Dedup) -> Vec<Term> {
+        let mut solver = Solver::new(numbers, target).unwrap();
+        solver.set_dedup(dedup);
+        solver.solve();
+        let mut found = solver.solutions().iter()
+            .map(|s| (*s.term).clone())
+            .collect::<Vec<_>>();
+        found.sort();
+        found.dedup();
+        found
+    }
+
+    /// Skipping stacks searched before mustn't lose a solution which only
+    /// differs in the order of equal operands, such as
+    /// `((100 * ((6 / 2) + (2 + 1))) - 8)`, compared with the search
+    /// without skipping.
+    #[test]
+    fn transpositions_keep_every_tree() {
+        let puzzles = [
+            ([100, 2, 8, 2, 1, 6], 592, 1020),
+            ([75, 25, 100, 50, 3, 4], 122, 1279),
+            ([50, 25, 100, 75, 3, 9], 262, 1097),
+        ];
+        for &(numbers, target, count) in puzzles.iter() {
+            let found = solutions(&numbers, target, Dedup::Value);
+            assert_eq!(found.len(), count, "{:?} {}", numbers, target);
+            assert_eq!(found, solutions(&numbers, target, Dedup::None), "{:?} {}", numbers, target);
+        }
+    }
+
+    #[test]
+    fn reset_starts_the_node_count_over() {
+        let mut solver = Solver::new(&[100u64, 6, 2, 2, 1], 600).unwrap();
+        solver.solve();
+        let nodes = solver.nodes();
+        solver.reset(&[100, 6, 2, 2, 1], 600).unwrap();
+        assert_eq!(solver.nodes(), 0);
+        solver.solve();
+        assert_eq!(solver.nodes(), nodes);
+    }
+}
@@ -0,0 +1,229 @@
+//! The stack of terms a search has left to combine.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use term::Term;
+
+/// Terms kept in place, more than the six of a round and the few made of
+/// them, without reaching for the heap. Also the number of starting
+/// numbers whose use is tracked in a `u16`.
+pub const INLINE: usize = 16;
+
+/// What the stack tracks about a term.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tag {
+    /// Starting numbers the term uses, bit `i` standing for the `i`-th
+    /// number given
+    pub mask: u16,
+    /// Zobrist hash of the tree, see [`Solver`](../struct.Solver.html)
+    pub hash: u64,
+    /// Small primes dividing the value, see
+    /// [`Value::small_primes`](../trait.Value.html#method.small_primes)
+    pub primes: u8,
+    /// The tree written out exactly in postfix, the low `bits` bits. A
+    /// number is a 1 bit and its position in 4 bits, an operator a 0 bit
+    /// and its index. 0 bits if it doesn't fit.
+    pub code: u128,
+    pub bits: u8,
+}
+
+/// Terms left to combine. Held inline for puzzles of up to 16 numbers, so
+/// inserting and removing in the hot loop only shifts a few pointers
+/// within the solver.
+///
+/// Inline terms carry a [`Tag`](struct.Tag.html), so questions about tiles
+/// are answered without walking trees, and the stack keeps the sum of
+/// their hashes as a hash of the whole multiset, updated as terms come and
+/// go. Their codes make up an exact [`key`](#method.key) of the stack.
+/// Larger puzzles don't track them.
+// Being inline is the point, boxing the large variant would undo it
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum TermStack<V> {
+    /// Slots `0..len` hold terms and their tags, the rest are empty
+    Inline {
+        slots: [Option<Box<Term<V>>>; INLINE],
+        tags: [Tag; INLINE],
+        len: usize,
+        state: u64,
+    },
+    /// Puzzles with more numbers
+    Heap(Vec<Box<Term<V>>>),
+}
+
+impl<V> TermStack<V> {
+    /// Stack holding `terms` with their tags, in order.
+    pub fn new(terms: Vec<(Box<Term<V>>, Tag)>) -> TermStack<V> {
+        if terms.len() > INLINE {
+            return TermStack::Heap(terms.into_iter().map(|(term, _)| term).collect());
+        }
+        let len = terms.len();
+        let mut slots: [Option<Box<Term<V>>>; INLINE] = Default::default();
+        let mut tags = [Tag::default(); INLINE];
+        let mut state = 0u64;
+        for (i, (term, tag)) in terms.into_iter().enumerate() {
+            slots[i] = Some(term);
+            tags[i] = tag;
+            state = state.wrapping_add(tag.hash);
+        }
+        TermStack::Inline { slots, tags, len, state }
+    }
+
+    /// Whether tags are tracked.
+    pub fn tagged(&self) -> bool {
+        matches!(*self, TermStack::Inline { .. })
+    }
+
+    /// Hash of the multiset of terms, 0 if tags aren't tracked.
+    pub fn state(&self) -> u64 {
+        match *self {
+            TermStack::Inline { state, .. } => state,
+            TermStack::Heap(_) => 0,
+        }
+    }
+
+    /// The codes of the terms one after the other, the stack exactly, down
+    /// to the order of equal terms. Evaluated as postfix they leave the
+    /// terms on the stack in order, and the first bit is always that of a
+    /// number, so no two stacks share a key. `None` if tags aren't tracked
+    /// or the codes don't fit, which with the basic operators they always
+    /// do, as `n` numbers take at most `8n - 3` bits.
+    pub fn key(&self) -> Option<u128> {
+        let (tags, len) = match *self {
+            TermStack::Inline { ref tags, len, .. } => (tags, len),
+            TermStack::Heap(_) => return None,
+        };
+        let mut key = 0u128;
+        let mut bits = 0;
+        for tag in tags[..len].iter() {
+            bits += u32::from(tag.bits);
+            if tag.bits == 0 || bits > u128::BITS {
+                return None;
+            }
+            key = key << tag.bits | tag.code;
+        }
+        Some(key)
+    }
+
+    pub fn len(&self) -> usize {
+        match *self {
+            TermStack::Inline { len, .. } => len,
+            TermStack::Heap(ref terms) => terms.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The term at `index`.
+    pub fn get(&self, index: usize) -> &Term<V> {
+        match *self {
+            TermStack::Inline { ref slots, .. } => {
+                slots[index].as_ref().expect("slots below the length hold terms")
+            },
+            TermStack::Heap(ref terms) => &terms[index],
+        }
+    }
+
+    /// The tag of the term at `index`, empty if tags aren't tracked.
+    pub fn tag(&self, index: usize) -> Tag {
+        match *self {
+            TermStack::Inline { ref tags, .. } => tags[index],
+            TermStack::Heap(_) => Tag::default(),
+        }
+    }
+
+    /// Inserts `term` with its `tag` at `index`, shifting the terms after
+    /// it up.
+    pub fn insert(&mut self, index: usize, term: Box<Term<V>>, tag: Tag) {
+        match *self {
+            TermStack::Inline { ref mut slots, ref mut tags, ref mut len, ref mut state } => {
+                // Terms are only inserted where one was removed, so there
+                // is always a free slot
+                slots[*len] = Some(term);
+                tags[*len] = tag;
+                for k in (index..*len).rev() {
+                    slots.swap(k, k + 1);
+                    tags.swap(k, k + 1);
+                }
+                *len += 1;
+                *state = state.wrapping_add(tag.hash);
+            },
+            TermStack::Heap(ref mut terms) => terms.insert(index, term),
+        }
+    }
+
+    /// Removes the term at `index` and its tag, shifting the terms after it
+    /// down. The tag is empty if tags aren't tracked.
+    pub fn remove(&mut self, index: usize) -> (Box<Term<V>>, Tag) {
+        match *self {
+            TermStack::Inline { ref mut slots, ref mut tags, ref mut len, ref mut state } => {
+                let term = slots[index].take();
+                let tag = tags[index];
+                for k in index + 1..*len {
+                    slots.swap(k - 1, k);
+                    tags.swap(k - 1, k);
+                }
+                *len -= 1;
+                *state = state.wrapping_sub(tag.hash);
+                (term.expect("slots below the length hold terms"), tag)
+            },
+            TermStack::Heap(ref mut terms) => (terms.remove(index), Tag::default()),
+        }
+    }
+}
+
+/// The stacks a search has expanded, a set of their
+/// [`key`](enum.TermStack.html#method.key)s with open addressing, placed by
+/// their hash. 0 marks an empty slot, so it's never stored.
+#[derive(Debug, Default)]
+pub struct Transpositions {
+    slots: Vec<(u64, u128)>,
+    len: usize,
+}
+
+impl Transpositions {
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    /// Adds `key`, whose stack hashes to `hash`, returns whether it wasn't
+    /// there yet. The hash only decides where to look, so a collision never
+    /// skips a stack.
+    pub fn insert(&mut self, hash: u64, key: u128) -> bool {
+        // Grow to keep the table at most half full
+        if (self.len + 1) * 2 > self.slots.len() {
+            let old = core::mem::take(&mut self.slots);
+            self.slots.resize((old.len() * 2).max(1024), (0, 0));
+            for (h, k) in old.into_iter().filter(|e| e.1 != 0) {
+                self.place(h, k);
+            }
+        }
+        if self.place(hash, key) {
+            self.len += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Stores `key` in the first free slot from the home of `hash`, unless
+    /// found on the way.
+    fn place(&mut self, hash: u64, key: u128) -> bool {
+        let last = self.slots.len() - 1;
+        let mut i = hash as usize & last;
+        loop {
+            match self.slots[i] {
+                (_, 0) => {
+                    self.slots[i] = (hash, key);
+                    return true;
+                },
+                (_, k) if k == key => return false,
+                _ => i = (i + 1) & last,
+            }
+        }
+    }
+}
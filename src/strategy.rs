@@ -0,0 +1,239 @@
+//! Simple strategies contestants use under the clock, to compare how often
+//! each gets close against an exhaustive search.
+
+use alloc::vec::Vec;
+
+use parse::apply;
+use term::{Operator, Term};
+
+/// A way of playing a round which doesn't search every expression.
+pub trait Strategy {
+    /// Short name of the strategy.
+    fn name(&self) -> &'static str;
+    /// The declaration the strategy arrives at, `None` without numbers.
+    fn play(&self, numbers: &[u64], target: u64) -> Option<Term>;
+}
+
+/// Repeatedly combines the two terms, with the operator, giving the value
+/// closest to the target, declaring the closest value seen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Greedy;
+
+impl Strategy for Greedy {
+    fn name(&self) -> &'static str {
+        "greedy"
+    }
+
+    fn play(&self, numbers: &[u64], target: u64) -> Option<Term> {
+        let mut terms = numbers.iter().map(|&n| number(n)).collect::<Vec<_>>();
+        let mut best = closest(terms.iter().cloned(), target)?;
+
+        while terms.len() > 1 {
+            let mut step: Option<(usize, usize, Term)> = None;
+            for i in 0..terms.len() {
+                for j in 0..terms.len() {
+                    if i == j || terms[i].value < terms[j].value {
+                        continue;
+                    }
+                    for &op in Operator::ALL.iter() {
+                        let t = match apply(op, terms[i].clone(), terms[j].clone()) {
+                            Ok(t) => t,
+                            Err(_) => continue,
+                        };
+                        let distance = t.value.abs_diff(target);
+                        if step.as_ref().is_none_or(|s| distance < s.2.value.abs_diff(target)) {
+                            step = Some((i, j, t));
+                        }
+                    }
+                }
+            }
+
+            // Every pair can at least be added, unless it overflows
+            let (i, j, t) = match step {
+                Some(step) => step,
+                None => break,
+            };
+            let (hi, lo) = (i.max(j), i.min(j));
+            terms.remove(hi);
+            terms.remove(lo);
+            if t.value.abs_diff(target) < best.value.abs_diff(target) {
+                best = t.clone();
+            }
+            if t.value == target {
+                break;
+            }
+            terms.push(t);
+        }
+        Some(best)
+    }
+}
+
+/// Aims for a multiple of 25 near the target, a number or the product of
+/// two, then adds or subtracts the remaining numbers, or products of two
+/// of them, while that gets closer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoundThenAdjust;
+
+impl Strategy for RoundThenAdjust {
+    fn name(&self) -> &'static str {
+        "round-then-adjust"
+    }
+
+    fn play(&self, numbers: &[u64], target: u64) -> Option<Term> {
+        // Bases with the indices of the numbers they use
+        let mut bases = Vec::new();
+        for (i, &a) in numbers.iter().enumerate() {
+            bases.push(([i].to_vec(), number(a)));
+            for (j, &b) in numbers.iter().enumerate().skip(i + 1) {
+                let (hi, lo) = if a >= b { (a, b) } else { (b, a) };
+                if let Ok(t) = apply(Operator::Multiplication, number(hi), number(lo)) {
+                    bases.push(([i, j].to_vec(), t));
+                }
+            }
+        }
+        let round = bases.iter().filter(|(_, t)| t.value % 25 == 0)
+            .min_by_key(|(_, t)| t.value.abs_diff(target))
+            .cloned();
+        // Without a multiple of 25, start from the closest base instead
+        let (mut used, mut term) = match round {
+            Some(base) => base,
+            None => bases.into_iter().min_by_key(|(_, t)| t.value.abs_diff(target))?,
+        };
+
+        while term.value != target {
+            let remaining = (0..numbers.len()).filter(|i| !used.contains(i)).collect::<Vec<_>>();
+            let mut operands = Vec::new();
+            for (k, &i) in remaining.iter().enumerate() {
+                operands.push(([i].to_vec(), number(numbers[i])));
+                for &j in &remaining[k + 1..] {
+                    let (hi, lo) = if numbers[i] >= numbers[j] { (i, j) } else { (j, i) };
+                    if let Ok(t) = apply(Operator::Multiplication,
+                        number(numbers[hi]), number(numbers[lo]))
+                    {
+                        operands.push(([i, j].to_vec(), t));
+                    }
+                }
+            }
+
+            let op = if term.value < target { Operator::Addition } else { Operator::Subtraction };
+            let next = operands.into_iter()
+                .filter_map(|(uses, operand)| {
+                    apply(op, term.clone(), operand).ok().map(|t| (uses, t))
+                })
+                .min_by_key(|(_, t)| t.value.abs_diff(target));
+            match next {
+                Some((uses, t)) if t.value.abs_diff(target) < term.value.abs_diff(target) => {
+                    used.extend(uses);
+                    term = t;
+                },
+                _ => break,
+            }
+        }
+        Some(term)
+    }
+}
+
+/// A bounded search imitating a person: only the `effort` most promising
+/// positions are kept after each operation, those holding the most
+/// promising value. A value is promising when it's close to the target or
+/// a round number, a multiple of 10 or 25, which people find easy to
+/// adjust from.
+///
+/// Unlike [`Solver`](../struct.Solver.html), most of the expressions are
+/// never looked at, so a solution it misses may still exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Human {
+    /// Positions kept after each operation, the lookahead of the search
+    pub effort: usize,
+}
+
+impl Default for Human {
+    fn default() -> Human {
+        Human { effort: 8 }
+    }
+}
+
+impl Human {
+    /// How promising `value` looks on the way to `target`, lower is better.
+    fn promise(value: u64, target: u64) -> u64 {
+        let distance = value.abs_diff(target);
+        match value {
+            v if v % 25 == 0 => distance / 4,
+            v if v % 10 == 0 => distance / 2,
+            _ => distance,
+        }
+    }
+}
+
+impl Strategy for Human {
+    fn name(&self) -> &'static str {
+        "human"
+    }
+
+    fn play(&self, numbers: &[u64], target: u64) -> Option<Term> {
+        let start = numbers.iter().map(|&n| number(n)).collect::<Vec<_>>();
+        let mut best = closest(start.iter().cloned(), target)?;
+        let mut positions = [start].to_vec();
+
+        while !positions.is_empty() && best.value != target {
+            // Every position one operation on, with the value it just made
+            let mut next = Vec::new();
+            for terms in &positions {
+                for i in 0..terms.len() {
+                    for j in 0..terms.len() {
+                        if i == j || terms[i].value < terms[j].value {
+                            continue;
+                        }
+                        for &op in Operator::ALL.iter() {
+                            let t = match apply(op, terms[i].clone(), terms[j].clone()) {
+                                Ok(t) => t,
+                                Err(_) => continue,
+                            };
+                            if t.value.abs_diff(target) < best.value.abs_diff(target) {
+                                best = t.clone();
+                            }
+                            let mut rest = terms.iter().enumerate()
+                                .filter(|&(k, _)| k != i && k != j)
+                                .map(|(_, t)| t.clone())
+                                .collect::<Vec<_>>();
+                            rest.push(t);
+                            let promise = rest.iter()
+                                .map(|t| Human::promise(t.value, target)).min()
+                                .expect("a value was just made");
+                            next.push((promise, rest));
+                        }
+                    }
+                }
+            }
+
+            // Stable, so ties go to the position found first
+            next.sort_by_key(|&(promise, _)| promise);
+            next.truncate(self.effort);
+            positions = next.into_iter()
+                .map(|(_, terms)| terms)
+                .filter(|terms| terms.len() > 1)
+                .collect();
+        }
+        Some(best)
+    }
+}
+
+/// Every strategy, in the order they're reported.
+pub fn all() -> Vec<&'static dyn Strategy> {
+    [&Greedy as &dyn Strategy, &RoundThenAdjust, &Human { effort: 8 }].to_vec()
+}
+
+/// The term closest to `target`, the first if tied.
+fn closest<I: IntoIterator<Item = Term>>(terms: I, target: u64) -> Option<Term> {
+    let mut best: Option<Term> = None;
+    for t in terms {
+        if best.as_ref().is_none_or(|b| t.value.abs_diff(target) < b.value.abs_diff(target)) {
+            best = Some(t);
+        }
+    }
+    best
+}
+
+fn number(n: u64) -> Term {
+    Term { expression: None, value: n }
+}
@@ -0,0 +1,173 @@
+//! Solving without blocking the caller, for servers and GUIs which must
+//! stay responsive.
+//!
+//! [`solve_async`](fn.solve_async.html) runs the search on a small pool of
+//! threads, one per core, and returns a future of its
+//! [`Report`](struct.Report.html), so it works with any executor. Searches
+//! beyond the size of the pool wait for a thread. Cancelling the
+//! [`CancellationToken`](struct.CancellationToken.html) stops the search
+//! soon after, and the future resolves with what was found so far.
+//! Dropping the future stops its search the same way.
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use error::Error;
+use solver::{Report, Solver};
+use value::Value;
+
+/// Shared flag asking a search to stop. Clones share the flag, so one can
+/// be kept to cancel while another is handed to the search.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Token which isn't cancelled yet.
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Asks every search holding the token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Outcome of the search and the waker of the task awaiting it.
+#[derive(Debug)]
+struct Shared<V> {
+    result: Option<Result<Report<V>, Error>>,
+    waker: Option<Waker>,
+}
+
+/// Locks `shared`. Neither side panics while holding the lock, but should
+/// that change the outcome is still sound, so poisoning is ignored.
+fn lock<V>(shared: &Mutex<Shared<V>>) -> MutexGuard<'_, Shared<V>> {
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A search run by the pool, which catches its panics.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Queue of the threads running searches, started on first use.
+static POOL: OnceLock<Mutex<Sender<Job>>> = OnceLock::new();
+
+/// Queues `job` to run on the pool.
+fn spawn(job: Job) {
+    let pool = POOL.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        for i in 0..threads {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("countdown-solve-{}", i))
+                .spawn(move || work(&receiver))
+                .expect("can start the solver threads");
+        }
+        Mutex::new(sender)
+    });
+    pool.lock().unwrap_or_else(|e| e.into_inner())
+        .send(job)
+        .expect("the solver threads never stop");
+}
+
+/// Runs the jobs queued on `receiver`, one at a time.
+fn work(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv();
+        match job {
+            Ok(job) => job(),
+            Err(_) => return,
+        }
+    }
+}
+
+/// A search queued on the pool, see [`solve_async`](fn.solve_async.html).
+/// Dropping it stops the search, without cancelling the token it was given.
+#[derive(Debug)]
+pub struct SolveFuture<V = u64> {
+    shared: Arc<Mutex<Shared<V>>>,
+    dropped: CancellationToken,
+}
+
+impl<V> Drop for SolveFuture<V> {
+    fn drop(&mut self) {
+        self.dropped.cancel();
+    }
+}
+
+impl<V> Future for SolveFuture<V> {
+    type Output = Result<Report<V>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut shared = lock(&self.shared);
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+/// Finds all solutions like [`Solver::solve`](struct.Solver.html#method.solve)
+/// on the pool. If `token` is cancelled first, the report holds the
+/// solutions found until then and
+/// [`Stats::cancelled`](struct.Stats.html#structfield.cancelled) is set. If
+/// the search panics, the future resolves to
+/// [`Error::Panicked`](enum.Error.html#variant.Panicked), as long as panics
+/// unwind; built with `panic = "abort"`, as the release profile is, the
+/// process aborts instead.
+pub fn solve_async<V>(numbers: &[V], target: V, token: &CancellationToken) -> SolveFuture<V>
+    where V: Value + Send + Sync + 'static
+{
+    solve_async_with(numbers, target, token, |_| ())
+}
+
+/// [`solve_async`](fn.solve_async.html), calling `configure` on the solver
+/// first to set limits or variant rules.
+pub fn solve_async_with<V, F>(numbers: &[V], target: V, token: &CancellationToken,
+    configure: F) -> SolveFuture<V>
+    where V: Value + Send + Sync + 'static, F: FnOnce(&mut Solver<V>) + Send + 'static
+{
+    let shared = Arc::new(Mutex::new(Shared { result: None, waker: None }));
+    let numbers = numbers.to_vec();
+    let token = token.clone();
+    let dropped = CancellationToken::new();
+    {
+        let shared = shared.clone();
+        let dropped = dropped.clone();
+        spawn(Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(move || {
+                Solver::new(&numbers[..], target).map(|mut solver| {
+                    configure(&mut solver);
+                    solver.set_cancel(move || token.is_cancelled() || dropped.is_cancelled());
+                    solver.solve();
+                    solver.report()
+                })
+            })).unwrap_or(Err(Error::Panicked));
+            let waker = {
+                let mut shared = lock(&shared);
+                shared.result = Some(result);
+                shared.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }));
+    }
+    SolveFuture { shared, dropped }
+}
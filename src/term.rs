@@ -0,0 +1,457 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+use value::Value;
+
+/// The four basic mathematical operations, or one added to the solver
+/// with [`Solver::add_operator`](struct.Solver.html#method.add_operator)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Operator {
+    Addition,
+    Subtraction,
+    Multiplication,
+    Division,
+    /// An [`Operation`](trait.Operation.html) defined outside the crate.
+    /// Terms using one can't be serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Custom),
+}
+
+/// How terms refer to an operation defined outside the crate, taken from
+/// the [`Operation`](trait.Operation.html) itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Custom {
+    pub name: &'static str,
+    pub symbol: &'static str,
+    pub commutative: bool,
+}
+
+impl Custom {
+    /// The reference to `op`.
+    pub fn of<V, O: Operation<V> + ?Sized>(op: &O) -> Custom {
+        Custom { name: op.name(), symbol: op.symbol(), commutative: op.commutative() }
+    }
+}
+
+/// An operation combining two values, as the solver applies them.
+/// [`Operator`](enum.Operator.html) implements it for the four basic
+/// operations, other crates implement it for their own, such as modulo or
+/// exponentiation.
+pub trait Operation<V> {
+    /// Name of the operation, unique among those a solver uses.
+    fn name(&self) -> &'static str;
+
+    /// Symbol used when rendering expressions.
+    fn symbol(&self) -> &'static str;
+
+    /// `a op b`, `None` if the rules don't allow it or it overflows.
+    fn apply(&self, a: &V, b: &V) -> Option<V>;
+
+    /// Whether `a op b` is always `b op a`. The solver tries both orders
+    /// of the operands of an operation which isn't.
+    fn commutative(&self) -> bool;
+}
+
+/// The basic operations. Subtraction and division are only defined with
+/// the larger operand first, and a `Custom` operator has no value here,
+/// only through the `Operation` it refers to.
+impl<V: Value> Operation<V> for Operator {
+    fn name(&self) -> &'static str {
+        Operator::name(self)
+    }
+
+    fn symbol(&self) -> &'static str {
+        Operator::symbol(self)
+    }
+
+    fn apply(&self, a: &V, b: &V) -> Option<V> {
+        match *self {
+            Operator::Addition => a.checked_add(b),
+            Operator::Subtraction if a > b => Some(a.sub(b)),
+            Operator::Multiplication => a.checked_mul(b),
+            Operator::Division if a.is_multiple_of(b) => Some(a.div(b)),
+            Operator::Subtraction | Operator::Division | Operator::Custom(_) => None,
+        }
+    }
+
+    fn commutative(&self) -> bool {
+        match *self {
+            Operator::Addition | Operator::Multiplication => true,
+            Operator::Subtraction | Operator::Division => false,
+            Operator::Custom(c) => c.commutative,
+        }
+    }
+}
+
+impl Operator {
+    /// All operators, in the order the solver tries them.
+    pub const ALL: [Operator; 4] = [
+        Operator::Addition,
+        Operator::Subtraction,
+        Operator::Multiplication,
+        Operator::Division,
+    ];
+
+    /// Looks up an operator by name, `add`, `sub`, `mul` or `div`, or by
+    /// its symbol.
+    pub fn from_name(name: &str) -> Option<Operator> {
+        match name {
+            "add" | "+" => Some(Operator::Addition),
+            "sub" | "-" => Some(Operator::Subtraction),
+            "mul" | "*" => Some(Operator::Multiplication),
+            "div" | "/" => Some(Operator::Division),
+            _ => None,
+        }
+    }
+
+    /// Name of the operator, the one [`from_name`](#method.from_name)
+    /// takes for the basic operations.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Operator::Addition => "add",
+            Operator::Subtraction => "sub",
+            Operator::Multiplication => "mul",
+            Operator::Division => "div",
+            Operator::Custom(c) => c.name,
+        }
+    }
+
+    /// Symbol used when rendering expressions.
+    pub fn symbol(&self) -> &'static str {
+        match *self {
+            Operator::Addition => "+",
+            Operator::Subtraction => "-",
+            Operator::Multiplication => "*",
+            Operator::Division => "/",
+            Operator::Custom(c) => c.symbol,
+        }
+    }
+}
+
+/// Basic mathematical expression with two terms and an operator,
+/// forms a binary expression tree.
+pub type Expr<V = u64> = (Operator, Box<Term<V>>, Box<Term<V>>);
+
+/// Mathematical Term
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Term<V = u64> {
+    /// Expression used to calculate this term.
+    pub expression: Option<Expr<V>>,
+    /// Integer value of the term
+    pub value: V,
+}
+
+/// Walks an expression tree, see [`Term::walk`](struct.Term.html#method.walk).
+/// Every method does nothing by default, so a visitor only implements the
+/// orders it needs.
+pub trait TermVisitor<V = u64> {
+    /// Called on a starting number.
+    fn number(&mut self, _value: &V) {}
+
+    /// Called on an operation before its operands, in pre-order.
+    fn enter(&mut self, _op: Operator, _term: &Term<V>) {}
+
+    /// Called on an operation between its operands, in in-order.
+    fn between(&mut self, _op: Operator, _term: &Term<V>) {}
+
+    /// Called on an operation after its operands, in post-order.
+    fn leave(&mut self, _op: Operator, _term: &Term<V>) {}
+}
+
+/// Counts the uses of an operator.
+struct Count(Operator, usize);
+
+impl<V> TermVisitor<V> for Count {
+    fn enter(&mut self, op: Operator, _term: &Term<V>) {
+        if op == self.0 {
+            self.1 += 1;
+        }
+    }
+}
+
+impl<V> Term<V> {
+    /// Walks the tree depth first, left operand before right, calling
+    /// `visitor` on every term.
+    pub fn walk<T: TermVisitor<V> + ?Sized>(&self, visitor: &mut T) {
+        match self.expression {
+            Some((op, ref a, ref b)) => {
+                visitor.enter(op, self);
+                a.walk(visitor);
+                visitor.between(op, self);
+                b.walk(visitor);
+                visitor.leave(op, self);
+            },
+            None => visitor.number(&self.value),
+        }
+    }
+
+    /// Every term in the tree, each before its operands.
+    pub fn preorder(&self) -> Vec<&Term<V>> {
+        let mut terms = Vec::new();
+        let mut stack = Vec::new();
+        stack.push(self);
+        while let Some(term) = stack.pop() {
+            terms.push(term);
+            if let Some((_, ref a, ref b)) = term.expression {
+                stack.push(b);
+                stack.push(a);
+            }
+        }
+        terms
+    }
+
+    /// Every term in the tree, each after its operands.
+    pub fn postorder(&self) -> Vec<&Term<V>> {
+        // Reversing the pre-order of the mirrored tree
+        let mut terms = Vec::new();
+        let mut stack = Vec::new();
+        stack.push(self);
+        while let Some(term) = stack.pop() {
+            terms.push(term);
+            if let Some((_, ref a, ref b)) = term.expression {
+                stack.push(a);
+                stack.push(b);
+            }
+        }
+        terms.reverse();
+        terms
+    }
+
+    /// Number of operations in the expression tree.
+    pub fn operations(&self) -> usize {
+        match self.expression {
+            Some((_, ref a, ref b)) => 1 + a.operations() + b.operations(),
+            None => 0,
+        }
+    }
+
+    /// Number of times `op` is used in the expression tree.
+    pub fn count(&self, op: Operator) -> usize {
+        let mut count = Count(op, 0);
+        self.walk(&mut count);
+        count.1
+    }
+
+    /// Number of times each operator is used, in the order of
+    /// [`Operator::ALL`](enum.Operator.html#associatedconstant.ALL).
+    pub fn counts(&self) -> [usize; 4] {
+        let mut counts = [0; 4];
+        for (count, &op) in counts.iter_mut().zip(Operator::ALL.iter()) {
+            *count = self.count(op);
+        }
+        counts
+    }
+
+    /// Levels of operations in the expression tree, 0 for a starting
+    /// number.
+    pub fn depth(&self) -> usize {
+        match self.expression {
+            Some((_, ref a, ref b)) => 1 + a.depth().max(b.depth()),
+            None => 0,
+        }
+    }
+
+    /// Number of starting numbers used.
+    pub fn tiles(&self) -> usize {
+        self.operations() + 1
+    }
+
+    /// Starting numbers used in the tree, left to right.
+    pub fn numbers(&self) -> Vec<&V> {
+        self.preorder().into_iter()
+            .filter(|t| t.expression.is_none())
+            .map(|t| &t.value)
+            .collect()
+    }
+}
+
+impl<V: PartialEq> Term<V> {
+    /// Whether every starting number in the tree is one of `numbers`, each
+    /// used no more times than it appears there.
+    pub fn uses_only(&self, numbers: &[V]) -> bool {
+        let mut used = Vec::new();
+        used.resize(numbers.len(), false);
+        self.numbers().into_iter().all(|n| {
+            let free = (0..numbers.len()).find(|&i| !used[i] && numbers[i] == *n);
+            free.map(|i| used[i] = true).is_some()
+        })
+    }
+
+    /// Which of `numbers` the tree uses, bit `i` standing for `numbers[i]`.
+    /// A number given twice is taken from its first unused position, and
+    /// positions past 64 aren't recorded.
+    pub fn usage(&self, numbers: &[V]) -> u64 {
+        let mut usage = 0u64;
+        for n in self.numbers() {
+            let free = (0..numbers.len().min(64))
+                .find(|&i| usage & 1 << i == 0 && numbers[i] == *n);
+            if let Some(i) = free {
+                usage |= 1 << i;
+            }
+        }
+        usage
+    }
+}
+
+impl<V: Ord> Term<V> {
+    /// Largest value calculated by any operation in the tree, or the value
+    /// itself for a starting number.
+    pub fn peak(&self) -> &V {
+        self.operation_peak().unwrap_or(&self.value)
+    }
+
+    /// Largest value calculated by any operation, `None` for a starting
+    /// number.
+    fn operation_peak(&self) -> Option<&V> {
+        match self.expression {
+            Some((_, ref a, ref b)) => {
+                let peak = a.operation_peak().max(b.operation_peak());
+                Some(peak.map_or(&self.value, |p| p.max(&self.value)))
+            },
+            None => None,
+        }
+    }
+
+    /// The term with the operands of every operation which could be
+    /// swapped ordered larger value first, then by the canonical order.
+    /// Expressions which only differ in such swaps have the same canonical
+    /// form.
+    pub fn canonical(self) -> Term<V> {
+        let (op, a, b) = match self.expression {
+            Some((op, a, b)) => (op, a.canonical(), b.canonical()),
+            None => return self,
+        };
+
+        let swappable = match op {
+            Operator::Addition | Operator::Multiplication => true,
+            Operator::Subtraction | Operator::Division => a.value == b.value,
+            Operator::Custom(c) => c.commutative,
+        };
+        let order = b.value.cmp(&a.value).then_with(|| b.cmp(&a));
+        let (a, b) = if swappable && order == Ordering::Greater { (b, a) } else { (a, b) };
+
+        Term { expression: Some((op, Box::new(a), Box::new(b))), value: self.value }
+    }
+
+    /// Compare the structure of two trees, operators in the order of
+    /// `Operator::ALL` and starting numbers by value.
+    fn cmp_structure(&self, other: &Term<V>) -> Ordering {
+        match (&self.expression, &other.expression) {
+            (&Some((op1, ref a1, ref b1)), &Some((op2, ref a2, ref b2))) => {
+                op1.cmp(&op2)
+                    .then_with(|| a1.cmp_structure(a2))
+                    .then_with(|| b1.cmp_structure(b2))
+            },
+            (&Some(_), &None) => Ordering::Greater,
+            (&None, &Some(_)) => Ordering::Less,
+            (&None, &None) => self.value.cmp(&other.value),
+        }
+    }
+}
+
+impl<V: fmt::Display> fmt::Display for Term<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.expression {
+            Some((ref op, ref a, ref b)) => write!(f, "({} {} {})", a, op.symbol(), b),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+impl<V: fmt::Display> Term<V> {
+    /// The expression without spaces or parentheses which the usual
+    /// precedence makes redundant, such as `75*(50+2)`.
+    pub fn compact(&self) -> String {
+        let mut out = String::new();
+        self.write_compact(&mut out).expect("writing to a String can't fail");
+        out
+    }
+
+    fn write_compact(&self, out: &mut String) -> fmt::Result {
+        use core::fmt::Write;
+
+        match self.expression {
+            Some((op, ref a, ref b)) => {
+                // The right operand of - or / keeps its parentheses even at
+                // the same precedence, a - (b + c) isn't a - b + c
+                let same = precedence(op) == b.precedence()
+                    && (op == Operator::Subtraction || op == Operator::Division);
+                a.write_operand(out, precedence(op) > a.precedence())?;
+                out.push_str(op.symbol());
+                b.write_operand(out, precedence(op) > b.precedence() || same)
+            },
+            None => write!(out, "{}", self.value),
+        }
+    }
+
+    fn write_operand(&self, out: &mut String, parenthesize: bool) -> fmt::Result {
+        if parenthesize {
+            out.push('(');
+            self.write_compact(out)?;
+            out.push(')');
+            Ok(())
+        } else {
+            self.write_compact(out)
+        }
+    }
+
+    /// Precedence of the operation making the term, numbers bind tightest.
+    /// Custom operators have no agreed precedence, so they're always
+    /// parenthesized.
+    fn precedence(&self) -> u8 {
+        match self.expression {
+            Some((Operator::Custom(_), _, _)) => 0,
+            Some((op, _, _)) => precedence(op),
+            None => 4,
+        }
+    }
+}
+
+fn precedence(op: Operator) -> u8 {
+    match op {
+        Operator::Addition | Operator::Subtraction => 1,
+        Operator::Multiplication | Operator::Division => 2,
+        Operator::Custom(_) => 3,
+    }
+}
+
+impl<V: PartialEq> PartialEq for Term<V> {
+    fn eq(&self, other: &Term<V>) -> bool {
+        if self.value != other.value {
+            return false;
+        }
+
+        match (&self.expression, &other.expression) {
+            (&Some((ref op1, ref a1, ref b1)),
+             &Some((ref op2, ref a2, ref b2))) =>
+            {
+                op1 == op2 && a1.eq(a2) && b1.eq(b2)
+            },
+            (&None, &None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<V: Eq> Eq for Term<V> {}
+
+impl<V: Ord> PartialOrd for Term<V> {
+    fn partial_cmp(&self, other: &Term<V>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The canonical order of terms, simpler expressions with fewer operations
+/// first, then by structure. Solutions are reported in this order so the
+/// same puzzle always gives the same output.
+impl<V: Ord> Ord for Term<V> {
+    fn cmp(&self, other: &Term<V>) -> Ordering {
+        self.operations().cmp(&other.operations())
+            .then_with(|| self.cmp_structure(other))
+    }
+}
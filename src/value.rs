@@ -0,0 +1,110 @@
+use core::fmt;
+
+/// Integer type terms are calculated in.
+///
+/// Implemented for `u64` (the default), `u128` and, with the `bigint`
+/// feature, `num_bigint::BigUint`. Values are only ever combined largest
+/// to smallest, so `sub` and `div` may assume `self >= rhs`. Addition and
+/// multiplication return `None` when the result doesn't fit.
+pub trait Value: Clone + Ord + fmt::Debug + fmt::Display {
+    /// `self + rhs`, or `None` on overflow
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+    /// `self - rhs`, where `self >= rhs`
+    fn sub(&self, rhs: &Self) -> Self;
+    /// `self * rhs`, or `None` on overflow
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+    /// `self / rhs`, where `rhs` divides `self`
+    fn div(&self, rhs: &Self) -> Self;
+    /// Whether `rhs` divides `self` without remainder
+    fn is_multiple_of(&self, rhs: &Self) -> bool;
+    /// Absolute difference between two values
+    fn distance(&self, other: &Self) -> Self;
+    /// Whether the value is zero
+    fn is_zero(&self) -> bool;
+    /// Which of the primes up to 19 divide the value, bit `i` for the
+    /// `i`-th of [`SMALL_PRIMES`](constant.SMALL_PRIMES.html). None are
+    /// known by default.
+    fn small_primes(&self) -> u8 {
+        0
+    }
+}
+
+/// Primes whose division of values is tracked, to rule out divisions
+/// without dividing.
+pub const SMALL_PRIMES: [u8; 8] = [2, 3, 5, 7, 11, 13, 17, 19];
+
+macro_rules! impl_value {
+    ($($t:ty),*) => {$(
+        impl Value for $t {
+            fn checked_add(&self, rhs: &$t) -> Option<$t> {
+                <$t>::checked_add(*self, *rhs)
+            }
+
+            fn sub(&self, rhs: &$t) -> $t {
+                *self - *rhs
+            }
+
+            fn checked_mul(&self, rhs: &$t) -> Option<$t> {
+                <$t>::checked_mul(*self, *rhs)
+            }
+
+            fn div(&self, rhs: &$t) -> $t {
+                *self / *rhs
+            }
+
+            fn is_multiple_of(&self, rhs: &$t) -> bool {
+                <$t>::is_multiple_of(*self, *rhs)
+            }
+
+            fn distance(&self, other: &$t) -> $t {
+                self.abs_diff(*other)
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == 0
+            }
+
+            fn small_primes(&self) -> u8 {
+                // Divisions by constants, which compile to multiplications
+                let mut primes = 0;
+                for (i, &p) in SMALL_PRIMES.iter().enumerate() {
+                    primes |= u8::from(*self % <$t>::from(p) == 0) << i;
+                }
+                primes
+            }
+        }
+    )*}
+}
+
+impl_value!(u64, u128);
+
+#[cfg(feature = "bigint")]
+impl Value for ::num_bigint::BigUint {
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs)
+    }
+
+    fn sub(&self, rhs: &Self) -> Self {
+        self - rhs
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(self * rhs)
+    }
+
+    fn div(&self, rhs: &Self) -> Self {
+        self / rhs
+    }
+
+    fn is_multiple_of(&self, rhs: &Self) -> bool {
+        !Value::is_zero(rhs) && Value::is_zero(&(self % rhs))
+    }
+
+    fn distance(&self, other: &Self) -> Self {
+        if self > other { self - other } else { other - self }
+    }
+
+    fn is_zero(&self) -> bool {
+        ::num_traits::Zero::is_zero(self)
+    }
+}